@@ -0,0 +1,125 @@
+// תחליף מקומי לערוצי ההתראות - מקבל webhooks ב-HTTP ומיילים ב-SMTP ומדפיס אותם, לבדיקות
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+const USAGE: &str = "Usage: notify_sink [--http 127.0.0.1:9090] [--smtp 127.0.0.1:2525]";
+
+fn flag_value(args: &[String], flag: &str, default: &str) -> Result<String, String> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) => args.get(i + 1).cloned().ok_or_else(|| format!("{} needs a value\n{}", flag, USAGE)),
+        None => Ok(default.to_string()),
+    }
+}
+
+fn handle_http(stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut content_length = 0;
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.trim_end().split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+            headers.push(format!("{}:{}", name, value));
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    println!("🌐 {}", request_line.trim_end());
+    for header in headers {
+        println!("   {}", header);
+    }
+    println!("   {}", String::from_utf8_lossy(&body));
+
+    let mut stream = stream;
+    stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+}
+
+fn handle_smtp(stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+    stream.write_all(b"220 notify_sink ready\r\n")?;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let command = line.trim_end().to_string();
+        let upper = command.to_ascii_uppercase();
+        if upper == "DATA" {
+            stream.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")?;
+            println!("📧 New mail");
+            loop {
+                let mut data = String::new();
+                if reader.read_line(&mut data)? == 0 {
+                    return Ok(());
+                }
+                let data = data.trim_end();
+                if data == "." {
+                    break;
+                }
+                // נקודה מוכפלת בתחילת שורה חוזרת להיות אחת
+                println!("   {}", data.strip_prefix('.').filter(|rest| rest.starts_with('.')).unwrap_or(data));
+            }
+            stream.write_all(b"250 OK: queued\r\n")?;
+        } else if upper == "QUIT" {
+            stream.write_all(b"221 Bye\r\n")?;
+            return Ok(());
+        } else {
+            if upper.starts_with("MAIL FROM") || upper.starts_with("RCPT TO") {
+                println!("📧 {}", command);
+            }
+            stream.write_all(b"250 OK\r\n")?;
+        }
+    }
+}
+
+fn serve(address: String, handler: fn(TcpStream) -> std::io::Result<()>) -> std::io::Result<thread::JoinHandle<()>> {
+    let listener = TcpListener::bind(&address)?;
+    Ok(thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || {
+                if let Err(e) = handler(stream) {
+                    eprintln!("⚠️ Connection failed: {}", e);
+                }
+            });
+        }
+    }))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let (http, smtp) = match (flag_value(&args, "--http", "127.0.0.1:9090"), flag_value(&args, "--smtp", "127.0.0.1:2525")) {
+        (Ok(http), Ok(smtp)) => (http, smtp),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let handles = match (serve(http.clone(), handle_http), serve(smtp.clone(), handle_smtp)) {
+        (Ok(h), Ok(s)) => [h, s],
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("❌ Cannot listen: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("🪝 Webhooks on http://{}/  📧 SMTP on {}", http, smtp);
+    for handle in handles {
+        let _ = handle.join();
+    }
+}
@@ -0,0 +1,115 @@
+// סימולטור חיישנים מקומי - שולח קריאות טמפרטורה ולחות לשרת שרץ, לבדיקות
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{SystemTime, UNIX_EPOCH};
+use chrono::{Local, NaiveDateTime, TimeDelta, Timelike};
+use app::rng::Rng;
+
+// שנה של קריאות כל דקה זה כבר מיליוני שורות
+const MAX_HOURS: i64 = 24 * 366;
+
+const USAGE: &str = "Usage: sensor_sim [--host 127.0.0.1:8080] [--enclosure 3] [--hours 24] [--every 10] [--batch 50]";
+
+fn flag_value<T: std::str::FromStr>(args: &[String], flag: &str, default: T) -> Result<T, String> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) => args
+            .get(i + 1)
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| format!("{} needs a valid value\n{}", flag, USAGE)),
+        None => Ok(default),
+    }
+}
+
+// ערך בסיס עם מחזור יומי ורעש; מדי פעם קפיצה שחורגת מהטווח
+fn simulate(rng: &mut Rng, metric: &str, at: NaiveDateTime) -> f64 {
+    let hour = at.hour() as f64 + at.minute() as f64 / 60.0;
+    let daily = ((hour - 9.0) / 24.0 * std::f64::consts::TAU).sin();
+    let (base, swing, noise, spike) = match metric {
+        "temp" => (28.0, 3.0, 0.5, 8.0),
+        _ => (60.0, -10.0, 2.0, 25.0),
+    };
+    let mut value = base + swing * daily + (rng.next_f64() - 0.5) * 2.0 * noise;
+    if rng.next_f64() < 0.02 {
+        value += if rng.next_f64() < 0.5 { -spike } else { spike };
+    }
+    (value * 10.0).round() / 10.0
+}
+
+fn post_batch(host: &str, body: &str) -> Result<String, String> {
+    let mut stream = TcpStream::connect(host).map_err(|e| format!("Cannot connect to {}: {}", host, e))?;
+    let request = format!(
+        "POST /readings/ HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        host,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(|e| e.to_string())?;
+    let status = response.lines().next().unwrap_or_default().to_string();
+    if !status.contains(" 201 ") {
+        let body = response.split("\r\n\r\n").nth(1).unwrap_or_default();
+        return Err(format!("{} {}", status, body));
+    }
+    Ok(response.split("\r\n\r\n").nth(1).unwrap_or_default().to_string())
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let host: String = flag_value(args, "--host", "127.0.0.1:8080".to_string())?;
+    let enclosure_id: i64 = flag_value(args, "--enclosure", 3)?;
+    let hours: i64 = flag_value(args, "--hours", 24)?;
+    let every: i64 = flag_value(args, "--every", 10)?;
+    let batch_size: usize = flag_value(args, "--batch", 50)?;
+    if every <= 0 || batch_size == 0 {
+        return Err(format!("--every and --batch must be positive\n{}", USAGE));
+    }
+    if !(1..=MAX_HOURS).contains(&hours) {
+        return Err(format!("--hours must be between 1 and {}\n{}", MAX_HOURS, USAGE));
+    }
+
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(1);
+    let mut rng = Rng::new(seed);
+    let now = Local::now().naive_local().with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or_default();
+
+    // שני חיישנים לכל מדד, קריאה כל `every` דקות לאורך `hours` השעות האחרונות
+    let mut readings = Vec::new();
+    let step = TimeDelta::try_minutes(every).ok_or("--every is out of range")?;
+    let mut at = TimeDelta::try_hours(hours)
+        .and_then(|span| now.checked_sub_signed(span))
+        .ok_or("--hours is out of range")?;
+    while at <= now {
+        for (sensor_id, metric) in [("T-1", "temp"), ("T-2", "temp"), ("H-1", "humidity"), ("H-2", "humidity")] {
+            readings.push(format!(
+                r#"{{"enclosure_id":{},"sensor_id":"{}","metric":"{}","value":{},"recorded_at":"{}"}}"#,
+                enclosure_id,
+                sensor_id,
+                metric,
+                simulate(&mut rng, metric, at),
+                at.format("%Y-%m-%dT%H:%M:%S")
+            ));
+        }
+        at = match at.checked_add_signed(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    for chunk in readings.chunks(batch_size) {
+        let summary = post_batch(&host, &format!(r#"{{"readings":[{}]}}"#, chunk.join(",")))?;
+        println!("📤 {} readings -> {}", chunk.len(), summary.chars().take(120).collect::<String>());
+    }
+    println!("✅ Sent {} readings for enclosure {}", readings.len(), enclosure_id);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
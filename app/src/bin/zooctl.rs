@@ -0,0 +1,423 @@
+// כלי ניהול למסד הנתונים של גן החיות - עובד ישירות על zoo.db, בלי השרת
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use chrono::NaiveDate;
+use futures_util::StreamExt;
+use serde::Serialize;
+use sqlx::{sqlite::{SqliteConnectOptions, SqliteRow}, SqlitePool, Row};
+use app::cli::{self, flag_value};
+use app::controllers::animal_controller::{animal_from_row, ANIMAL_COLUMNS};
+use app::controllers::enclosure_controller::enclosure_from_row;
+use app::controllers::export_controller::{build_bundle, table_csv};
+use app::controllers::import_controller::{import_entity, import_format};
+use app::controllers::maintenance_controller::out_of_service_sql;
+use app::controllers::staff_controller::{insert_staff, staff_from_row};
+use app::db::{connect, init_db, migrate, references_to, schema_version, DB_PATH, SCHEMA_VERSION};
+use app::fixtures::{generate_fixtures, FixtureOptions, FixtureSummary, PRESETS};
+use app::models::animal::Animal;
+use app::models::enclosure::Enclosure;
+use app::models::staff::{CreateStaffRequest, Staff};
+
+const USAGE: &str = "Usage: zooctl <command> [--json]
+
+Database:
+  zooctl migrate
+  zooctl seed <dir>                 imports enclosures, staff and animals (.csv or .jsonl) from <dir>
+  zooctl seed --preset <small|medium|large> [--seed <n>] [--days <n>] [--until <date>]
+              [--enclosures <n>] [--staff <n>] [--animals <n>] [--out <new file>]
+  zooctl import <animals|staff|enclosures> <file> [--format csv|jsonl] [--dry-run]
+  zooctl export <file.zip>          every table as CSV in one zip
+  zooctl export <file.csv> --table <table>
+  zooctl backup [--dir <dir>]
+  zooctl backups [--dir <dir>]
+  zooctl verify-backup <file>
+  zooctl restore <file>             (stop the server first)
+  zooctl studbook-export <file> [--species <species>] [--format csv|xml]
+  zooctl studbook-import <file> [--format csv|xml]
+
+Staff:
+  zooctl staff [--role <role>]
+  zooctl staff-add <first_name> <last_name> <role> <phone>
+  zooctl staff-remove <id>
+
+Queries:
+  zooctl animals [--enclosure <id>] [--species <species>] [--status <status>]
+  zooctl enclosures";
+
+// פקודות שכבר קיימות ב-CLI של השרת ועוברות אליו כמו שהן
+const SHARED_COMMANDS: [&str; 7] = [
+    "import",
+    "backup",
+    "backups",
+    "verify-backup",
+    "restore",
+    "studbook-export",
+    "studbook-import",
+];
+
+// קבצי seed לפי סדר התלות: חיות צריכות מתחם קיים
+const SEED_ENTITIES: [&str; 3] = ["enclosures", "staff", "animals"];
+
+fn db_error(e: sqlx::Error) -> String {
+    format!("Database error: {}", e)
+}
+
+// טבלה מיושרת לטרמינל; רוחב לפי תווים ולא בתים, בשביל עברית
+fn print_table(out: &mut impl Write, headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut line = |cells: Vec<String>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect();
+        let _ = writeln!(out, "{}", padded.join("  ").trim_end());
+    };
+    line(headers.iter().map(|h| h.to_string()).collect());
+    line(widths.iter().map(|width| "-".repeat(*width)).collect());
+    for row in rows {
+        line(row.clone());
+    }
+}
+
+// שגיאות כתיבה לא נחשבות: פלט שנחתך באמצע (למשל `| head`) לא צריך להפיל את הפקודה
+fn print_records<T: Serialize>(records: &[T], json: bool, headers: &[&str], to_cells: fn(&T) -> Vec<String>) -> Result<(), String> {
+    let mut out = std::io::stdout().lock();
+    if json {
+        let _ = writeln!(out, "{}", serde_json::to_string_pretty(records).map_err(|e| e.to_string())?);
+    } else {
+        print_table(&mut out, headers, &records.iter().map(to_cells).collect::<Vec<_>>());
+    }
+    Ok(())
+}
+
+async fn list_animals(pool: &SqlitePool, args: &[String], json: bool) -> Result<(), String> {
+    let enclosure = match flag_value(args, "--enclosure") {
+        Some(id) => Some(id.parse::<i64>().map_err(|_| format!("Invalid enclosure id {}", id))?),
+        None => None,
+    };
+    let species = flag_value(args, "--species");
+    let status = flag_value(args, "--status");
+    let animals: Vec<Animal> = sqlx::query(&format!(
+        "SELECT {} FROM animals
+        WHERE (? IS NULL OR enclosure_id = ?) AND (? IS NULL OR species = ?) AND (? IS NULL OR status = ?)
+        ORDER BY id",
+        ANIMAL_COLUMNS
+    ))
+    .bind(enclosure)
+    .bind(enclosure)
+    .bind(species)
+    .bind(species)
+    .bind(status)
+    .bind(status)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?
+    .iter()
+    .map(animal_from_row)
+    .collect();
+
+    print_records(&animals, json, &["id", "name", "species", "sex", "age", "enclosure", "status"], |a| {
+        vec![
+            a.id.to_string(),
+            a.name.clone(),
+            a.species.clone(),
+            a.sex.clone(),
+            a.age.to_string(),
+            a.enclosure_id.to_string(),
+            a.status.clone(),
+        ]
+    })
+}
+
+#[derive(Serialize)]
+struct EnclosureOccupancy {
+    #[serde(flatten)]
+    enclosure: Enclosure,
+    residents: i64,
+}
+
+fn occupancy_from_row(row: &SqliteRow) -> EnclosureOccupancy {
+    EnclosureOccupancy { enclosure: enclosure_from_row(row), residents: row.get("residents") }
+}
+
+async fn list_enclosures(pool: &SqlitePool, json: bool) -> Result<(), String> {
+    let enclosures: Vec<EnclosureOccupancy> = sqlx::query(&format!(
+        "SELECT e.id, e.name, e.enclosure_type, e.capacity, e.is_quarantine, {} AS out_of_service,
+            (SELECT COUNT(*) FROM animals a WHERE a.enclosure_id = e.id AND a.status = 'resident') AS residents
+        FROM enclosures e ORDER BY e.id",
+        out_of_service_sql("e.id")
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?
+    .iter()
+    .map(occupancy_from_row)
+    .collect();
+
+    print_records(&enclosures, json, &["id", "name", "type", "residents", "capacity", "quarantine", "out of service"], |o| {
+        let e = &o.enclosure;
+        vec![
+            e.id.to_string(),
+            e.name.clone(),
+            e.enclosure_type.clone(),
+            o.residents.to_string(),
+            e.capacity.to_string(),
+            if e.is_quarantine { "yes" } else { "" }.to_string(),
+            if e.out_of_service { "yes" } else { "" }.to_string(),
+        ]
+    })
+}
+
+async fn list_staff(pool: &SqlitePool, args: &[String], json: bool) -> Result<(), String> {
+    let role = flag_value(args, "--role");
+    let staff: Vec<Staff> = sqlx::query("SELECT id, first_name, last_name, role, phone FROM staff WHERE ? IS NULL OR role = ? ORDER BY id")
+        .bind(role)
+        .bind(role)
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?
+        .iter()
+        .map(staff_from_row)
+        .collect();
+
+    print_records(&staff, json, &["id", "first name", "last name", "role", "phone"], |s| {
+        vec![s.id.to_string(), s.first_name.clone(), s.last_name.clone(), s.role.clone(), s.phone.clone()]
+    })
+}
+
+async fn add_staff(pool: &SqlitePool, args: &[String], json: bool) -> Result<(), String> {
+    let [first_name, last_name, role, phone] = match args.get(1..5) {
+        Some([first_name, last_name, role, phone]) => [first_name, last_name, role, phone].map(String::clone),
+        _ => return Err(USAGE.to_string()),
+    };
+    let mut conn = pool.acquire().await.map_err(db_error)?;
+    let staff = insert_staff(&mut conn, &CreateStaffRequest { first_name, last_name, role, phone })
+        .await
+        .map_err(db_error)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&staff).map_err(|e| e.to_string())?);
+    } else {
+        println!("👤 Added {} {} as staff #{}", staff.first_name, staff.last_name, staff.id);
+    }
+    Ok(())
+}
+
+async fn remove_staff(pool: &SqlitePool, args: &[String]) -> Result<(), String> {
+    let id: i64 = args.get(1).and_then(|id| id.parse().ok()).ok_or(USAGE)?;
+    let mut conn = pool.acquire().await.map_err(db_error)?;
+    // כמו ב-API: עובד שרשום בהאכלות, משמרות או משימות נשאר בהיסטוריה
+    let references = references_to(&mut conn, "staff", id).await.map_err(db_error)?;
+    if !references.is_empty() {
+        return Err(format!("Staff #{} is still referenced by {}", id, references.join(", ")));
+    }
+    let removed = sqlx::query("DELETE FROM staff WHERE id = ?")
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(db_error)?
+        .rows_affected();
+    if removed == 0 {
+        return Err(format!("Staff #{} not found", id));
+    }
+    println!("👤 Removed staff #{}", id);
+    Ok(())
+}
+
+async fn seed_from_dir(pool: &SqlitePool, dir: &Path) -> Result<(), String> {
+    let mut seeded = 0;
+    for entity in SEED_ENTITIES {
+        let Some(file) = ["csv", "jsonl"]
+            .iter()
+            .map(|extension| dir.join(format!("{}.{}", entity, extension)))
+            .find(|file| file.is_file())
+        else {
+            continue;
+        };
+        let name = file.display().to_string();
+        let data = std::fs::read_to_string(&file).map_err(|e| format!("Cannot read {}: {}", name, e))?;
+        let format = import_format(None, &name)?;
+        let summary = import_entity(pool, entity, &data, format, false).await.map_err(db_error)?.summary;
+        for error in &summary.errors {
+            eprintln!("⚠️ {} row {}: {}", name, error.row, error.error);
+        }
+        if !summary.errors.is_empty() {
+            return Err(format!("{} has invalid rows, stopped before importing it", name));
+        }
+        println!("🌱 Seeded {} {} from {}", summary.imported, entity, name);
+        seeded += 1;
+    }
+    if seeded == 0 {
+        return Err(format!(
+            "No seed files in {} (expected {})",
+            dir.display(),
+            SEED_ENTITIES.map(|entity| format!("{}.csv", entity)).join(", ")
+        ));
+    }
+    Ok(())
+}
+
+fn parse_flag<T: FromStr>(args: &[String], flag: &str) -> Result<Option<T>, String> {
+    match flag_value(args, flag) {
+        Some(value) => value.parse().map(Some).map_err(|_| format!("Invalid value for {}: {}", flag, value)),
+        None => Ok(None),
+    }
+}
+
+fn fixture_options(args: &[String]) -> Result<FixtureOptions, String> {
+    let preset = flag_value(args, "--preset").unwrap_or("small");
+    let mut options = FixtureOptions::preset(preset)
+        .ok_or_else(|| format!("Unknown preset {} (use {})", preset, PRESETS.join(", ")))?;
+    if let Some(seed) = parse_flag(args, "--seed")? {
+        options.seed = seed;
+    }
+    if let Some(enclosures) = parse_flag(args, "--enclosures")? {
+        options.enclosures = enclosures;
+    }
+    if let Some(staff) = parse_flag(args, "--staff")? {
+        options.staff = staff;
+    }
+    if let Some(animals) = parse_flag(args, "--animals")? {
+        options.animals = animals;
+    }
+    if let Some(days) = parse_flag(args, "--days")? {
+        options.days = days;
+    }
+    if let Some(until) = parse_flag::<NaiveDate>(args, "--until")? {
+        options.until = until;
+    }
+    Ok(options)
+}
+
+fn print_fixture_summary(summary: &FixtureSummary, json: bool) -> Result<(), String> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(summary).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    let rows = [
+        ("enclosures", summary.enclosures),
+        ("species ranges", summary.species_ranges),
+        ("staff", summary.staff),
+        ("keeper assignments", summary.keeper_assignments),
+        ("shifts", summary.shifts),
+        ("animals", summary.animals),
+        ("feedings", summary.feedings),
+        ("medical treatments", summary.medical_treatments),
+    ];
+    print_table(
+        &mut std::io::stdout().lock(),
+        &["table", "rows"],
+        &rows.map(|(table, count)| vec![table.to_string(), count.to_string()]),
+    );
+    Ok(())
+}
+
+// מסד חדש בקובץ נפרד, כדי לא לגעת ב-zoo.db; אחר כך אפשר להעלות אותו עם restore
+async fn seed_new_database(file: &str, args: &[String], json: bool) -> Result<(), String> {
+    let options = fixture_options(args)?;
+    options.validate()?;
+    if Path::new(file).exists() {
+        return Err(format!("{} already exists", file));
+    }
+    let pool = SqlitePool::connect_with(SqliteConnectOptions::new().filename(file).create_if_missing(true))
+        .await
+        .map_err(|e| format!("Cannot create {}: {}", file, e))?;
+    migrate(&pool).await.map_err(db_error)?;
+    let summary = generate_fixtures(&pool, &options).await?;
+    pool.close().await;
+    print_fixture_summary(&summary, json)?;
+    println!("🌱 Generated {} (load it with `zooctl restore {}`)", file, file);
+    Ok(())
+}
+
+async fn seed(pool: &SqlitePool, args: &[String], json: bool) -> Result<(), String> {
+    if flag_value(args, "--preset").is_none() {
+        return seed_from_dir(pool, Path::new(args.get(1).ok_or(USAGE)?)).await;
+    }
+    let summary = generate_fixtures(pool, &fixture_options(args)?).await?;
+    print_fixture_summary(&summary, json)
+}
+
+async fn export(pool: &SqlitePool, args: &[String]) -> Result<(), String> {
+    let file = args.get(1).ok_or(USAGE)?;
+    let mut data = match flag_value(args, "--table") {
+        Some(table) => {
+            // שם הטבלה נכנס ל-SQL, אז רק טבלאות שקיימות באמת
+            let exists: Option<String> = sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(table)
+                .fetch_optional(pool)
+                .await
+                .map_err(db_error)?;
+            if exists.is_none() {
+                return Err(format!("No table named {}", table));
+            }
+            table_csv(pool, table)
+        }
+        None => build_bundle(pool),
+    };
+
+    let mut out = std::fs::File::create(file).map_err(|e| format!("Cannot write {}: {}", file, e))?;
+    let mut written = 0;
+    while let Some(chunk) = data.next().await {
+        let chunk = chunk.map_err(|e| {
+            // ייצוא שנקטע באמצע לא נשאר על הדיסק כאילו הוא שלם
+            let _ = std::fs::remove_file(file);
+            e.to_string()
+        })?;
+        out.write_all(&chunk).map_err(|e| format!("Cannot write {}: {}", file, e))?;
+        written += chunk.len();
+    }
+    println!("📤 Exported {} bytes to {}", written, file);
+    Ok(())
+}
+
+async fn run(args: &[String]) -> Result<(), String> {
+    let command = args.first().ok_or(USAGE)?.as_str();
+    let json = args.iter().any(|a| a == "--json");
+
+    if command == "migrate" {
+        let pool = init_db().await.map_err(db_error)?;
+        println!("🗄️ {} is at schema version {}", DB_PATH, schema_version(&pool).await.map_err(db_error)?);
+        return Ok(());
+    }
+    if let ("seed", Some(file)) = (command, flag_value(args, "--out")) {
+        return seed_new_database(file, args, json).await;
+    }
+
+    // שאר הפקודות לא משנות מבנה, אז מסד שלא עבר migrate נעצר כאן במקום להיכשל באמצע
+    let pool = connect().await.map_err(|e| format!("Cannot open {}: {}", DB_PATH, e))?;
+    let version = schema_version(&pool).await.map_err(db_error)?;
+    if version != SCHEMA_VERSION {
+        return Err(format!(
+            "{} is at schema version {} but zooctl expects {}; run `zooctl migrate`",
+            DB_PATH, version, SCHEMA_VERSION
+        ));
+    }
+
+    match command {
+        command if SHARED_COMMANDS.contains(&command) => cli::run(&pool, args).await,
+        "seed" => seed(&pool, args, json).await,
+        "export" => export(&pool, args).await,
+        "animals" => list_animals(&pool, args, json).await,
+        "enclosures" => list_enclosures(&pool, json).await,
+        "staff" => list_staff(&pool, args, json).await,
+        "staff-add" => add_staff(&pool, args, json).await,
+        "staff-remove" => remove_staff(&pool, args).await,
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args).await {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
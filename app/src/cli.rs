@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+use sqlx::SqlitePool;
+use crate::controllers::backup_controller::{create_backup, list_backups, prune_backups, restore_backup, verify_backup};
+use crate::controllers::settings_controller::{get_setting, BACKUP_DIR, BACKUP_KEEP};
+use crate::controllers::import_controller::{import_entity, import_format, IMPORT_ENTITIES};
+use crate::controllers::studbook_controller::{
+    build_studbook, import_studbook, parse_studbook, studbook_format, studbook_to_csv, studbook_to_xml,
+};
+
+const USAGE: &str = "Usage:
+  app studbook-export <file> [--species <species>] [--format csv|xml]
+  app studbook-import <file> [--format csv|xml]
+  app import <animals|staff|enclosures> <file> [--format csv|jsonl] [--dry-run]
+  app backup [--dir <dir>]
+  app backups [--dir <dir>]
+  app verify-backup <file>
+  app restore <file>   (stop the server first)";
+
+// ערך של דגל כמו --species Lion
+pub fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+}
+
+// תיקיית הגיבויים מהדגל, ואם אין - מההגדרות
+async fn backup_dir(pool: &SqlitePool, args: &[String]) -> Result<PathBuf, String> {
+    if let Some(dir) = flag_value(args, "--dir") {
+        return Ok(PathBuf::from(dir));
+    }
+    let mut conn = pool.acquire().await.map_err(|e| format!("Database error: {}", e))?;
+    let dir = get_setting(&mut conn, BACKUP_DIR).await.map_err(|e| format!("Database error: {}", e))?;
+    Ok(PathBuf::from(dir))
+}
+
+/// Runs a one-off command instead of the server; returns an error message on failure.
+pub async fn run(pool: &SqlitePool, args: &[String]) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        Some("studbook-export") => {
+            let file = args.get(1).ok_or(USAGE)?;
+            let entries = build_studbook(pool, flag_value(args, "--species"))
+                .await
+                .map_err(|e| format!("Database error: {}", e))?;
+            let output = match flag_value(args, "--format").unwrap_or("csv") {
+                "csv" => studbook_to_csv(&entries).map_err(|e| format!("CSV error: {}", e))?,
+                "xml" => studbook_to_xml(&entries),
+                _ => return Err("Invalid format (use csv or xml)".to_string()),
+            };
+            std::fs::write(file, output).map_err(|e| format!("Cannot write {}: {}", file, e))?;
+            println!("📤 Exported {} studbook records to {}", entries.len(), file);
+            Ok(())
+        }
+        Some("studbook-import") => {
+            let file = args.get(1).ok_or(USAGE)?;
+            let data = std::fs::read_to_string(file).map_err(|e| format!("Cannot read {}: {}", file, e))?;
+            let rows = parse_studbook(&data, studbook_format(flag_value(args, "--format"), file)?)?;
+            let summary = import_studbook(pool, &rows)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?
+                .summary;
+            println!("📥 Imported {} studbook records, linked {} parents", summary.records, summary.parents_linked);
+            Ok(())
+        }
+        Some("import") => {
+            let (Some(entity), Some(file)) = (args.get(1), args.get(2)) else {
+                return Err(USAGE.to_string());
+            };
+            if !IMPORT_ENTITIES.contains(&entity.as_str()) {
+                return Err(format!("Cannot import {} (use {})", entity, IMPORT_ENTITIES.join(", ")));
+            }
+            let format = import_format(flag_value(args, "--format"), file)?;
+            let dry_run = args.iter().any(|a| a == "--dry-run");
+            let data = std::fs::read_to_string(file).map_err(|e| format!("Cannot read {}: {}", file, e))?;
+            let summary = import_entity(pool, entity, &data, format, dry_run)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?
+                .summary;
+
+            for error in &summary.errors {
+                eprintln!("⚠️ Row {}: {}", error.row, error.error);
+            }
+            if !summary.errors.is_empty() {
+                return Err(format!("{} of {} rows are invalid, nothing was imported", summary.errors.len(), summary.rows));
+            }
+            if dry_run {
+                println!("🔎 All {} {} rows are valid (dry run, nothing was imported)", summary.rows, entity);
+            } else {
+                println!("📥 Imported {} {}", summary.imported, entity);
+            }
+            Ok(())
+        }
+        Some("backup") => {
+            let dir = backup_dir(pool, args).await?;
+            let backup = create_backup(pool, &dir).await?;
+            let mut conn = pool.acquire().await.map_err(|e| format!("Database error: {}", e))?;
+            let keep = get_setting(&mut conn, BACKUP_KEEP).await.ok().and_then(|v| v.parse().ok()).unwrap_or(7);
+            let pruned = prune_backups(&dir, keep);
+            println!("💾 Backup written to {} ({} bytes)", dir.join(&backup.file).display(), backup.size_bytes);
+            if pruned > 0 {
+                println!("🧹 Removed {} old backups (keeping {})", pruned, keep);
+            }
+            Ok(())
+        }
+        Some("backups") => {
+            let dir = backup_dir(pool, args).await?;
+            let backups = list_backups(&dir);
+            if backups.is_empty() {
+                println!("No backups in {}", dir.display());
+            }
+            for backup in backups {
+                println!("{}  {}  {} bytes", backup.file, backup.created_at.format("%Y-%m-%d %H:%M:%S"), backup.size_bytes);
+            }
+            Ok(())
+        }
+        Some("verify-backup") => {
+            let file = args.get(1).ok_or(USAGE)?;
+            let check = verify_backup(Path::new(file)).await?;
+            println!("Integrity: {}", check.integrity.join("; "));
+            println!("Schema version: {} (this app is at {})", check.schema_version, check.expected_schema_version);
+            if !check.ok {
+                return Err(format!("{} cannot be restored", file));
+            }
+            println!("✅ {} is a valid backup", file);
+            Ok(())
+        }
+        Some("restore") => {
+            let file = args.get(1).ok_or(USAGE)?;
+            let safety = restore_backup(pool, Path::new(file)).await?;
+            println!("💾 Previous database saved as {}", safety.file);
+            println!("♻️ Restored database from {}", file);
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
@@ -0,0 +1,491 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqlitePool, Row};
+use chrono::{Local, TimeDelta};
+use crate::controllers::assignment_controller::load_active_keepers;
+use crate::controllers::certification_controller::{find_expiring_certifications, DEFAULT_EXPIRING_WITHIN_DAYS};
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::controllers::feeding_controller::find_unfed_animals;
+use crate::controllers::measurement_controller::find_weight_loss_alerts;
+use crate::controllers::settings_controller::{get_setting, ALERT_INTERVAL_MINUTES, SMTP_FROM, SMTP_SERVER};
+use crate::controllers::task_controller::STAFF_HEADER;
+use crate::models::alert::{Alert, AlertChannel, AlertListQuery, AlertRunSummary, CreateAlertChannelRequest};
+use crate::notify::{has_control_chars, is_webhook_url, notifier_for, LogNotifier, Notifier, WEBHOOK_URL_RULE};
+
+const ALERT_COLUMNS: &str =
+    "id, rule, alert_key, severity, message, status, created_at, last_seen_at, acknowledged_at, acknowledged_by, resolved_at, assignee_id";
+const CHANNEL_COLUMNS: &str = "id, kind, target, min_severity, enabled, staff_id";
+const SEVERITIES: [&str; 3] = ["info", "warning", "critical"];
+const CHANNEL_KINDS: [&str; 3] = ["log", "webhook", "smtp"];
+const DEFAULT_INTERVAL_MINUTES: u64 = 5;
+
+// הקריטיות קודם
+const SEVERITY_ORDER: &str = "CASE severity WHEN 'critical' THEN 0 WHEN 'warning' THEN 1 ELSE 2 END";
+
+// תנאי שמתקיים כרגע; המפתח מזהה אותו בין ריצות כדי לא לפתוח התראה כפולה
+struct Condition {
+    rule: &'static str,
+    key: String,
+    severity: &'static str,
+    message: String,
+    // השומר הראשון שאחראי על החיה או המתחם, או העובד שההסמכה שלו פגה
+    assignee_id: Option<i64>,
+}
+
+fn severity_rank(severity: &str) -> usize {
+    SEVERITIES.iter().position(|s| *s == severity).unwrap_or_default()
+}
+
+fn alert_from_row(row: &SqliteRow) -> Alert {
+    Alert {
+        id: row.get("id"),
+        rule: row.get("rule"),
+        alert_key: row.get("alert_key"),
+        severity: row.get("severity"),
+        message: row.get("message"),
+        status: row.get("status"),
+        created_at: row.get("created_at"),
+        last_seen_at: row.get("last_seen_at"),
+        acknowledged_at: row.get("acknowledged_at"),
+        acknowledged_by: row.get("acknowledged_by"),
+        resolved_at: row.get("resolved_at"),
+        assignee_id: row.get("assignee_id"),
+    }
+}
+
+fn channel_from_row(row: &SqliteRow) -> AlertChannel {
+    AlertChannel {
+        id: row.get("id"),
+        kind: row.get("kind"),
+        target: row.get("target"),
+        min_severity: row.get("min_severity"),
+        enabled: row.get("enabled"),
+        staff_id: row.get("staff_id"),
+    }
+}
+
+async fn evaluate_rules(pool: &SqlitePool) -> Result<Vec<Condition>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let today = Local::now().date_naive();
+    let keepers = load_active_keepers(&mut conn, today).await?;
+    let mut conditions = Vec::new();
+
+    // האכלה באיחור לפי כללי ההאכלה
+    for unfed in find_unfed_animals(&mut conn, &keepers).await? {
+        let message = match unfed.last_feeding_time {
+            Some(last) => format!(
+                "{} ({}) has not been fed since {} (max interval {}h)",
+                unfed.name, unfed.species, last, unfed.max_interval_hours
+            ),
+            None => format!("{} ({}) has no recorded feeding", unfed.name, unfed.species),
+        };
+        conditions.push(Condition {
+            rule: "overdue_feeding",
+            key: format!("overdue_feeding:animal:{}", unfed.animal_id),
+            severity: "warning",
+            message,
+            assignee_id: unfed.keeper_ids.first().copied(),
+        });
+    }
+
+    // מתחם עם יותר חיות מהקיבולת שלו
+    let crowded = sqlx::query("SELECT e.id, e.name, e.capacity, COUNT(a.id) AS animals
+        FROM enclosures e
+        JOIN animals a ON a.enclosure_id = e.id AND a.status = 'resident'
+        GROUP BY e.id
+        HAVING COUNT(a.id) > e.capacity")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in crowded {
+        let id: i64 = row.get("id");
+        let name: String = row.get("name");
+        let capacity: i64 = row.get("capacity");
+        let animals: i64 = row.get("animals");
+        conditions.push(Condition {
+            rule: "enclosure_over_capacity",
+            key: format!("enclosure_over_capacity:enclosure:{}", id),
+            severity: "critical",
+            message: format!("{} holds {} animals but its capacity is {}", name, animals, capacity),
+            assignee_id: keepers.of_enclosure(id).first().copied(),
+        });
+    }
+
+    // הסמכות שפגו או יפוגו בקרוב
+    let horizon = TimeDelta::try_days(DEFAULT_EXPIRING_WITHIN_DAYS)
+        .and_then(|delta| today.checked_add_signed(delta))
+        .unwrap_or(today);
+    for expiring in find_expiring_certifications(&mut conn, today, horizon).await? {
+        let certification = &expiring.certification;
+        let expiry = certification.expiry_date.map(|date| date.to_string()).unwrap_or_default();
+        conditions.push(Condition {
+            rule: "expiring_certification",
+            key: format!("expiring_certification:certification:{}", certification.id),
+            severity: if expiring.expired { "warning" } else { "info" },
+            message: format!(
+                "{}'s {} certification {} on {}",
+                expiring.staff_name,
+                certification.name,
+                if expiring.expired { "expired" } else { "expires" },
+                expiry
+            ),
+            assignee_id: Some(certification.staff_id),
+        });
+    }
+    drop(conn);
+
+    // ירידה במשקל מעבר לסף של המין
+    for loss in find_weight_loss_alerts(pool).await? {
+        conditions.push(Condition {
+            rule: "weight_loss",
+            key: format!("weight_loss:animal:{}", loss.animal_id),
+            severity: "critical",
+            message: format!(
+                "{} ({}) lost {:.1}% of body weight within {} days (limit {}%)",
+                loss.name, loss.species, -loss.change_pct, loss.window_days, loss.max_loss_pct
+            ),
+            assignee_id: loss.keeper_ids.first().copied(),
+        });
+    }
+
+    Ok(conditions)
+}
+
+/// Evaluates every rule once: opens alerts for new conditions, refreshes active ones and resolves the rest.
+pub async fn run_alert_rules(pool: &SqlitePool) -> Result<AlertRunSummary, sqlx::Error> {
+    let conditions = evaluate_rules(pool).await?;
+    let now = Local::now().naive_local();
+    let mut tx = pool.begin().await?;
+
+    let mut raised = Vec::new();
+    for condition in &conditions {
+        let refreshed = sqlx::query("UPDATE alerts SET last_seen_at = ?, severity = ?, message = ?, assignee_id = ?
+            WHERE alert_key = ? AND status != 'resolved' RETURNING id")
+            .bind(now)
+            .bind(condition.severity)
+            .bind(&condition.message)
+            .bind(condition.assignee_id)
+            .bind(&condition.key)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if refreshed.is_some() {
+            continue;
+        }
+        // ריצה מקבילה (המנוע ו-POST /alerts/run) אולי כבר פתחה את ההתראה; אז היא פשוט עדיין פעילה
+        let inserted = sqlx::query(&format!(
+            "INSERT INTO alerts (rule, alert_key, severity, message, status, created_at, last_seen_at, assignee_id)
+            VALUES (?, ?, ?, ?, 'open', ?, ?, ?) ON CONFLICT DO NOTHING RETURNING {}",
+            ALERT_COLUMNS
+        ))
+        .bind(condition.rule)
+        .bind(&condition.key)
+        .bind(condition.severity)
+        .bind(&condition.message)
+        .bind(now)
+        .bind(now)
+        .bind(condition.assignee_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(row) = inserted {
+            raised.push(alert_from_row(&row));
+        }
+    }
+
+    // התראה שהתנאי שלה כבר לא מתקיים נסגרת אוטומטית
+    let auto_resolved = sqlx::query("UPDATE alerts SET status = 'resolved', resolved_at = ? WHERE status != 'resolved' AND last_seen_at < ?")
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+
+    deliver(pool, &raised).await?;
+    Ok(AlertRunSummary {
+        raised: raised.len(),
+        still_active: conditions.len() - raised.len(),
+        auto_resolved,
+    })
+}
+
+// כל ערוץ פעיל מקבל התראות מהחומרה המינימלית שלו ומעלה, וערוץ של עובד רק את אלה שהוא אחראי עליהן;
+// התראה שאף ערוץ לא קיבל נרשמת ללוג
+async fn deliver(pool: &SqlitePool, alerts: &[Alert]) -> Result<(), sqlx::Error> {
+    if alerts.is_empty() {
+        return Ok(());
+    }
+    let mut conn = pool.acquire().await?;
+    let smtp_server = get_setting(&mut conn, SMTP_SERVER).await?;
+    let smtp_from = get_setting(&mut conn, SMTP_FROM).await?;
+    let channels: Vec<AlertChannel> = sqlx::query(&format!("SELECT {} FROM alert_channels ORDER BY id", CHANNEL_COLUMNS))
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(channel_from_row)
+        .collect();
+    drop(conn);
+
+    let mut routed_any = vec![false; alerts.len()];
+    for channel in channels.iter().filter(|channel| channel.enabled) {
+        let notifier = notifier_for(channel, &smtp_server, &smtp_from);
+        for (index, alert) in alerts.iter().enumerate() {
+            if severity_rank(&alert.severity) < severity_rank(&channel.min_severity)
+                || channel.staff_id.is_some_and(|staff_id| alert.assignee_id != Some(staff_id))
+            {
+                continue;
+            }
+            routed_any[index] = true;
+            if let Err(e) = notifier.send(alert).await {
+                eprintln!("⚠️ Alert #{} not delivered via {} channel #{}: {}", alert.id, channel.kind, channel.id, e);
+            }
+        }
+    }
+    for (alert, _) in alerts.iter().zip(routed_any).filter(|(_, routed)| !routed) {
+        let _ = LogNotifier.send(alert).await;
+    }
+    Ok(())
+}
+
+/// Background task started with the server: runs the rules every `alert_interval_minutes`.
+pub async fn run_alert_engine(pool: SqlitePool) {
+    loop {
+        // כל ריצה במשימה משלה, כך שגם פאניקה בכלל אחד רק נרשמת ללוג והמנוע ממשיך
+        let run = tokio::spawn({
+            let pool = pool.clone();
+            async move { run_alert_rules(&pool).await }
+        });
+        match run.await {
+            Ok(Ok(summary)) if summary.raised > 0 || summary.auto_resolved > 0 => println!(
+                "🔔 Alert engine: {} raised, {} auto-resolved",
+                summary.raised, summary.auto_resolved
+            ),
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("❌ Alert engine failed: {}", e),
+            Err(e) => eprintln!("❌ Alert engine run aborted: {}", e),
+        }
+
+        let minutes = match pool.acquire().await {
+            Ok(mut conn) => get_setting(&mut conn, ALERT_INTERVAL_MINUTES).await.ok().and_then(|v| v.parse().ok()),
+            Err(_) => None,
+        };
+        tokio::time::sleep(std::time::Duration::from_secs(minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES) * 60)).await;
+    }
+}
+
+#[get("/")]
+pub async fn get_alerts(pool: web::Data<SqlitePool>, query: web::Query<AlertListQuery>, req: HttpRequest) -> impl Responder {
+    // פתוחות קודם, ואז לפי חומרה
+    let sql = format!(
+        "SELECT {} FROM alerts
+        WHERE (? IS NULL OR status = ?) AND (? IS NULL OR severity = ?) AND (? IS NULL OR rule = ?) AND (? IS NULL OR assignee_id = ?)
+        ORDER BY status = 'resolved', {}, last_seen_at DESC, id DESC",
+        ALERT_COLUMNS, SEVERITY_ORDER
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![
+            query.status.clone(),
+            query.status.clone(),
+            query.severity.clone(),
+            query.severity.clone(),
+            query.rule.clone(),
+            query.rule.clone(),
+            query.assignee_id,
+            query.assignee_id,
+        ],
+        alert_from_row,
+        "alerts",
+    )
+    .await
+}
+
+#[post("/run")]
+pub async fn run_alerts_now(pool: web::Data<SqlitePool>) -> impl Responder {
+    match run_alert_rules(&pool).await {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/channels")]
+pub async fn create_alert_channel(pool: web::Data<SqlitePool>, channel: web::Json<CreateAlertChannelRequest>) -> impl Responder {
+    if !CHANNEL_KINDS.contains(&channel.kind.as_str()) {
+        return HttpResponse::BadRequest().body("Invalid channel kind (use log, webhook or smtp)");
+    }
+    let min_severity = channel.min_severity.as_deref().unwrap_or("info");
+    if !SEVERITIES.contains(&min_severity) {
+        return HttpResponse::BadRequest().body("Invalid min_severity (use info, warning or critical)");
+    }
+    let target = channel.target.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    match (channel.kind.as_str(), target) {
+        ("webhook", Some(url)) if !is_webhook_url(url) => {
+            return HttpResponse::BadRequest().body(format!("Webhook target {}", WEBHOOK_URL_RULE))
+        }
+        ("smtp", Some(address)) if !address.contains('@') || has_control_chars(address) => {
+            return HttpResponse::BadRequest().body("SMTP target must be an email address")
+        }
+        ("webhook" | "smtp", None) => return HttpResponse::BadRequest().body("Target is required for webhook and smtp channels"),
+        _ => {}
+    }
+
+    if let Some(staff_id) = channel.staff_id {
+        match sqlx::query("SELECT id FROM staff WHERE id = ?")
+            .bind(staff_id)
+            .fetch_optional(&**pool)
+            .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return HttpResponse::NotFound().body("Staff not found"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+
+    match sqlx::query(&format!(
+        "INSERT INTO alert_channels (kind, target, min_severity, enabled, staff_id) VALUES (?, ?, ?, 1, ?) RETURNING {}",
+        CHANNEL_COLUMNS
+    ))
+    .bind(&channel.kind)
+    .bind(target)
+    .bind(min_severity)
+    .bind(channel.staff_id)
+    .fetch_one(&**pool)
+    .await
+    {
+        Ok(row) => HttpResponse::Created().json(channel_from_row(&row)),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/channels")]
+pub async fn get_alert_channels(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = format!("SELECT {} FROM alert_channels ORDER BY id", CHANNEL_COLUMNS);
+    query_response(&pool, &req, sql, sql_args![], channel_from_row, "alert_channels").await
+}
+
+#[delete("/channels/{id}")]
+pub async fn delete_alert_channel(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query("DELETE FROM alert_channels WHERE id = ?")
+        .bind(id)
+        .execute(&**pool)
+        .await
+    {
+        Ok(res) if res.rows_affected() > 0 => HttpResponse::Ok().body("Deleted"),
+        Ok(_) => HttpResponse::NotFound().body("Channel not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/channels/{id}/test")]
+pub async fn test_alert_channel(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let channel = match sqlx::query(&format!("SELECT {} FROM alert_channels WHERE id = ?", CHANNEL_COLUMNS))
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+    {
+        Ok(Some(row)) => channel_from_row(&row),
+        Ok(None) => return HttpResponse::NotFound().body("Channel not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let (smtp_server, smtp_from) = match (get_setting(&mut conn, SMTP_SERVER).await, get_setting(&mut conn, SMTP_FROM).await) {
+        (Ok(server), Ok(from)) => (server, from),
+        (Err(e), _) | (_, Err(e)) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    drop(conn);
+
+    // התראת בדיקה שלא נשמרת
+    let now = Local::now().naive_local();
+    let alert = Alert {
+        id: 0,
+        rule: "test".to_string(),
+        alert_key: "test".to_string(),
+        severity: "info".to_string(),
+        message: format!("Test message for {} channel #{}", channel.kind, channel.id),
+        status: "open".to_string(),
+        created_at: now,
+        last_seen_at: now,
+        acknowledged_at: None,
+        acknowledged_by: None,
+        resolved_at: None,
+        assignee_id: channel.staff_id,
+    };
+    match notifier_for(&channel, &smtp_server, &smtp_from).send(&alert).await {
+        Ok(_) => HttpResponse::Ok().body("Delivered"),
+        Err(e) => HttpResponse::BadGateway().body(e),
+    }
+}
+
+#[get("/{id}")]
+pub async fn get_alert_by_id(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query(&format!("SELECT {} FROM alerts WHERE id = ?", ALERT_COLUMNS))
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => HttpResponse::Ok().json(alert_from_row(&row)),
+        Ok(None) => HttpResponse::NotFound().body("Alert not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+// ההתראה לא במצב שמאפשר את הפעולה - 404 אם אינה קיימת, אחרת 409
+async fn alert_state_conflict(pool: &SqlitePool, id: i64, action: &str) -> HttpResponse {
+    match sqlx::query("SELECT status FROM alerts WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(row)) => HttpResponse::Conflict().body(format!("Cannot {} an alert that is {}", action, row.get::<String, _>("status"))),
+        Ok(None) => HttpResponse::NotFound().body("Alert not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{id}/ack")]
+pub async fn acknowledge_alert(pool: web::Data<SqlitePool>, path: web::Path<i64>, req: HttpRequest) -> impl Responder {
+    let id = path.into_inner();
+    let staff_id = req
+        .headers()
+        .get(STAFF_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok());
+
+    match sqlx::query(&format!(
+        "UPDATE alerts SET status = 'acknowledged', acknowledged_at = ?, acknowledged_by = ? WHERE id = ? AND status = 'open' RETURNING {}",
+        ALERT_COLUMNS
+    ))
+    .bind(Local::now().naive_local())
+    .bind(staff_id)
+    .bind(id)
+    .fetch_optional(&**pool)
+    .await
+    {
+        Ok(Some(row)) => HttpResponse::Ok().json(alert_from_row(&row)),
+        Ok(None) => alert_state_conflict(&pool, id, "acknowledge").await,
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{id}/resolve")]
+pub async fn resolve_alert(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query(&format!(
+        "UPDATE alerts SET status = 'resolved', resolved_at = ? WHERE id = ? AND status != 'resolved' RETURNING {}",
+        ALERT_COLUMNS
+    ))
+    .bind(Local::now().naive_local())
+    .bind(id)
+    .fetch_optional(&**pool)
+    .await
+    {
+        Ok(Some(row)) => HttpResponse::Ok().json(alert_from_row(&row)),
+        Ok(None) => alert_state_conflict(&pool, id, "resolve").await,
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
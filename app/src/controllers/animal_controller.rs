@@ -1,72 +1,190 @@
-use actix_web::{get, post, put, delete, web, HttpResponse, Responder};
-use sqlx::{SqlitePool, Row};
-use crate::models::animal::{Animal, CreateAnimalRequest, UpdateAnimalRequest};
+use actix_web::{get, post, put, delete, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use crate::controllers::breeding_controller::{is_ancestor, load_parents};
+use crate::controllers::maintenance_controller::is_out_of_service;
+use crate::controllers::quarantine_controller::{is_in_quarantine, is_quarantine_enclosure};
+use crate::controllers::event_controller::{emit_event, emit_move_event};
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::db::references_to;
+use crate::models::animal::{
+    Animal, AnimalIdentifier, AnimalListQuery, AnimalLookupQuery, CreateAnimalIdentifierRequest, CreateAnimalRequest,
+    UpdateAnimalRequest,
+};
+use crate::models::animal_event::{AnimalEvent, CreateAnimalEventRequest};
+use crate::models::event::DeletedEntity;
 
-#[post("/")]
-pub async fn create_animal(pool: web::Data<SqlitePool>, animal: web::Json<CreateAnimalRequest>) -> impl Responder {
-    match sqlx::query("INSERT INTO animals (name, age, enclosure_id, species) VALUES (?, ?, ?, ?)")
+// העמודות של Animal - משותף לכל השאילתות שמחזירות חיה
+pub const ANIMAL_COLUMNS: &str = "id, name, age, enclosure_id, species, status, sire_id, dam_id, sire_external, dam_external, studbook_number,
+    sex, accession_number, microchip_id, transponder_id, distinguishing_marks";
+
+pub fn animal_from_row(row: &SqliteRow) -> Animal {
+    Animal {
+        id: row.get("id"),
+        name: row.get("name"),
+        age: row.get("age"),
+        enclosure_id: row.get("enclosure_id"),
+        species: row.get("species"),
+        status: row.get("status"),
+        sire_id: row.get("sire_id"),
+        dam_id: row.get("dam_id"),
+        sire_external: row.get("sire_external"),
+        dam_external: row.get("dam_external"),
+        studbook_number: row.get("studbook_number"),
+        sex: row.get("sex"),
+        accession_number: row.get("accession_number"),
+        microchip_id: row.get("microchip_id"),
+        transponder_id: row.get("transponder_id"),
+        distinguishing_marks: row.get("distinguishing_marks"),
+    }
+}
+
+const SEXES: [&str; 3] = ["M", "F", "U"];
+const IDENTIFIER_TYPES: [&str; 3] = ["band", "tag", "tattoo"];
+
+// סורקים שולחים לפעמים רווחים - מזהה ריק נשמר כ-NULL כדי לא להתנגש באינדקס הייחודי
+fn normalize_id(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+// הסורק לא מבחין בין שבב למשדר, לכן מספר שמופיע באחד מהם חייב להיות ייחודי בשניהם
+async fn chip_in_use(
+    conn: &mut SqliteConnection,
+    animal_id: Option<i64>,
+    microchip_id: &Option<String>,
+    transponder_id: &Option<String>,
+) -> Result<bool, sqlx::Error> {
+    if microchip_id.is_none() && transponder_id.is_none() {
+        return Ok(false);
+    }
+    let row = sqlx::query("SELECT COUNT(*) AS taken FROM animals
+        WHERE id IS NOT ? AND (microchip_id IN (?, ?) OR transponder_id IN (?, ?))")
+        .bind(animal_id)
+        .bind(microchip_id)
+        .bind(transponder_id)
+        .bind(microchip_id)
+        .bind(transponder_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(row.get::<i64, _>("taken") > 0)
+}
+
+fn is_unique_violation(e: &sqlx::Error) -> bool {
+    e.as_database_error().is_some_and(|e| e.is_unique_violation())
+}
+
+fn sex_or_unknown(sex: &Option<String>) -> Option<String> {
+    let sex = sex.as_deref().unwrap_or("U").to_uppercase();
+    SEXES.contains(&sex.as_str()).then_some(sex)
+}
+
+pub enum CreateAnimalError {
+    Invalid(&'static str),
+    Conflict(&'static str),
+    Database(sqlx::Error),
+}
+
+/// Validates and stores a new animal with the same rules as `POST /animals/`.
+pub async fn insert_animal(conn: &mut SqliteConnection, animal: &CreateAnimalRequest) -> Result<Animal, CreateAnimalError> {
+    let Some(sex) = sex_or_unknown(&animal.sex) else {
+        return Err(CreateAnimalError::Invalid("Invalid sex (use M, F or U)"));
+    };
+    let studbook_number = normalize_id(&animal.studbook_number);
+    let accession_number = normalize_id(&animal.accession_number);
+    let microchip_id = normalize_id(&animal.microchip_id);
+    let transponder_id = normalize_id(&animal.transponder_id);
+
+    match chip_in_use(&mut *conn, None, &microchip_id, &transponder_id).await {
+        Ok(false) => {}
+        Ok(true) => return Err(CreateAnimalError::Conflict("Chip already belongs to another animal")),
+        Err(e) => return Err(CreateAnimalError::Database(e)),
+    }
+
+    // מתחם עם תקלה חמורה פתוחה לא מקבל חיות
+    match is_out_of_service(&mut *conn, animal.enclosure_id).await {
+        Ok(false) => {}
+        Ok(true) => return Err(CreateAnimalError::Conflict("Enclosure is out of service until its defects are resolved")),
+        Err(e) => return Err(CreateAnimalError::Database(e)),
+    }
+
+    match sqlx::query("INSERT INTO animals (name, age, enclosure_id, species, sire_id, dam_id, sire_external, dam_external, studbook_number,
+            sex, accession_number, microchip_id, transponder_id, distinguishing_marks) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
         .bind(&animal.name)
-        .bind(&animal.age)
-        .bind(&animal.enclosure_id)
+        .bind(animal.age)
+        .bind(animal.enclosure_id)
         .bind(&animal.species)
-        .execute(&**pool)
+        .bind(animal.sire_id)
+        .bind(animal.dam_id)
+        .bind(&animal.sire_external)
+        .bind(&animal.dam_external)
+        .bind(&studbook_number)
+        .bind(&sex)
+        .bind(&accession_number)
+        .bind(&microchip_id)
+        .bind(&transponder_id)
+        .bind(&animal.distinguishing_marks)
+        .execute(&mut *conn)
         .await
     {
-        Ok(result) => {
-            let new_animal = Animal {
-                id: result.last_insert_rowid(),
-                name: animal.name.clone(),
-                age: animal.age.clone(),
-                enclosure_id: animal.enclosure_id.clone(),
-                species: animal.species.clone(),
-            };
+        Ok(result) => Ok(Animal {
+            id: result.last_insert_rowid(),
+            name: animal.name.clone(),
+            age: animal.age,
+            enclosure_id: animal.enclosure_id,
+            species: animal.species.clone(),
+            status: "resident".to_string(),
+            sire_id: animal.sire_id,
+            dam_id: animal.dam_id,
+            sire_external: animal.sire_external.clone(),
+            dam_external: animal.dam_external.clone(),
+            studbook_number,
+            sex,
+            accession_number,
+            microchip_id,
+            transponder_id,
+            distinguishing_marks: animal.distinguishing_marks.clone(),
+        }),
+        Err(e) if is_unique_violation(&e) => Err(CreateAnimalError::Conflict(
+            "Studbook number, accession number or chip already belongs to another animal",
+        )),
+        Err(e) => Err(CreateAnimalError::Database(e)),
+    }
+}
+
+#[post("/")]
+pub async fn create_animal(pool: web::Data<SqlitePool>, animal: web::Json<CreateAnimalRequest>) -> impl Responder {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match insert_animal(&mut conn, &animal).await {
+        Ok(new_animal) => {
+            emit_event(&pool, "animal.created", &new_animal).await;
             HttpResponse::Created().json(new_animal)
         }
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        Err(CreateAnimalError::Invalid(message)) => HttpResponse::BadRequest().body(message),
+        Err(CreateAnimalError::Conflict(message)) => HttpResponse::Conflict().body(message),
+        Err(CreateAnimalError::Database(e)) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
 
 #[get("/")]
-pub async fn get_animals(pool: web::Data<SqlitePool>) -> impl Responder {
-    match sqlx::query("SELECT id, name, age, enclosure_id, species FROM animals ORDER BY id")
-        .fetch_all(&**pool)
-        .await
-    {
-        Ok(rows) => {
-            let animals: Vec<Animal> = rows
-                .iter()
-                .map(|row| Animal {
-                    id: row.get("id"),
-                    name: row.get("name"),
-                    age: row.get("age"),
-                    enclosure_id: row.get("enclosure_id"),
-                    species: row.get("species"),
-                })
-                .collect();
-            HttpResponse::Ok().json(animals)
-        }
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    }
+pub async fn get_animals(pool: web::Data<SqlitePool>, query: web::Query<AnimalListQuery>, req: HttpRequest) -> impl Responder {
+    let sql = format!("SELECT {} FROM animals WHERE ? IS NULL OR status = ? ORDER BY id", ANIMAL_COLUMNS);
+    query_response(&pool, &req, sql, sql_args![query.status.clone(), query.status.clone()], animal_from_row, "animals").await
 }
 
 #[get("/{id}")]
 pub async fn get_animal_by_id(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
     let id = path.into_inner();
     
-    match sqlx::query("SELECT id, name, age, enclosure_id, species FROM animals WHERE id = ?")
-        .bind(&id)
+    match sqlx::query(&format!("SELECT {} FROM animals WHERE id = ?", ANIMAL_COLUMNS))
+        .bind(id)
         .fetch_optional(&**pool)
         .await
     {
         Ok(Some(row)) => {
-            let animal = Animal {
-                id: row.get("id"),
-                name: row.get("name"),
-                age: row.get("age"),
-                enclosure_id: row.get("enclosure_id"),
-                species: row.get("species"),
-            };
-            HttpResponse::Ok().json(animal)
+            HttpResponse::Ok().json(animal_from_row(&row))
         }
         Ok(None) => HttpResponse::NotFound().body("Animal not found"),
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
@@ -80,30 +198,122 @@ pub async fn update_animal(
     updated: web::Json<UpdateAnimalRequest>,
 ) -> impl Responder {
     let id = path.into_inner();
-    
-    match sqlx::query("UPDATE animals SET name = ?, age = ?, enclosure_id = ?, species = ? WHERE id = ?")
+    let Some(sex) = sex_or_unknown(&updated.sex) else {
+        return HttpResponse::BadRequest().body("Invalid sex (use M, F or U)");
+    };
+    let studbook_number = normalize_id(&updated.studbook_number);
+    let accession_number = normalize_id(&updated.accession_number);
+    let microchip_id = normalize_id(&updated.microchip_id);
+    let transponder_id = normalize_id(&updated.transponder_id);
+
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match chip_in_use(&mut conn, Some(id), &microchip_id, &transponder_id).await {
+        Ok(false) => {}
+        Ok(true) => return HttpResponse::Conflict().body("Chip already belongs to another animal"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    // חיה בהסגר לא עוברת למתחם רגיל עד שוטרינר משחרר אותה
+    let (current_enclosure, status): (i64, String) = match sqlx::query("SELECT enclosure_id, status FROM animals WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+    {
+        Ok(Some(row)) => (row.get("enclosure_id"), row.get("status")),
+        Ok(None) => return HttpResponse::NotFound().body("Animal not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    if current_enclosure != updated.enclosure_id {
+        let blocked = match is_in_quarantine(&mut conn, id).await {
+            Ok(true) => match is_quarantine_enclosure(&mut conn, updated.enclosure_id).await {
+                Ok(target_is_quarantine) => !target_is_quarantine,
+                Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+            },
+            Ok(false) => false,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        };
+        if blocked {
+            return HttpResponse::Conflict().body("Animal is in quarantine and must be cleared by a vet before transfer");
+        }
+        match is_out_of_service(&mut conn, updated.enclosure_id).await {
+            Ok(false) => {}
+            Ok(true) => return HttpResponse::Conflict().body("Enclosure is out of service until its defects are resolved"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+
+    // הורה לא יכול להיות החיה עצמה או אחד מצאצאיה
+    if updated.sire_id.is_some() || updated.dam_id.is_some() {
+        let parents = match load_parents(&mut conn).await {
+            Ok(parents) => parents,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        };
+        let creates_cycle = [updated.sire_id, updated.dam_id]
+            .into_iter()
+            .flatten()
+            .any(|parent_id| parent_id == id || is_ancestor(&parents, id, parent_id));
+        if creates_cycle {
+            return HttpResponse::BadRequest().body("An animal cannot be its own ancestor");
+        }
+    }
+
+    match sqlx::query("UPDATE animals SET name = ?, age = ?, enclosure_id = ?, species = ?, sire_id = ?, dam_id = ?, sire_external = ?, dam_external = ?, studbook_number = ?,
+            sex = ?, accession_number = ?, microchip_id = ?, transponder_id = ?, distinguishing_marks = ? WHERE id = ?")
         .bind(&updated.name)
-        .bind(&updated.age)
-        .bind(&updated.enclosure_id)
+        .bind(updated.age)
+        .bind(updated.enclosure_id)
         .bind(&updated.species)
-        .bind(&id)
-        .execute(&**pool)
+        .bind(updated.sire_id)
+        .bind(updated.dam_id)
+        .bind(&updated.sire_external)
+        .bind(&updated.dam_external)
+        .bind(&studbook_number)
+        .bind(&sex)
+        .bind(&accession_number)
+        .bind(&microchip_id)
+        .bind(&transponder_id)
+        .bind(&updated.distinguishing_marks)
+        .bind(id)
+        .execute(&mut *conn)
         .await
     {
         Ok(result) => {
             if result.rows_affected() > 0 {
                 let updated_animal = Animal {
-                    id: id,
+                    id,
                     name: updated.name.clone(),
-                    age: updated.age.clone(),
-                    enclosure_id: updated.enclosure_id.clone(),
+                    age: updated.age,
+                    enclosure_id: updated.enclosure_id,
                     species: updated.species.clone(),
+                    status,
+                    sire_id: updated.sire_id,
+                    dam_id: updated.dam_id,
+                    sire_external: updated.sire_external.clone(),
+                    dam_external: updated.dam_external.clone(),
+                    studbook_number,
+                    sex,
+                    accession_number,
+                    microchip_id,
+                    transponder_id,
+                    distinguishing_marks: updated.distinguishing_marks.clone(),
                 };
+                if current_enclosure == updated.enclosure_id {
+                    emit_event(&pool, "animal.updated", &updated_animal).await;
+                } else {
+                    emit_move_event(&pool, "animal.updated", &updated_animal, current_enclosure).await;
+                }
                 HttpResponse::Ok().json(updated_animal)
             } else {
                 HttpResponse::NotFound().body("Animal not found")
             }
         }
+        Err(e) if is_unique_violation(&e) => {
+            HttpResponse::Conflict().body("Studbook number, accession number or chip already belongs to another animal")
+        }
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
@@ -111,19 +321,43 @@ pub async fn update_animal(
 #[delete("/{id}")]
 pub async fn delete_animal(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
     let id = path.into_inner();
-    
-    match sqlx::query("DELETE FROM animals WHERE id = ?")
-        .bind(&id)
-        .execute(&**pool)
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // מזהים של חיה שנמחקה משתחררים לשימוש חוזר, והשיוכים לשומרים נמחקים איתה
+    for child_table in ["animal_identifiers", "animal_assignments"] {
+        if let Err(e) = sqlx::query(&format!("DELETE FROM {} WHERE animal_id = ?", child_table))
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        {
+            return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+        }
+    }
+    // האכלות, טיפולים, משימות וצאצאים נשארים כהיסטוריה - חיה שיש לה כאלה לא נמחקת
+    match references_to(&mut tx, "animals", id).await {
+        Ok(references) if references.is_empty() => {}
+        Ok(references) => return HttpResponse::Conflict().body(format!("Animal is still referenced by {}", references.join(", "))),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    match sqlx::query("DELETE FROM animals WHERE id = ? RETURNING enclosure_id")
+        .bind(id)
+        .fetch_optional(&mut *tx)
         .await
     {
-        Ok(result) => {
-            if result.rows_affected() > 0 {
-                HttpResponse::Ok().body("Deleted")
-            } else {
-                HttpResponse::NotFound().body("Animal not found")
+        Ok(Some(row)) => {
+            if let Err(e) = tx.commit().await {
+                return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
             }
+            let deleted = DeletedEntity { id, animal_id: Some(id), enclosure_id: Some(row.get("enclosure_id")) };
+            emit_event(&pool, "animal.deleted", &deleted).await;
+            HttpResponse::Ok().body("Deleted")
         }
+        Ok(None) => HttpResponse::NotFound().body("Animal not found"),
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
@@ -131,7 +365,8 @@ pub async fn delete_animal(pool: web::Data<SqlitePool>, path: web::Path<i64>) ->
 #[get("/older/{age}")]
 pub async fn get_animals_older_than(
     pool: web::Data<SqlitePool>,
-    path: web::Path<String>
+    path: web::Path<String>,
+    req: HttpRequest,
 ) -> impl Responder {
     let age_str = path.into_inner();
     let age: i32 = match age_str.parse() {
@@ -139,23 +374,208 @@ pub async fn get_animals_older_than(
         Err(_) => return HttpResponse::BadRequest().body("Invalid age format"),
     };
 
-    match sqlx::query("SELECT id, name, age, enclosure_id, species FROM animals WHERE age > ?")
-        .bind(age)
+    let sql = format!("SELECT {} FROM animals WHERE age > ?", ANIMAL_COLUMNS);
+    query_response(&pool, &req, sql, sql_args![age], animal_from_row, "animals").await
+}
+
+// הסטטוס שנגזר מכל סוג אירוע
+fn status_for_event(event_type: &str) -> Option<&'static str> {
+    match event_type {
+        "born" | "acquired" | "loaned_in" => Some("resident"),
+        "loaned_out" => Some("loaned_out"),
+        "sold" | "released" => Some("departed"),
+        "deceased" => Some("deceased"),
+        _ => None,
+    }
+}
+
+// מחשב מחדש את סטטוס החיה לפי האירוע המאוחר ביותר
+async fn refresh_status(conn: &mut SqliteConnection, animal_id: i64) -> Result<(), sqlx::Error> {
+    let latest: Option<String> = sqlx::query("SELECT event_type FROM animal_events WHERE animal_id = ? ORDER BY event_date DESC, id DESC LIMIT 1")
+        .bind(animal_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|row| row.get("event_type"));
+
+    let status = latest.as_deref().and_then(status_for_event).unwrap_or("resident");
+    sqlx::query("UPDATE animals SET status = ? WHERE id = ?")
+        .bind(status)
+        .bind(animal_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+#[post("/{id}/events")]
+pub async fn create_animal_event(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    event: web::Json<CreateAnimalEventRequest>,
+) -> impl Responder {
+    let animal_id = path.into_inner();
+
+    if status_for_event(&event.event_type).is_none() {
+        return HttpResponse::BadRequest()
+            .body("Invalid event type (use born, acquired, loaned_in, loaned_out, sold, released or deceased)");
+    }
+    if event.event_type == "deceased" && event.cause.is_none() {
+        return HttpResponse::BadRequest().body("A deceased event requires a cause");
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match sqlx::query("SELECT id FROM animals WHERE id = ?")
+        .bind(animal_id)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Animal not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    let id = match sqlx::query("INSERT INTO animal_events (animal_id, event_type, event_date, institution, cause, necropsy_treatment_id, notes) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(animal_id)
+        .bind(&event.event_type)
+        .bind(event.event_date)
+        .bind(&event.institution)
+        .bind(&event.cause)
+        .bind(event.necropsy_treatment_id)
+        .bind(&event.notes)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(result) => result.last_insert_rowid(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    if let Err(e) = refresh_status(&mut tx, animal_id).await {
+        return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+    }
+
+    match tx.commit().await {
+        Ok(_) => {
+            let new_event = AnimalEvent {
+                id,
+                animal_id,
+                event_type: event.event_type.clone(),
+                event_date: event.event_date,
+                institution: event.institution.clone(),
+                cause: event.cause.clone(),
+                necropsy_treatment_id: event.necropsy_treatment_id,
+                notes: event.notes.clone(),
+            };
+            emit_event(&pool, "animal_event.created", &new_event).await;
+            HttpResponse::Created().json(new_event)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn animal_event_from_row(row: &SqliteRow) -> AnimalEvent {
+    AnimalEvent {
+        id: row.get("id"),
+        animal_id: row.get("animal_id"),
+        event_type: row.get("event_type"),
+        event_date: row.get("event_date"),
+        institution: row.get("institution"),
+        cause: row.get("cause"),
+        necropsy_treatment_id: row.get("necropsy_treatment_id"),
+        notes: row.get("notes"),
+    }
+}
+
+#[get("/{id}/events")]
+pub async fn get_animal_events(pool: web::Data<SqlitePool>, path: web::Path<i64>, req: HttpRequest) -> impl Responder {
+    let animal_id = path.into_inner();
+    let sql = "SELECT id, animal_id, event_type, event_date, institution, cause, necropsy_treatment_id, notes
+        FROM animal_events WHERE animal_id = ? ORDER BY event_date, id".to_string();
+    query_response(&pool, &req, sql, sql_args![animal_id], animal_event_from_row, "animal_events").await
+}
+
+#[get("/lookup")]
+pub async fn lookup_animal(pool: web::Data<SqlitePool>, query: web::Query<AnimalLookupQuery>) -> impl Responder {
+    let chip = query.chip.trim();
+    if chip.is_empty() {
+        return HttpResponse::BadRequest().body("Chip is required");
+    }
+
+    // שבב או משדר - הסורק לא יודע איזה מהם נקרא
+    match sqlx::query(&format!("SELECT {} FROM animals WHERE microchip_id = ? OR transponder_id = ? ORDER BY id", ANIMAL_COLUMNS))
+        .bind(chip)
+        .bind(chip)
         .fetch_all(&**pool)
         .await
     {
-        Ok(rows) => {
-            let animals: Vec<Animal> = rows.iter().map(|row| Animal {
-                id: row.get("id"),
-                name: row.get("name"),
-                age: row.get("age"),
-                enclosure_id: row.get("enclosure_id"),
-                species: row.get("species")
-            }).collect();
-
-            HttpResponse::Ok().json(animals)
+        Ok(rows) if rows.is_empty() => HttpResponse::NotFound().body("No animal with this chip"),
+        Ok(rows) if rows.len() == 1 => HttpResponse::Ok().json(animal_from_row(&rows[0])),
+        // נתונים מלפני בדיקת הייחודיות - מחזירים את כל המועמדים ולא בוחרים אחד באקראי
+        Ok(rows) => HttpResponse::MultipleChoices().json(rows.iter().map(animal_from_row).collect::<Vec<_>>()),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{id}/identifiers")]
+pub async fn create_animal_identifier(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    identifier: web::Json<CreateAnimalIdentifierRequest>,
+) -> impl Responder {
+    let animal_id = path.into_inner();
+    if !IDENTIFIER_TYPES.contains(&identifier.id_type.as_str()) {
+        return HttpResponse::BadRequest().body("Invalid identifier type (use band, tag or tattoo)");
+    }
+    let value = identifier.value.trim();
+    if value.is_empty() {
+        return HttpResponse::BadRequest().body("Identifier value is required");
+    }
+
+    match sqlx::query("SELECT id FROM animals WHERE id = ?")
+        .bind(animal_id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Animal not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    match sqlx::query("INSERT INTO animal_identifiers (animal_id, id_type, value) VALUES (?, ?, ?)")
+        .bind(animal_id)
+        .bind(&identifier.id_type)
+        .bind(value)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_identifier = AnimalIdentifier {
+                id: result.last_insert_rowid(),
+                animal_id,
+                id_type: identifier.id_type.clone(),
+                value: value.to_string(),
+            };
+            HttpResponse::Created().json(new_identifier)
         }
-        Err(e) => HttpResponse::InternalServerError()
-            .body(format!("Database error: {}", e))
+        Err(e) if is_unique_violation(&e) => HttpResponse::Conflict().body("Identifier already belongs to an animal"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
-}
\ No newline at end of file
+}
+
+fn animal_identifier_from_row(row: &SqliteRow) -> AnimalIdentifier {
+    AnimalIdentifier {
+        id: row.get("id"),
+        animal_id: row.get("animal_id"),
+        id_type: row.get("id_type"),
+        value: row.get("value"),
+    }
+}
+
+#[get("/{id}/identifiers")]
+pub async fn get_animal_identifiers(pool: web::Data<SqlitePool>, path: web::Path<i64>, req: HttpRequest) -> impl Responder {
+    let animal_id = path.into_inner();
+    let sql = "SELECT id, animal_id, id_type, value FROM animal_identifiers WHERE animal_id = ? ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![animal_id], animal_identifier_from_row, "animal_identifiers").await
+}
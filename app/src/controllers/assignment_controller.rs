@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use chrono::{Local, NaiveDate};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::models::assignment::{
+    AnimalAssignment, AssignmentListQuery, CreateKeeperAssignmentRequest, EndAssignmentRequest, EnclosureAssignment,
+};
+
+const ASSIGNMENT_COLUMNS: &str = "id, staff_id, enclosure_id, keeper_role, since, until";
+const ANIMAL_ASSIGNMENT_COLUMNS: &str = "id, staff_id, animal_id, keeper_role, since, until";
+
+// שיוך פעיל בתאריך מסוים: התחיל ועדיין לא הסתיים
+const ACTIVE_ON: &str = "since <= ? AND (until IS NULL OR until >= ?)";
+
+// שומר משויך למתחם או לחיה מסוימת - אותם כללים, טבלה אחרת
+struct KeeperTarget {
+    table: &'static str,
+    column: &'static str,
+    parent_table: &'static str,
+    label: &'static str,
+}
+
+const ENCLOSURE_KEEPERS: KeeperTarget = KeeperTarget {
+    table: "enclosure_assignments",
+    column: "enclosure_id",
+    parent_table: "enclosures",
+    label: "Enclosure",
+};
+
+const ANIMAL_KEEPERS: KeeperTarget = KeeperTarget {
+    table: "animal_assignments",
+    column: "animal_id",
+    parent_table: "animals",
+    label: "Animal",
+};
+
+/// Keepers active on a given day, by enclosure and by animal; primary keepers come first.
+#[derive(Default)]
+pub struct KeeperMap {
+    enclosures: HashMap<i64, Vec<i64>>,
+    animals: HashMap<i64, Vec<i64>>,
+}
+
+impl KeeperMap {
+    pub fn of_enclosure(&self, enclosure_id: i64) -> Vec<i64> {
+        self.enclosures.get(&enclosure_id).cloned().unwrap_or_default()
+    }
+
+    /// The animal's own keepers, then those of its enclosure.
+    pub fn of_animal(&self, animal_id: i64, enclosure_id: i64) -> Vec<i64> {
+        let mut keepers = self.animals.get(&animal_id).cloned().unwrap_or_default();
+        for keeper in self.of_enclosure(enclosure_id) {
+            if !keepers.contains(&keeper) {
+                keepers.push(keeper);
+            }
+        }
+        keepers
+    }
+}
+
+fn assignment_from_row(row: &SqliteRow) -> EnclosureAssignment {
+    EnclosureAssignment {
+        id: row.get("id"),
+        staff_id: row.get("staff_id"),
+        enclosure_id: row.get("enclosure_id"),
+        keeper_role: row.get("keeper_role"),
+        since: row.get("since"),
+        until: row.get("until"),
+    }
+}
+
+fn animal_assignment_from_row(row: &SqliteRow) -> AnimalAssignment {
+    AnimalAssignment {
+        id: row.get("id"),
+        staff_id: row.get("staff_id"),
+        animal_id: row.get("animal_id"),
+        keeper_role: row.get("keeper_role"),
+        since: row.get("since"),
+        until: row.get("until"),
+    }
+}
+
+async fn load_keepers_of(conn: &mut SqliteConnection, target: &KeeperTarget, on: NaiveDate) -> Result<HashMap<i64, Vec<i64>>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "SELECT {column} AS target_id, staff_id FROM {} WHERE {} ORDER BY {column}, keeper_role, since, id",
+        target.table,
+        ACTIVE_ON,
+        column = target.column
+    ))
+    .bind(on)
+    .bind(on)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut keepers: HashMap<i64, Vec<i64>> = HashMap::new();
+    for row in rows {
+        keepers.entry(row.get("target_id")).or_default().push(row.get("staff_id"));
+    }
+    Ok(keepers)
+}
+
+pub async fn load_active_keepers(conn: &mut SqliteConnection, on: NaiveDate) -> Result<KeeperMap, sqlx::Error> {
+    Ok(KeeperMap {
+        enclosures: load_keepers_of(conn, &ENCLOSURE_KEEPERS, on).await?,
+        animals: load_keepers_of(conn, &ANIMAL_KEEPERS, on).await?,
+    })
+}
+
+/// The keeper responsible for an animal on a given day: its own primary or secondary keeper, else the enclosure's.
+pub async fn default_keeper(
+    conn: &mut SqliteConnection,
+    animal_id: i64,
+    enclosure_id: i64,
+    on: NaiveDate,
+) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        "SELECT staff_id FROM (
+            SELECT staff_id, 0 AS level, keeper_role, since, id FROM animal_assignments WHERE animal_id = ? AND {active}
+            UNION ALL
+            SELECT staff_id, 1 AS level, keeper_role, since, id FROM enclosure_assignments WHERE enclosure_id = ? AND {active}
+        )
+        ORDER BY level, keeper_role, since, id LIMIT 1",
+        active = ACTIVE_ON
+    ))
+    .bind(animal_id)
+    .bind(on)
+    .bind(on)
+    .bind(enclosure_id)
+    .bind(on)
+    .bind(on)
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(row.map(|row| row.get("staff_id")))
+}
+
+// בודק ושומר שיוך חדש; מחזיר את המזהה שלו או תשובת שגיאה מוכנה
+async fn insert_assignment(
+    pool: &SqlitePool,
+    target: &KeeperTarget,
+    target_id: i64,
+    assignment: &CreateKeeperAssignmentRequest,
+) -> Result<i64, HttpResponse> {
+    if assignment.keeper_role != "primary" && assignment.keeper_role != "secondary" {
+        return Err(HttpResponse::BadRequest().body("Invalid keeper role (use primary or secondary)"));
+    }
+    if assignment.until.is_some_and(|until| until < assignment.since) {
+        return Err(HttpResponse::BadRequest().body("Assignment must end after it starts"));
+    }
+
+    let database_error = |e: sqlx::Error| HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+    let mut tx = pool.begin().await.map_err(database_error)?;
+
+    let found = sqlx::query(&format!(
+        "SELECT (SELECT COUNT(*) FROM {} WHERE id = ?) AS targets, (SELECT COUNT(*) FROM staff WHERE id = ?) AS staff",
+        target.parent_table
+    ))
+    .bind(target_id)
+    .bind(assignment.staff_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(database_error)?;
+    if found.get::<i64, _>("targets") == 0 {
+        return Err(HttpResponse::NotFound().body(format!("{} not found", target.label)));
+    }
+    if found.get::<i64, _>("staff") == 0 {
+        return Err(HttpResponse::NotFound().body("Staff not found"));
+    }
+
+    // בכל תקופה יש שומר ראשי אחד לכל היותר, ועובד לא משויך פעמיים לאותו מתחם או חיה
+    let overlapping = sqlx::query(&format!(
+        "SELECT staff_id, keeper_role FROM {} WHERE {} = ? AND (staff_id = ? OR (keeper_role = 'primary' AND ? = 'primary'))
+            AND (? IS NULL OR since <= ?) AND (until IS NULL OR until >= ?)
+        LIMIT 1",
+        target.table, target.column
+    ))
+    .bind(target_id)
+    .bind(assignment.staff_id)
+    .bind(&assignment.keeper_role)
+    .bind(assignment.until)
+    .bind(assignment.until)
+    .bind(assignment.since)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(database_error)?;
+    match overlapping {
+        Some(row) if row.get::<i64, _>("staff_id") == assignment.staff_id => {
+            return Err(HttpResponse::Conflict().body(format!(
+                "Staff member is already assigned to this {} for that period",
+                target.label.to_lowercase()
+            )))
+        }
+        Some(_) => {
+            return Err(HttpResponse::Conflict().body(format!("{} already has a primary keeper for that period", target.label)))
+        }
+        None => {}
+    }
+
+    let id = sqlx::query(&format!(
+        "INSERT INTO {} (staff_id, {}, keeper_role, since, until) VALUES (?, ?, ?, ?, ?)",
+        target.table, target.column
+    ))
+    .bind(assignment.staff_id)
+    .bind(target_id)
+    .bind(&assignment.keeper_role)
+    .bind(assignment.since)
+    .bind(assignment.until)
+    .execute(&mut *tx)
+    .await
+    .map_err(database_error)?
+    .last_insert_rowid();
+
+    tx.commit().await.map_err(database_error)?;
+    Ok(id)
+}
+
+// שיוך מסתיים בתאריך שניתן (ברירת מחדל היום), אבל לא לפני שהתחיל
+async fn end_assignment(
+    pool: &SqlitePool,
+    target: &KeeperTarget,
+    columns: &str,
+    target_id: i64,
+    assignment_id: i64,
+    until: NaiveDate,
+) -> Result<Option<SqliteRow>, sqlx::Error> {
+    sqlx::query(&format!(
+        "UPDATE {} SET until = ? WHERE id = ? AND {} = ? AND since <= ? RETURNING {}",
+        target.table, target.column, columns
+    ))
+    .bind(until)
+    .bind(assignment_id)
+    .bind(target_id)
+    .bind(until)
+    .fetch_optional(pool)
+    .await
+}
+
+#[post("/{id}/keepers")]
+pub async fn create_enclosure_assignment(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    assignment: web::Json<CreateKeeperAssignmentRequest>,
+) -> impl Responder {
+    let enclosure_id = path.into_inner();
+    match insert_assignment(&pool, &ENCLOSURE_KEEPERS, enclosure_id, &assignment).await {
+        Ok(id) => {
+            let new_assignment = EnclosureAssignment {
+                id,
+                staff_id: assignment.staff_id,
+                enclosure_id,
+                keeper_role: assignment.keeper_role.clone(),
+                since: assignment.since,
+                until: assignment.until,
+            };
+            emit_event(&pool, "enclosure_assignment.created", &new_assignment).await;
+            HttpResponse::Created().json(new_assignment)
+        }
+        Err(response) => response,
+    }
+}
+
+#[post("/{id}/keepers/{assignment_id}/end")]
+pub async fn end_enclosure_assignment(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<(i64, i64)>,
+    end: web::Json<EndAssignmentRequest>,
+) -> impl Responder {
+    let (enclosure_id, assignment_id) = path.into_inner();
+    let until = end.until.unwrap_or_else(|| Local::now().date_naive());
+
+    match end_assignment(&pool, &ENCLOSURE_KEEPERS, ASSIGNMENT_COLUMNS, enclosure_id, assignment_id, until).await {
+        Ok(Some(row)) => {
+            let ended = assignment_from_row(&row);
+            emit_event(&pool, "enclosure_assignment.updated", &ended).await;
+            HttpResponse::Ok().json(ended)
+        }
+        Ok(None) => HttpResponse::NotFound().body("Assignment not found (or ends before it starts)"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/{id}/keepers")]
+pub async fn get_enclosure_keepers(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    query: web::Query<AssignmentListQuery>,
+    req: HttpRequest,
+) -> impl Responder {
+    let enclosure_id = path.into_inner();
+    let today = Local::now().date_naive();
+
+    // ברירת מחדל - רק שיוכים פעילים היום; all=true מחזיר גם היסטוריה
+    let sql = format!(
+        "SELECT {} FROM enclosure_assignments WHERE enclosure_id = ? AND (? OR ({})) ORDER BY keeper_role, since, id",
+        ASSIGNMENT_COLUMNS, ACTIVE_ON
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![enclosure_id, query.all.unwrap_or(false), today, today],
+        assignment_from_row,
+        "enclosure_keepers",
+    )
+    .await
+}
+
+#[get("/{id}/enclosures")]
+pub async fn get_staff_enclosures(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    query: web::Query<AssignmentListQuery>,
+    req: HttpRequest,
+) -> impl Responder {
+    let staff_id = path.into_inner();
+    let today = Local::now().date_naive();
+
+    let sql = format!(
+        "SELECT {} FROM enclosure_assignments WHERE staff_id = ? AND (? OR ({})) ORDER BY since, id",
+        ASSIGNMENT_COLUMNS, ACTIVE_ON
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![staff_id, query.all.unwrap_or(false), today, today],
+        assignment_from_row,
+        "staff_enclosures",
+    )
+    .await
+}
+
+#[post("/{id}/keepers")]
+pub async fn create_animal_assignment(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    assignment: web::Json<CreateKeeperAssignmentRequest>,
+) -> impl Responder {
+    let animal_id = path.into_inner();
+    match insert_assignment(&pool, &ANIMAL_KEEPERS, animal_id, &assignment).await {
+        Ok(id) => {
+            let new_assignment = AnimalAssignment {
+                id,
+                staff_id: assignment.staff_id,
+                animal_id,
+                keeper_role: assignment.keeper_role.clone(),
+                since: assignment.since,
+                until: assignment.until,
+            };
+            emit_event(&pool, "animal_assignment.created", &new_assignment).await;
+            HttpResponse::Created().json(new_assignment)
+        }
+        Err(response) => response,
+    }
+}
+
+#[post("/{id}/keepers/{assignment_id}/end")]
+pub async fn end_animal_assignment(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<(i64, i64)>,
+    end: web::Json<EndAssignmentRequest>,
+) -> impl Responder {
+    let (animal_id, assignment_id) = path.into_inner();
+    let until = end.until.unwrap_or_else(|| Local::now().date_naive());
+
+    match end_assignment(&pool, &ANIMAL_KEEPERS, ANIMAL_ASSIGNMENT_COLUMNS, animal_id, assignment_id, until).await {
+        Ok(Some(row)) => {
+            let ended = animal_assignment_from_row(&row);
+            emit_event(&pool, "animal_assignment.updated", &ended).await;
+            HttpResponse::Ok().json(ended)
+        }
+        Ok(None) => HttpResponse::NotFound().body("Assignment not found (or ends before it starts)"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/{id}/keepers")]
+pub async fn get_animal_keepers(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    query: web::Query<AssignmentListQuery>,
+    req: HttpRequest,
+) -> impl Responder {
+    let animal_id = path.into_inner();
+    let today = Local::now().date_naive();
+
+    let sql = format!(
+        "SELECT {} FROM animal_assignments WHERE animal_id = ? AND (? OR ({})) ORDER BY keeper_role, since, id",
+        ANIMAL_ASSIGNMENT_COLUMNS, ACTIVE_ON
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![animal_id, query.all.unwrap_or(false), today, today],
+        animal_assignment_from_row,
+        "animal_keepers",
+    )
+    .await
+}
+
+#[get("/{id}/animals")]
+pub async fn get_staff_animals(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    query: web::Query<AssignmentListQuery>,
+    req: HttpRequest,
+) -> impl Responder {
+    let staff_id = path.into_inner();
+    let today = Local::now().date_naive();
+
+    let sql = format!(
+        "SELECT {} FROM animal_assignments WHERE staff_id = ? AND (? OR ({})) ORDER BY since, id",
+        ANIMAL_ASSIGNMENT_COLUMNS, ACTIVE_ON
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![staff_id, query.all.unwrap_or(false), today, today],
+        animal_assignment_from_row,
+        "staff_animals",
+    )
+    .await
+}
@@ -0,0 +1,220 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Local, Utc};
+use sqlx::{sqlite::SqliteConnectOptions, Connection, SqliteConnection, SqlitePool, Row};
+use crate::controllers::export_controller::list_response;
+use crate::controllers::settings_controller::{get_setting, BACKUP_DIR, BACKUP_INTERVAL_HOURS, BACKUP_KEEP};
+use crate::db::{connect, lock_database, migrate, DB_PATH, SCHEMA_VERSION};
+use crate::models::backup::{BackupCheck, BackupInfo};
+
+// כל כמה זמן המתזמן בודק אם הגיע הזמן לגיבוי הבא
+const SCHEDULER_CHECK_MINUTES: u64 = 10;
+
+struct BackupSettings {
+    dir: PathBuf,
+    interval_hours: u64,
+    keep: usize,
+}
+
+async fn backup_settings(pool: &SqlitePool) -> Result<BackupSettings, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let dir = get_setting(&mut conn, BACKUP_DIR).await?;
+    let interval_hours = get_setting(&mut conn, BACKUP_INTERVAL_HOURS).await?.parse().unwrap_or(24);
+    let keep = get_setting(&mut conn, BACKUP_KEEP).await?.parse().unwrap_or(7);
+    Ok(BackupSettings { dir: PathBuf::from(dir), interval_hours, keep })
+}
+
+// רק קבצים שהגיבוי עצמו יצר, כדי שלא נמחק או נפתח משהו אחר מהתיקייה
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with("zoo-") && name.ends_with(".db") && !name.contains(['/', '\\']) && !name.contains("..")
+}
+
+fn backup_info(path: &Path) -> Option<BackupInfo> {
+    let file = path.file_name()?.to_str()?.to_string();
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    Some(BackupInfo {
+        file,
+        size_bytes: metadata.len(),
+        created_at: DateTime::<Local>::from(modified).naive_local(),
+    })
+}
+
+/// Backups in `dir`, newest first.
+pub fn list_backups(dir: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_str().is_some_and(is_backup_name))
+        .filter_map(|entry| backup_info(&entry.path()))
+        .collect();
+    // שם הקובץ מכיל את חותמת הזמן ב-UTC, אז מיון לפי שם הוא מיון לפי זמן גם כשהשעון עובר לשעון קיץ או חורף
+    backups.sort_by(|a, b| b.file.cmp(&a.file));
+    backups
+}
+
+/// Copies the live database into a timestamped file with `VACUUM INTO`, which is consistent while the server keeps writing.
+pub async fn create_backup(pool: &SqlitePool, dir: &Path) -> Result<BackupInfo, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("zoo-{}.db", Utc::now().format("%Y%m%d-%H%M%S-%3fZ")));
+    let target = path.to_str().ok_or("Backup path is not valid UTF-8")?;
+    sqlx::query("VACUUM INTO ?")
+        .bind(target)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Backup failed: {}", e))?;
+    backup_info(&path).ok_or_else(|| format!("Backup {} was not written", path.display()))
+}
+
+/// Deletes the oldest backups so that only `keep` remain; returns how many were removed.
+pub fn prune_backups(dir: &Path, keep: usize) -> usize {
+    list_backups(dir)
+        .iter()
+        .skip(keep)
+        .filter(|backup| std::fs::remove_file(dir.join(&backup.file)).is_ok())
+        .count()
+}
+
+// גיבוי ישן יותר, גם כזה מלפני שהמבנה קיבל גרסאות (0), עובר את שלבי המבנה החסרים אחרי השחזור; חדש יותר - לא
+fn restorable_version(version: i64) -> bool {
+    (0..=SCHEMA_VERSION).contains(&version)
+}
+
+/// Runs `PRAGMA integrity_check` on a backup file and reads its schema version, without touching the live database.
+pub async fn verify_backup(path: &Path) -> Result<BackupCheck, String> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    let integrity: Vec<String> = sqlx::query("PRAGMA integrity_check")
+        .fetch_all(&mut conn)
+        .await
+        .map_err(|e| format!("Integrity check failed: {}", e))?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    let schema_version: i64 = sqlx::query("PRAGMA user_version")
+        .fetch_one(&mut conn)
+        .await
+        .map_err(|e| format!("Cannot read schema version: {}", e))?
+        .get(0);
+    conn.close().await.ok();
+
+    Ok(BackupCheck {
+        file: path.display().to_string(),
+        ok: integrity == ["ok"] && restorable_version(schema_version),
+        integrity,
+        schema_version,
+        expected_schema_version: SCHEMA_VERSION,
+    })
+}
+
+/// Replaces the live database with a verified backup and migrates it to the current schema. Refuses while the
+/// server holds the database lock; the current database is backed up first, so a bad restore can be undone.
+pub async fn restore_backup(pool: &SqlitePool, path: &Path) -> Result<BackupInfo, String> {
+    let check = verify_backup(path).await?;
+    if check.integrity != ["ok"] {
+        return Err(format!("Backup is corrupt: {}", check.integrity.join("; ")));
+    }
+    if !restorable_version(check.schema_version) {
+        return Err(format!(
+            "Backup has schema version {}, this version of the app can restore 0 to {}",
+            check.schema_version, SCHEMA_VERSION
+        ));
+    }
+
+    let _lock = lock_database().map_err(|e| format!("{}; stop it before restoring", e))?;
+    let settings = backup_settings(pool).await.map_err(|e| format!("Database error: {}", e))?;
+    let safety = create_backup(pool, &settings.dir).await?;
+    pool.close().await;
+
+    // מעתיקים לקובץ זמני ליד המסד ואז מחליפים בפעולה אחת, כך שלא נשאר מסד חצי מועתק
+    let temp = format!("{}.restore", DB_PATH);
+    std::fs::copy(path, &temp).map_err(|e| format!("Cannot copy {}: {}", path.display(), e))?;
+    std::fs::rename(&temp, DB_PATH).map_err(|e| format!("Cannot replace {}: {}", DB_PATH, e))?;
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", DB_PATH, suffix));
+    }
+
+    let restored = connect().await.map_err(|e| format!("Cannot open the restored database: {}", e))?;
+    migrate(&restored).await.map_err(|e| format!("Cannot migrate the restored database: {}", e))?;
+    restored.close().await;
+    Ok(safety)
+}
+
+async fn run_scheduled_backup(pool: &SqlitePool) -> Result<Option<BackupInfo>, String> {
+    let settings = backup_settings(pool).await.map_err(|e| format!("Database error: {}", e))?;
+    if settings.interval_hours == 0 {
+        return Ok(None);
+    }
+    // לפי הגיבוי האחרון ולא לפי זמן העלייה, כדי שהפעלה מחדש לא תדחה או תכפיל גיבויים
+    let interval = Duration::from_secs(settings.interval_hours * 3600);
+    let due = list_backups(&settings.dir).first().is_none_or(|latest| {
+        let age = Local::now().naive_local() - latest.created_at;
+        age.to_std().is_ok_and(|age| age >= interval)
+    });
+    if !due {
+        return Ok(None);
+    }
+
+    let backup = create_backup(pool, &settings.dir).await?;
+    prune_backups(&settings.dir, settings.keep);
+    Ok(Some(backup))
+}
+
+/// Background task that takes a backup every `backup_interval_hours` and applies the retention policy.
+pub async fn run_backup_scheduler(pool: SqlitePool) {
+    loop {
+        match run_scheduled_backup(&pool).await {
+            Ok(Some(backup)) => println!("💾 Scheduled backup written to {}", backup.file),
+            Ok(None) => {}
+            Err(e) => eprintln!("❌ Scheduled backup failed: {}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(SCHEDULER_CHECK_MINUTES * 60)).await;
+    }
+}
+
+// אין שחזור דרך ה-API: החלפת הקובץ מתחת לשרת רץ תשבור חיבורים פתוחים, לכן זה רק בפקודת CLI
+
+#[post("/")]
+pub async fn create_backup_now(pool: web::Data<SqlitePool>) -> impl Responder {
+    let settings = match backup_settings(&pool).await {
+        Ok(settings) => settings,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    match create_backup(&pool, &settings.dir).await {
+        Ok(backup) => {
+            prune_backups(&settings.dir, settings.keep);
+            HttpResponse::Created().json(backup)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(e),
+    }
+}
+
+#[get("/")]
+pub async fn get_backups(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    match backup_settings(&pool).await {
+        Ok(settings) => list_response(&list_backups(&settings.dir), &req, "backups"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{file}/verify")]
+pub async fn verify_backup_file(pool: web::Data<SqlitePool>, path: web::Path<String>) -> impl Responder {
+    let file = path.into_inner();
+    let settings = match backup_settings(&pool).await {
+        Ok(settings) => settings,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let path = settings.dir.join(&file);
+    if !is_backup_name(&file) || !path.is_file() {
+        return HttpResponse::NotFound().body("Backup not found");
+    }
+    match verify_backup(&path).await {
+        Ok(check) => HttpResponse::Ok().json(BackupCheck { file, ..check }),
+        Err(e) => HttpResponse::InternalServerError().body(e),
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use crate::controllers::animal_controller::{animal_from_row, ANIMAL_COLUMNS};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::models::animal::Animal;
+use crate::models::breeding::{
+    BreedingPair, CreateBreedingPairRequest, PairingEvaluation, PairingQuery, PedigreeNode, PedigreeQuery,
+};
+
+const DEFAULT_GENERATIONS: u32 = 3;
+const MAX_GENERATIONS: u32 = 10;
+
+// מזהה חיה -> (אב, אם) עבור כל החיות בגן
+pub type ParentMap = HashMap<i64, (Option<i64>, Option<i64>)>;
+
+pub async fn load_parents(conn: &mut SqliteConnection) -> Result<ParentMap, sqlx::Error> {
+    let rows = sqlx::query("SELECT id, sire_id, dam_id FROM animals")
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows.iter().map(|row| (row.get("id"), (row.get("sire_id"), row.get("dam_id")))).collect())
+}
+
+/// True when `ancestor` appears anywhere in the pedigree of `animal`.
+pub fn is_ancestor(parents: &ParentMap, ancestor: i64, animal: i64) -> bool {
+    let mut stack = vec![animal];
+    let mut seen = HashSet::new();
+    while let Some(current) = stack.pop() {
+        if !seen.insert(current) {
+            continue;
+        }
+        if let Some((sire, dam)) = parents.get(&current) {
+            for parent in [sire, dam].into_iter().flatten() {
+                if *parent == ancestor {
+                    return true;
+                }
+                stack.push(*parent);
+            }
+        }
+    }
+    false
+}
+
+/// Coefficients of kinship over the in-house pedigree; unknown and external parents count as unrelated founders.
+struct Kinship<'a> {
+    parents: &'a ParentMap,
+    depths: HashMap<i64, usize>,
+    cache: HashMap<(i64, i64), f64>,
+    visiting: HashSet<(i64, i64)>,
+}
+
+impl<'a> Kinship<'a> {
+    fn new(parents: &'a ParentMap) -> Self {
+        Kinship { parents, depths: HashMap::new(), cache: HashMap::new(), visiting: HashSet::new() }
+    }
+
+    fn parents_of(&self, id: i64) -> (Option<i64>, Option<i64>) {
+        self.parents.get(&id).copied().unwrap_or((None, None))
+    }
+
+    // מספר הדורות הארוך ביותר מעל החיה (מייסד = 0)
+    fn depth(&mut self, id: i64) -> usize {
+        if let Some(depth) = self.depths.get(&id) {
+            return *depth;
+        }
+        // מונע לולאה אינסופית אם יש מעגל בנתונים
+        self.depths.insert(id, 0);
+        let (sire, dam) = self.parents_of(id);
+        let depth = [sire, dam]
+            .into_iter()
+            .flatten()
+            .map(|parent| self.depth(parent) + 1)
+            .max()
+            .unwrap_or(0);
+        self.depths.insert(id, depth);
+        depth
+    }
+
+    fn kinship(&mut self, a: Option<i64>, b: Option<i64>) -> f64 {
+        let (Some(a), Some(b)) = (a, b) else {
+            return 0.0;
+        };
+        let key = (a.min(b), a.max(b));
+        if let Some(value) = self.cache.get(&key) {
+            return *value;
+        }
+        // זוג שכבר בחישוב פירושו מעגל בנתוני ההורים - הענף הזה נספר כלא קרוב
+        if !self.visiting.insert(key) {
+            return 0.0;
+        }
+
+        let value = if a == b {
+            0.5 * (1.0 + self.inbreeding(a))
+        } else {
+            // פותחים תמיד את הצעיר מבין השניים, שלא יכול להיות אב קדמון של השני
+            let (older, younger) = if self.depth(a) >= self.depth(b) { (b, a) } else { (a, b) };
+            let (sire, dam) = self.parents_of(younger);
+            0.5 * (self.kinship(Some(older), sire) + self.kinship(Some(older), dam))
+        };
+        self.visiting.remove(&key);
+        self.cache.insert(key, value);
+        value
+    }
+
+    fn inbreeding(&mut self, id: i64) -> f64 {
+        let (sire, dam) = self.parents_of(id);
+        self.kinship(sire, dam)
+    }
+}
+
+fn build_pedigree(animals: &HashMap<i64, Animal>, id: i64, generations: u32) -> Option<PedigreeNode> {
+    let animal = animals.get(&id)?;
+    let parent_node = |parent_id: Option<i64>, external: &Option<String>| -> Option<Box<PedigreeNode>> {
+        if generations == 0 {
+            return None;
+        }
+        match (parent_id, external) {
+            (Some(parent_id), _) => build_pedigree(animals, parent_id, generations - 1).map(Box::new),
+            (None, Some(external)) => Some(Box::new(PedigreeNode {
+                id: None,
+                name: external.clone(),
+                species: None,
+                sire: None,
+                dam: None,
+            })),
+            (None, None) => None,
+        }
+    };
+
+    Some(PedigreeNode {
+        id: Some(animal.id),
+        name: animal.name.clone(),
+        species: Some(animal.species.clone()),
+        sire: parent_node(animal.sire_id, &animal.sire_external),
+        dam: parent_node(animal.dam_id, &animal.dam_external),
+    })
+}
+
+#[get("/{id}/pedigree")]
+pub async fn get_pedigree(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    query: web::Query<PedigreeQuery>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let generations = query.generations.unwrap_or(DEFAULT_GENERATIONS).min(MAX_GENERATIONS);
+
+    match sqlx::query(&format!("SELECT {} FROM animals", ANIMAL_COLUMNS))
+        .fetch_all(&**pool)
+        .await
+    {
+        Ok(rows) => {
+            let animals: HashMap<i64, Animal> = rows.iter().map(|row| {
+                let animal = animal_from_row(row);
+                (animal.id, animal)
+            }).collect();
+            match build_pedigree(&animals, id, generations) {
+                Some(pedigree) => HttpResponse::Ok().json(pedigree),
+                None => HttpResponse::NotFound().body("Animal not found"),
+            }
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/{id}/offspring")]
+pub async fn get_offspring(pool: web::Data<SqlitePool>, path: web::Path<i64>, req: HttpRequest) -> impl Responder {
+    let id = path.into_inner();
+    let sql = format!("SELECT {} FROM animals WHERE sire_id = ? OR dam_id = ? ORDER BY id", ANIMAL_COLUMNS);
+    query_response(&pool, &req, sql, sql_args![id, id], animal_from_row, "offspring").await
+}
+
+// זוג הוא שתי חיות שונות מאותו מין
+async fn check_pair(pool: &SqlitePool, sire_id: i64, dam_id: i64) -> Result<(), HttpResponse> {
+    if sire_id == dam_id {
+        return Err(HttpResponse::BadRequest().body("Sire and dam must be different animals"));
+    }
+
+    match sqlx::query("SELECT COUNT(DISTINCT species) AS species_count, COUNT(*) AS animal_count FROM animals WHERE id IN (?, ?)")
+        .bind(sire_id)
+        .bind(dam_id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) if row.get::<i64, _>("animal_count") < 2 => Err(HttpResponse::NotFound().body("Animal not found")),
+        Ok(row) if row.get::<i64, _>("species_count") > 1 => {
+            Err(HttpResponse::BadRequest().body("Sire and dam must be the same species"))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(HttpResponse::InternalServerError().body(format!("Database error: {}", e))),
+    }
+}
+
+#[post("/pairs")]
+pub async fn create_breeding_pair(pool: web::Data<SqlitePool>, pair: web::Json<CreateBreedingPairRequest>) -> impl Responder {
+    if let Err(response) = check_pair(&pool, pair.sire_id, pair.dam_id).await {
+        return response;
+    }
+
+    match sqlx::query("INSERT INTO breeding_pairs (sire_id, dam_id, start_date, end_date, notes) VALUES (?, ?, ?, ?, ?)")
+        .bind(pair.sire_id)
+        .bind(pair.dam_id)
+        .bind(pair.start_date)
+        .bind(pair.end_date)
+        .bind(&pair.notes)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_pair = BreedingPair {
+                id: result.last_insert_rowid(),
+                sire_id: pair.sire_id,
+                dam_id: pair.dam_id,
+                start_date: pair.start_date,
+                end_date: pair.end_date,
+                notes: pair.notes.clone(),
+            };
+            emit_event(&pool, "breeding_pair.created", &new_pair).await;
+            HttpResponse::Created().json(new_pair)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn breeding_pair_from_row(row: &SqliteRow) -> BreedingPair {
+    BreedingPair {
+        id: row.get("id"),
+        sire_id: row.get("sire_id"),
+        dam_id: row.get("dam_id"),
+        start_date: row.get("start_date"),
+        end_date: row.get("end_date"),
+        notes: row.get("notes"),
+    }
+}
+
+#[get("/pairs")]
+pub async fn get_breeding_pairs(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, sire_id, dam_id, start_date, end_date, notes FROM breeding_pairs ORDER BY start_date, id".to_string();
+    query_response(&pool, &req, sql, sql_args![], breeding_pair_from_row, "breeding_pairs").await
+}
+
+#[get("/evaluate")]
+pub async fn evaluate_pairing(pool: web::Data<SqlitePool>, query: web::Query<PairingQuery>) -> impl Responder {
+    if let Err(response) = check_pair(&pool, query.sire_id, query.dam_id).await {
+        return response;
+    }
+
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let parents = match load_parents(&mut conn).await {
+        Ok(parents) => parents,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let mut kinship = Kinship::new(&parents);
+    let evaluation = PairingEvaluation {
+        sire_id: query.sire_id,
+        dam_id: query.dam_id,
+        sire_inbreeding: kinship.inbreeding(query.sire_id),
+        dam_inbreeding: kinship.inbreeding(query.dam_id),
+        offspring_inbreeding: kinship.kinship(Some(query.sire_id), Some(query.dam_id)),
+    };
+    HttpResponse::Ok().json(evaluation)
+}
@@ -0,0 +1,262 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use chrono::{Local, NaiveDate, TimeDelta};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{list_response, query_response, sql_args};
+use crate::controllers::vaccination_controller::{parse_within, MAX_WITHIN_DAYS};
+use crate::models::certification::{
+    Certification, CertificationRequirement, CreateCertificationRequest, CreateCertificationRequirementRequest,
+    ExpiringCertification, ExpiringQuery,
+};
+use crate::models::event::DeletedEntity;
+
+pub const DEFAULT_EXPIRING_WITHIN_DAYS: i64 = 30;
+const ACTIVITIES: [&str; 2] = ["feeding", "medical_treatment"];
+
+fn certification_from_row(row: &SqliteRow) -> Certification {
+    Certification {
+        id: row.get("id"),
+        staff_id: row.get("staff_id"),
+        name: row.get("name"),
+        issued_date: row.get("issued_date"),
+        expiry_date: row.get("expiry_date"),
+    }
+}
+
+/// Certificates required to do `activity` with the animal that the staff member does not hold on `on`.
+pub async fn missing_certifications(
+    conn: &mut SqliteConnection,
+    staff_id: i64,
+    animal_id: i64,
+    activity: &str,
+    on: NaiveDate,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query("SELECT DISTINCT r.certification FROM certification_requirements r
+        JOIN animals a ON a.id = ?
+        WHERE (r.species IS NULL OR r.species = a.species)
+            AND (r.enclosure_id IS NULL OR r.enclosure_id = a.enclosure_id)
+            AND (r.activity IS NULL OR r.activity = ?)
+            AND NOT EXISTS (
+                SELECT 1 FROM certifications c
+                WHERE c.staff_id = ? AND c.name = r.certification COLLATE NOCASE
+                    AND c.issued_date <= ? AND (c.expiry_date IS NULL OR c.expiry_date >= ?)
+            )
+        ORDER BY r.certification")
+        .bind(animal_id)
+        .bind(activity)
+        .bind(staff_id)
+        .bind(on)
+        .bind(on)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows.iter().map(|row| row.get("certification")).collect())
+}
+
+pub fn missing_certifications_message(missing: &[String]) -> String {
+    format!("Staff member lacks a valid required certification: {}", missing.join(", "))
+}
+
+/// Certificates expiring on or before `horizon` that have not been renewed; `expired` is relative to `today`.
+pub async fn find_expiring_certifications(
+    conn: &mut SqliteConnection,
+    today: NaiveDate,
+    horizon: NaiveDate,
+) -> Result<Vec<ExpiringCertification>, sqlx::Error> {
+    // הסמכה שכבר חודשה (יש אחרת באותו שם שפגה מאוחר יותר) לא מופיעה
+    let rows = sqlx::query("SELECT c.id, c.staff_id, c.name, c.issued_date, c.expiry_date,
+            s.first_name || ' ' || s.last_name AS staff_name
+        FROM certifications c
+        JOIN staff s ON s.id = c.staff_id
+        WHERE c.expiry_date IS NOT NULL AND c.expiry_date <= ?
+            AND NOT EXISTS (
+                SELECT 1 FROM certifications n
+                WHERE n.staff_id = c.staff_id AND n.name = c.name COLLATE NOCASE AND n.id != c.id
+                    AND (n.expiry_date IS NULL OR n.expiry_date > c.expiry_date)
+            )
+        ORDER BY c.expiry_date, c.id")
+        .bind(horizon)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows.iter().map(|row| {
+        let certification = certification_from_row(row);
+        ExpiringCertification {
+            expired: certification.expiry_date.is_some_and(|expiry| expiry < today),
+            staff_name: row.get("staff_name"),
+            certification,
+        }
+    }).collect())
+}
+
+#[post("/{id}/certifications")]
+pub async fn create_certification(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    certification: web::Json<CreateCertificationRequest>,
+) -> impl Responder {
+    let staff_id = path.into_inner();
+    if certification.name.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Certification name is required");
+    }
+    if certification.expiry_date.is_some_and(|expiry| expiry < certification.issued_date) {
+        return HttpResponse::BadRequest().body("Certification must expire after it is issued");
+    }
+
+    match sqlx::query("SELECT id FROM staff WHERE id = ?")
+        .bind(staff_id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Staff not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    match sqlx::query("INSERT INTO certifications (staff_id, name, issued_date, expiry_date) VALUES (?, ?, ?, ?)")
+        .bind(staff_id)
+        .bind(certification.name.trim())
+        .bind(certification.issued_date)
+        .bind(certification.expiry_date)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_certification = Certification {
+                id: result.last_insert_rowid(),
+                staff_id,
+                name: certification.name.trim().to_string(),
+                issued_date: certification.issued_date,
+                expiry_date: certification.expiry_date,
+            };
+            emit_event(&pool, "certification.created", &new_certification).await;
+            HttpResponse::Created().json(new_certification)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/{id}/certifications")]
+pub async fn get_staff_certifications(pool: web::Data<SqlitePool>, path: web::Path<i64>, req: HttpRequest) -> impl Responder {
+    let staff_id = path.into_inner();
+    let sql = "SELECT id, staff_id, name, issued_date, expiry_date FROM certifications WHERE staff_id = ? ORDER BY name, issued_date".to_string();
+    query_response(&pool, &req, sql, sql_args![staff_id], certification_from_row, "staff_certifications").await
+}
+
+#[delete("/certifications/{id}")]
+pub async fn delete_certification(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query("DELETE FROM certifications WHERE id = ?")
+        .bind(id)
+        .execute(&**pool)
+        .await
+    {
+        Ok(res) if res.rows_affected() > 0 => {
+            emit_event(&pool, "certification.deleted", &DeletedEntity { id, animal_id: None, enclosure_id: None }).await;
+            HttpResponse::Ok().body("Deleted")
+        }
+        Ok(_) => HttpResponse::NotFound().body("Certification not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/certifications/expiring")]
+pub async fn get_expiring_certifications(pool: web::Data<SqlitePool>, query: web::Query<ExpiringQuery>, req: HttpRequest) -> impl Responder {
+    let within_days = match &query.within {
+        Some(within) => match parse_within(within) {
+            Some(days) => days,
+            None => return HttpResponse::BadRequest().body(format!("Invalid within (use e.g. 30d or 4w, up to {} days)", MAX_WITHIN_DAYS)),
+        },
+        None => DEFAULT_EXPIRING_WITHIN_DAYS,
+    };
+    let today = Local::now().date_naive();
+    let Some(horizon) = TimeDelta::try_days(within_days).and_then(|delta| today.checked_add_signed(delta)) else {
+        return HttpResponse::BadRequest().body("Within is out of range");
+    };
+
+    match pool.acquire().await {
+        Ok(mut conn) => match find_expiring_certifications(&mut conn, today, horizon).await {
+            Ok(expiring) => list_response(&expiring, &req, "expiring_certifications"),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        },
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/certifications/requirements")]
+pub async fn create_certification_requirement(
+    pool: web::Data<SqlitePool>,
+    requirement: web::Json<CreateCertificationRequirementRequest>,
+) -> impl Responder {
+    if requirement.certification.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Certification name is required");
+    }
+    if requirement.species.is_none() && requirement.enclosure_id.is_none() {
+        return HttpResponse::BadRequest().body("Give species, enclosure_id or both");
+    }
+    if requirement.activity.as_deref().is_some_and(|a| !ACTIVITIES.contains(&a)) {
+        return HttpResponse::BadRequest().body("Invalid activity (use feeding or medical_treatment)");
+    }
+
+    match sqlx::query("INSERT INTO certification_requirements (certification, species, enclosure_id, activity) VALUES (?, ?, ?, ?)")
+        .bind(requirement.certification.trim())
+        .bind(&requirement.species)
+        .bind(requirement.enclosure_id)
+        .bind(&requirement.activity)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_requirement = CertificationRequirement {
+                id: result.last_insert_rowid(),
+                certification: requirement.certification.trim().to_string(),
+                species: requirement.species.clone(),
+                enclosure_id: requirement.enclosure_id,
+                activity: requirement.activity.clone(),
+            };
+            emit_event(&pool, "certification_requirement.created", &new_requirement).await;
+            HttpResponse::Created().json(new_requirement)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn certification_requirement_from_row(row: &SqliteRow) -> CertificationRequirement {
+    CertificationRequirement {
+        id: row.get("id"),
+        certification: row.get("certification"),
+        species: row.get("species"),
+        enclosure_id: row.get("enclosure_id"),
+        activity: row.get("activity"),
+    }
+}
+
+#[get("/certifications/requirements")]
+pub async fn get_certification_requirements(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, certification, species, enclosure_id, activity FROM certification_requirements ORDER BY id".to_string();
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![],
+        certification_requirement_from_row,
+        "certification_requirements",
+    )
+    .await
+}
+
+#[delete("/certifications/requirements/{id}")]
+pub async fn delete_certification_requirement(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query("DELETE FROM certification_requirements WHERE id = ? RETURNING enclosure_id")
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => {
+            let deleted = DeletedEntity { id, animal_id: None, enclosure_id: row.get("enclosure_id") };
+            emit_event(&pool, "certification_requirement.deleted", &deleted).await;
+            HttpResponse::Ok().body("Deleted")
+        }
+        Ok(None) => HttpResponse::NotFound().body("Requirement not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
@@ -1,23 +1,51 @@
-use actix_web::{get, post, put, delete, web, HttpResponse, Responder};
-use sqlx::{SqlitePool, Row};
+use actix_web::{get, post, put, delete, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use crate::controllers::maintenance_controller::{is_out_of_service, out_of_service_sql};
+use crate::controllers::event_controller::emit_event;
+use crate::db::references_to;
+use crate::controllers::export_controller::{query_response, sql_args};
 use crate::models::enclosure::{Enclosure, CreateEnclosureRequest, UpdateEnclosureRequest};
+use crate::models::event::DeletedEntity;
 
-#[post("/")]
-pub async fn create_enclosure(pool: web::Data<SqlitePool>, enclosure: web::Json<CreateEnclosureRequest>) -> impl Responder {
-    match sqlx::query("INSERT INTO enclosures (name, enclosure_type, capacity) VALUES (?, ?, ?)")
+pub fn enclosure_from_row(row: &SqliteRow) -> Enclosure {
+    Enclosure {
+        id: row.get("id"),
+        name: row.get("name"),
+        enclosure_type: row.get("enclosure_type"),
+        capacity: row.get("capacity"),
+        is_quarantine: row.get("is_quarantine"),
+        out_of_service: row.get("out_of_service"),
+    }
+}
+
+pub async fn insert_enclosure(conn: &mut SqliteConnection, enclosure: &CreateEnclosureRequest) -> Result<Enclosure, sqlx::Error> {
+    let result = sqlx::query("INSERT INTO enclosures (name, enclosure_type, capacity, is_quarantine) VALUES (?, ?, ?, ?)")
         .bind(&enclosure.name)
         .bind(&enclosure.enclosure_type)
-        .bind(&enclosure.capacity)
-        .execute(&**pool)
-        .await
-    {
-        Ok(result) => {
-            let new_enclosure = Enclosure {
-                id: result.last_insert_rowid(),
-                name: enclosure.name.clone(),
-                enclosure_type: enclosure.enclosure_type.clone(),
-                capacity: enclosure.capacity.clone(),
-            };
+        .bind(enclosure.capacity)
+        .bind(enclosure.is_quarantine)
+        .execute(&mut *conn)
+        .await?;
+    Ok(Enclosure {
+        id: result.last_insert_rowid(),
+        name: enclosure.name.clone(),
+        enclosure_type: enclosure.enclosure_type.clone(),
+        capacity: enclosure.capacity,
+        is_quarantine: enclosure.is_quarantine,
+        out_of_service: false,
+    })
+}
+
+#[post("/")]
+pub async fn create_enclosure(pool: web::Data<SqlitePool>, enclosure: web::Json<CreateEnclosureRequest>) -> impl Responder {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match insert_enclosure(&mut conn, &enclosure).await {
+        Ok(new_enclosure) => {
+            emit_event(&pool, "enclosure.created", &new_enclosure).await;
             HttpResponse::Created().json(new_enclosure)
         }
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
@@ -25,48 +53,21 @@ pub async fn create_enclosure(pool: web::Data<SqlitePool>, enclosure: web::Json<
 }
 
 #[get("/")]
-pub async fn get_enclosures(pool: web::Data<SqlitePool>) -> impl Responder {
-    match sqlx::query("SELECT id, name, enclosure_type, capacity FROM enclosures ORDER BY id")
-        .fetch_all(&**pool)
-        .await
-    {
-        Ok(rows) => {
-            let enclosures: Vec<Enclosure> = rows.iter().map(|row| Enclosure {
-                id: row.get("id"),
-                name: row.get("name"),
-                enclosure_type: row.get("enclosure_type"),
-                capacity: row.get("capacity"),
-            }).collect();
-            HttpResponse::Ok().json(enclosures)
-        }
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    }
+pub async fn get_enclosures(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = format!("SELECT e.id, e.name, e.enclosure_type, e.capacity, e.is_quarantine, {} AS out_of_service FROM enclosures e ORDER BY e.id", out_of_service_sql("e.id"));
+    query_response(&pool, &req, sql, sql_args![], enclosure_from_row, "enclosures").await
 }
 
 #[get("/full")]
 pub async fn get_full_enclosure(
-    pool: web::Data<SqlitePool>
+    pool: web::Data<SqlitePool>,
+    req: HttpRequest,
 ) -> impl Responder {
-    match sqlx::query("SELECT e.* FROM enclosures e 
-        JOIN animals a ON e.id = a.enclosure_id 
+    let sql = format!("SELECT e.*, {} AS out_of_service FROM enclosures e
+        JOIN animals a ON e.id = a.enclosure_id AND a.status = 'resident'
         GROUP BY a.enclosure_id
-        HAVING COUNT(a.enclosure_id) = e.capacity")
-        .fetch_all(&**pool)
-        .await
-    {
-        Ok(rows) => {
-            let enclosures: Vec<Enclosure> = rows.iter().map(|row| Enclosure {
-                id: row.get("id"),
-                name: row.get("name"),
-                enclosure_type: row.get("enclosure_type"),
-                capacity: row.get("capacity")
-            }).collect();
-
-            HttpResponse::Ok().json(enclosures)
-        }
-        Err(e) => HttpResponse::InternalServerError()
-            .body(format!("Database error: {}", e))
-    }
+        HAVING COUNT(a.enclosure_id) = e.capacity", out_of_service_sql("e.id"));
+    query_response(&pool, &req, sql, sql_args![], enclosure_from_row, "full_enclosures").await
 }
 
 #[get("/{id}")]
@@ -75,8 +76,8 @@ pub async fn get_enclosure_by_id(
     path: web::Path<i64>,
 ) -> impl Responder {
     let id = path.into_inner();
-    match sqlx::query("SELECT id, name, enclosure_type, capacity FROM enclosures WHERE id = ?")
-        .bind(&id)
+    match sqlx::query(&format!("SELECT e.id, e.name, e.enclosure_type, e.capacity, e.is_quarantine, {} AS out_of_service FROM enclosures e WHERE e.id = ?", out_of_service_sql("e.id")))
+        .bind(id)
         .fetch_optional(&**pool)
         .await
     {
@@ -86,6 +87,8 @@ pub async fn get_enclosure_by_id(
                 name: row.get("name"),
                 enclosure_type: row.get("enclosure_type"),
                 capacity: row.get("capacity"),
+                is_quarantine: row.get("is_quarantine"),
+                out_of_service: row.get("out_of_service"),
             };
             HttpResponse::Ok().json(enclosure)
         }
@@ -102,27 +105,37 @@ pub async fn update_enclosure(
 ) -> impl Responder {
     let id = path.into_inner();
 
-    match sqlx::query("UPDATE enclosures SET name = ?, enclosure_type = ?, capacity = ? WHERE id = ?")
+    // בלי is_quarantine הסימון הקיים נשמר
+    match sqlx::query("UPDATE enclosures SET name = ?, enclosure_type = ?, capacity = ?, is_quarantine = COALESCE(?, is_quarantine)
+        WHERE id = ? RETURNING is_quarantine")
         .bind(&updated.name)
         .bind(&updated.enclosure_type)
-        .bind(&updated.capacity)
-        .bind(&id)
-        .execute(&**pool)
+        .bind(updated.capacity)
+        .bind(updated.is_quarantine)
+        .bind(id)
+        .fetch_optional(&**pool)
         .await
     {
-        Ok(result) => {
-            if result.rows_affected() > 0 {
-                let updated_enclosure = Enclosure {
-                    id: id,
-                    name: updated.name.clone(),
-                    enclosure_type: updated.enclosure_type.clone(),
-                    capacity: updated.capacity,
-                };
-                HttpResponse::Ok().json(updated_enclosure)
-            } else {
-                HttpResponse::NotFound().body("Enclosure not found")
-            }
+        Ok(Some(row)) => {
+            let out_of_service = match pool.acquire().await {
+                Ok(mut conn) => match is_out_of_service(&mut conn, id).await {
+                    Ok(out_of_service) => out_of_service,
+                    Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+                },
+                Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+            };
+            let updated_enclosure = Enclosure {
+                id,
+                name: updated.name.clone(),
+                enclosure_type: updated.enclosure_type.clone(),
+                capacity: updated.capacity,
+                is_quarantine: row.get("is_quarantine"),
+                out_of_service,
+            };
+            emit_event(&pool, "enclosure.updated", &updated_enclosure).await;
+            HttpResponse::Ok().json(updated_enclosure)
         }
+        Ok(None) => HttpResponse::NotFound().body("Enclosure not found"),
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
@@ -133,18 +146,43 @@ pub async fn delete_enclosure(
     path: web::Path<i64>,
 ) -> impl Responder {
     let id = path.into_inner();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // שיוכי שומרים ודרישות הסמכה הם הגדרות של המתחם ונמחקים איתו; בדיקות, תקלות וכל השאר הם היסטוריה
+    for child_table in ["enclosure_assignments", "certification_requirements"] {
+        if let Err(e) = sqlx::query(&format!("DELETE FROM {} WHERE enclosure_id = ?", child_table))
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        {
+            return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+        }
+    }
+    match references_to(&mut tx, "enclosures", id).await {
+        Ok(references) if references.is_empty() => {}
+        Ok(references) => {
+            return HttpResponse::Conflict().body(format!("Enclosure is still referenced by {}", references.join(", ")))
+        }
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
     match sqlx::query("DELETE FROM enclosures WHERE id = ?")
-        .bind(&id)
-        .execute(&**pool)
+        .bind(id)
+        .execute(&mut *tx)
         .await
     {
-        Ok(result) => {
-            if result.rows_affected() > 0 {
+        Ok(result) if result.rows_affected() > 0 => match tx.commit().await {
+            Ok(_) => {
+                emit_event(&pool, "enclosure.deleted", &DeletedEntity { id, animal_id: None, enclosure_id: Some(id) }).await;
                 HttpResponse::Ok().body("Deleted successfully")
-            } else {
-                HttpResponse::NotFound().body("Enclosure not found")
             }
-        }
+            Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        },
+        Ok(_) => HttpResponse::NotFound().body("Enclosure not found"),
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
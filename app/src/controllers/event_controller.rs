@@ -0,0 +1,170 @@
+use std::convert::Infallible;
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::Duration;
+use actix_web::{get, web, web::Bytes, HttpResponse, Responder};
+use chrono::Local;
+use futures_util::stream;
+use serde::Serialize;
+use sqlx::{SqlitePool, Row};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::timeout;
+use crate::controllers::webhook_controller::queue_event;
+use crate::models::event::{EventStreamQuery, LiveEvent};
+
+// אין אירועים לקריאות חיישן גולמיות (קריאה חריגה מגיעה כ-sensor_alert), ולאבחנות ולתרופות שנוצרות או נמחקות
+// יחד עם הטיפול - הן חלק מ-medical_treatment.created ו-medical_treatment.deleted
+pub const ENTITIES: [&str; 28] = [
+    "animal",
+    "animal_assignment",
+    "animal_event",
+    "breeding_pair",
+    "certification",
+    "certification_requirement",
+    "defect",
+    "diagnosis",
+    "enclosure",
+    "enclosure_assignment",
+    "feeding",
+    "feeding_rule",
+    "feeding_schedule",
+    "food_item",
+    "measurement",
+    "medical_treatment",
+    "medication",
+    "quarantine",
+    "sensor_alert",
+    "shift",
+    "species_range",
+    "staff",
+    "stock_lot",
+    "supplier",
+    "task",
+    "vaccination",
+    "vaccine",
+    "vaccine_interval",
+];
+pub const ACTIONS: [&str; 3] = ["created", "updated", "deleted"];
+
+// מאזין איטי שמפספס יותר מזה מקבל אירוע lagged
+const EVENT_BUFFER: usize = 256;
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+// ערוץ אחד לכל התהליך - כל חיבור SSE נרשם אליו
+static EVENTS: LazyLock<broadcast::Sender<LiveEvent>> = LazyLock::new(|| broadcast::channel(EVENT_BUFFER).0);
+// המזהה נלקח והאירוע נשלח תחת אותה נעילה, כך שהמאזינים מקבלים אותם לפי הסדר
+static NEXT_EVENT_ID: Mutex<u64> = Mutex::new(1);
+
+struct EventFilter {
+    entities: Option<Vec<String>>,
+    enclosure_id: Option<i64>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &LiveEvent) -> bool {
+        self.entities.as_ref().is_none_or(|entities| entities.contains(&event.entity))
+            && self
+                .enclosure_id
+                .is_none_or(|id| event.enclosure_id == Some(id) || event.previous_enclosure_id == Some(id))
+    }
+}
+
+// המתחם של האירוע: המתחם עצמו, השדה enclosure_id, או המתחם של החיה (ישירות או דרך הטיפול)
+async fn enclosure_of(pool: &SqlitePool, entity: &str, data: &serde_json::Value) -> Result<Option<i64>, sqlx::Error> {
+    if entity == "enclosure" {
+        return Ok(data["id"].as_i64());
+    }
+    if let Some(enclosure_id) = data["enclosure_id"].as_i64() {
+        return Ok(Some(enclosure_id));
+    }
+    if let Some(animal_id) = data["animal_id"].as_i64() {
+        return Ok(sqlx::query("SELECT enclosure_id FROM animals WHERE id = ?")
+            .bind(animal_id)
+            .fetch_optional(pool)
+            .await?
+            .map(|row| row.get("enclosure_id")));
+    }
+    let Some(treatment_id) = data["treatment_id"].as_i64() else {
+        return Ok(None);
+    };
+    Ok(sqlx::query("SELECT a.enclosure_id FROM medical_treatments m JOIN animals a ON a.id = m.animal_id WHERE m.id = ?")
+        .bind(treatment_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| row.get("enclosure_id")))
+}
+
+/// Publishes a change event to the live stream and queues it for matching webhook subscriptions.
+pub async fn emit_event<T: Serialize>(pool: &SqlitePool, event_type: &'static str, data: &T) {
+    publish(pool, event_type, data, None).await
+}
+
+/// Like [`emit_event`] for a change that moved an animal out of `previous_enclosure_id`, so that the old
+/// enclosure's stream sees it leave.
+pub async fn emit_move_event<T: Serialize>(pool: &SqlitePool, event_type: &'static str, data: &T, previous_enclosure_id: i64) {
+    publish(pool, event_type, data, Some(previous_enclosure_id)).await
+}
+
+async fn publish<T: Serialize>(pool: &SqlitePool, event_type: &'static str, data: &T, previous_enclosure_id: Option<i64>) {
+    let occurred_at = Local::now().naive_local();
+    let data = match serde_json::to_value(data) {
+        Ok(data) => data,
+        Err(e) => return eprintln!("⚠️ Cannot serialize {} event: {}", event_type, e),
+    };
+    let entity = event_type.split_once('.').map_or(event_type, |(entity, _)| entity);
+    // המתחם נקבע לפני השליחה, כדי שאירוע של בקשה אחת לא יעקוף אירוע קודם בגלל שאילתה איטית
+    let enclosure_id = enclosure_of(pool, entity, &data).await.unwrap_or_else(|e| {
+        eprintln!("⚠️ Cannot find enclosure for {} event: {}", event_type, e);
+        None
+    });
+    {
+        let mut next_id = NEXT_EVENT_ID.lock().unwrap_or_else(PoisonError::into_inner);
+        // אין מאזינים כרגע - לא שגיאה
+        let _ = EVENTS.send(LiveEvent {
+            id: *next_id,
+            event: event_type.to_string(),
+            entity: entity.to_string(),
+            enclosure_id,
+            previous_enclosure_id: previous_enclosure_id.filter(|previous| enclosure_id != Some(*previous)),
+            occurred_at,
+            data: data.clone(),
+        });
+        *next_id += 1;
+    }
+    queue_event(pool, event_type, occurred_at, &data).await;
+}
+
+#[get("/stream")]
+pub async fn stream_events(query: web::Query<EventStreamQuery>) -> impl Responder {
+    let entities: Option<Vec<String>> = query
+        .entity
+        .as_deref()
+        .map(|entity| entity.split(',').map(|e| e.trim().to_lowercase()).collect());
+    if let Some(invalid) = entities.iter().flatten().find(|e| !ENTITIES.contains(&e.as_str())) {
+        return HttpResponse::BadRequest().body(format!("Invalid entity '{}' (use {})", invalid, ENTITIES.join(", ")));
+    }
+    let filter = EventFilter { entities, enclosure_id: query.enclosure_id };
+
+    let events = stream::unfold((EVENTS.subscribe(), filter), |(mut receiver, filter)| async move {
+        loop {
+            let chunk = match timeout(KEEP_ALIVE, receiver.recv()).await {
+                // שורת הערה שומרת את החיבור פתוח ומגלה לקוחות שהתנתקו
+                Err(_) => ": keep-alive\n\n".to_string(),
+                Ok(Ok(event)) if filter.matches(&event) => format!(
+                    "id: {}\nevent: {}\ndata: {}\n\n",
+                    event.id,
+                    event.event,
+                    serde_json::to_string(&event).unwrap_or_default()
+                ),
+                Ok(Ok(_)) => continue,
+                Ok(Err(RecvError::Lagged(missed))) => format!("event: lagged\ndata: {{\"missed\":{}}}\n\n", missed),
+                Ok(Err(RecvError::Closed)) => return None,
+            };
+            return Some((Ok::<_, Infallible>(Bytes::from(chunk)), (receiver, filter)));
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events)
+}
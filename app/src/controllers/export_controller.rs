@@ -0,0 +1,396 @@
+use std::future::Future;
+use std::io::Write;
+use std::sync::{Arc, Mutex, PoisonError};
+use actix_web::{get, web, web::Bytes, HttpRequest, HttpResponse, Responder};
+use chrono::{Datelike, Local, Timelike};
+use futures_util::{stream, Stream, StreamExt};
+use serde::Serialize;
+use sqlx::{error::BoxDynError, sqlite::{SqliteArguments, SqliteConnection, SqliteRow}, SqlitePool, Row};
+use tokio::sync::mpsc;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+use crate::models::export::ExportQuery;
+
+// כמה שורות מוכנות מחכות ללקוח איטי לפני שהקריאה מהמסד נעצרת
+const EXPORT_BUFFER: usize = 64;
+// Excel מזהה UTF-8 (ועברית) רק עם BOM בתחילת הקובץ
+const UTF8_BOM: &str = "\u{FEFF}";
+// מעל הגודל הזה מה שנכתב לקובץ הייצוא נשלח ללקוח
+const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+// סודות לא יוצאים מהמסד: מפתח החתימה של webhook, ויעדי ערוצי ההתראות שיש בהם טוקנים וכתובות
+const REDACTED_COLUMNS: [(&str, &str); 2] = [("webhook_subscriptions", "secret"), ("alert_channels", "target")];
+
+pub type ExportStream = std::pin::Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+}
+
+impl ExportFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Jsonl => "application/x-ndjson",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// The export format asked for by `?format=` or the Accept header; `None` keeps the regular JSON array.
+pub fn requested_export(format: Option<&str>, req: &HttpRequest) -> Result<Option<ExportFormat>, String> {
+    match format.map(str::to_lowercase).as_deref() {
+        Some("json") => return Ok(None),
+        Some("csv") => return Ok(Some(ExportFormat::Csv)),
+        Some("jsonl") => return Ok(Some(ExportFormat::Jsonl)),
+        Some(_) => return Err("Invalid format (use json, csv or jsonl)".to_string()),
+        None => {}
+    }
+    let accept = req
+        .headers()
+        .get("Accept")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    if accept.contains("text/csv") {
+        Ok(Some(ExportFormat::Csv))
+    } else if accept.contains("application/x-ndjson") || accept.contains("application/jsonl") {
+        Ok(Some(ExportFormat::Jsonl))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Like [`requested_export`], for list endpoints whose own query has no `format` field.
+pub fn export_format(req: &HttpRequest) -> Result<Option<ExportFormat>, String> {
+    let query = web::Query::<ExportQuery>::from_query(req.query_string()).map_err(|e| e.to_string())?;
+    requested_export(query.format.as_deref(), req)
+}
+
+// שורת הכותרת של T גם כשאין אף רשומה: מה שנכתב עם כותרת, פחות מה שנכתב בלעדיה
+fn csv_header<T: Serialize + Default>() -> Result<Vec<u8>, String> {
+    let with_header = encode_record(&T::default(), ExportFormat::Csv, true)?;
+    let record = encode_record(&T::default(), ExportFormat::Csv, false)?;
+    Ok(with_header[..with_header.len() - record.len()].to_vec())
+}
+
+// תא בודד: מחרוזות כמו שהן, מערכים ואובייקטים מקוננים כ-JSON, ריק במקום null
+fn csv_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// שורת CSV עוברת דרך JSON כי ה-serializer של csv לא תומך ב-flatten ובשדות של רשימות
+fn csv_fields<T: Serialize>(record: &T) -> Result<Vec<(String, String)>, String> {
+    match serde_json::to_value(record).map_err(|e| e.to_string())? {
+        serde_json::Value::Object(fields) => Ok(fields.iter().map(|(key, value)| (key.clone(), csv_cell(value))).collect()),
+        other => Ok(vec![("value".to_string(), csv_cell(&other))]),
+    }
+}
+
+fn encode_record<T: Serialize>(record: &T, format: ExportFormat, first: bool) -> Result<Vec<u8>, String> {
+    match format {
+        ExportFormat::Csv => {
+            let fields = csv_fields(record)?;
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            // שורת הכותרת נכתבת רק לפני הרשומה הראשונה
+            if first {
+                writer.write_record(fields.iter().map(|(key, _)| key)).map_err(|e| e.to_string())?;
+            }
+            writer.write_record(fields.iter().map(|(_, value)| value)).map_err(|e| e.to_string())?;
+            let mut bytes = writer.into_inner().map_err(|e| e.to_string())?;
+            if first {
+                bytes.splice(0..0, UTF8_BOM.bytes());
+            }
+            Ok(bytes)
+        }
+        ExportFormat::Jsonl => {
+            let mut bytes = serde_json::to_vec(record).map_err(|e| e.to_string())?;
+            bytes.push(b'\n');
+            Ok(bytes)
+        }
+    }
+}
+
+/// Streams the query results row by row in `format` instead of collecting them first.
+pub fn stream_export<T: Serialize + Default + Send + 'static>(
+    pool: &SqlitePool,
+    sql: String,
+    args: SqliteArguments<'static>,
+    to_record: fn(&SqliteRow) -> T,
+    format: ExportFormat,
+    name: &str,
+) -> HttpResponse {
+    let (sender, receiver) = mpsc::channel::<Result<Bytes, std::io::Error>>(EXPORT_BUFFER);
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        let mut rows = sqlx::query_with(&sql, args).fetch(&pool);
+        let mut first = true;
+        while let Some(row) = rows.next().await {
+            let chunk = row
+                .map_err(|e| format!("Database error: {}", e))
+                .and_then(|row| encode_record(&to_record(&row), format, first));
+            first = false;
+            // שגיאה באמצע קוטעת את התשובה, כדי שהלקוח לא יקבל קובץ חלקי כאילו הוא שלם
+            let failed = chunk.is_err();
+            let chunk = chunk.map(Bytes::from).map_err(std::io::Error::other);
+            if sender.send(chunk).await.is_err() || failed {
+                return;
+            }
+        }
+        // רשימה ריקה ב-CSV היא עדיין קובץ עם כותרת
+        if first && matches!(format, ExportFormat::Csv) {
+            let _ = sender.send(csv_header::<T>().map(Bytes::from).map_err(std::io::Error::other)).await;
+        }
+    });
+
+    let body = stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.{}\"", name, format.extension())))
+        .streaming(body)
+}
+
+/// Builds the `SqliteArguments` of a query from its values in order, like a chain of `.bind()` calls.
+macro_rules! sql_args {
+    ($($value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut args = sqlx::sqlite::SqliteArguments::default();
+        Ok::<_, sqlx::error::BoxDynError>(())
+            $(.and_then(|_| sqlx::Arguments::add(&mut args, $value)))*
+            .map(|_| args)
+    }};
+}
+pub(crate) use sql_args;
+
+/// Runs `sql` and answers with the rows as a JSON array, or streams them with [`stream_export`] when the request
+/// asks for an export format. The list endpoints that read straight from the database go through here.
+pub async fn query_response<T: Serialize + Default + Send + 'static>(
+    pool: &SqlitePool,
+    req: &HttpRequest,
+    sql: String,
+    args: Result<SqliteArguments<'static>, BoxDynError>,
+    to_record: fn(&SqliteRow) -> T,
+    name: &str,
+) -> HttpResponse {
+    let args = match args {
+        Ok(args) => args,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    match export_format(req) {
+        Ok(Some(format)) => stream_export(pool, sql, args, to_record, format, name),
+        Ok(None) => match sqlx::query_with(&sql, args).fetch_all(pool).await {
+            Ok(rows) => HttpResponse::Ok().json(rows.iter().map(to_record).collect::<Vec<T>>()),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        },
+        Err(message) => HttpResponse::BadRequest().body(message),
+    }
+}
+
+/// Answers with `records` as a JSON array, or in the export format the request asks for. Only for lists that are
+/// computed in memory (filtered, nested or aggregated after the query); plain queries use [`query_response`].
+pub fn list_response<T: Serialize + Default>(records: &[T], req: &HttpRequest, name: &str) -> HttpResponse {
+    let format = match export_format(req) {
+        Ok(Some(format)) => format,
+        Ok(None) => return HttpResponse::Ok().json(records),
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
+    let body = if records.is_empty() && matches!(format, ExportFormat::Csv) {
+        csv_header::<T>()
+    } else {
+        records
+            .iter()
+            .enumerate()
+            .map(|(index, record)| encode_record(record, format, index == 0))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|chunks| chunks.concat())
+    };
+    match body {
+        Ok(body) => HttpResponse::Ok()
+            .content_type(format.content_type())
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.{}\"", name, format.extension())))
+            .body(body),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Export error: {}", e)),
+    }
+}
+
+// ערך של עמודה כטקסט, בלי לדעת מראש את הסוג שלה
+fn cell_text(row: &SqliteRow, index: usize) -> String {
+    if let Ok(value) = row.try_get::<Option<String>, _>(index) {
+        return value.unwrap_or_default();
+    }
+    if let Ok(value) = row.try_get::<Option<i64>, _>(index) {
+        return value.map(|v| v.to_string()).unwrap_or_default();
+    }
+    if let Ok(value) = row.try_get::<Option<f64>, _>(index) {
+        return value.map(|v| v.to_string()).unwrap_or_default();
+    }
+    row.try_get::<Option<Vec<u8>>, _>(index)
+        .ok()
+        .flatten()
+        .map(hex::encode)
+        .unwrap_or_default()
+}
+
+// כותב שאוסף את מה שנכתב אליו, כדי שהמשימה שמייצרת את הייצוא תעביר אותו ללקוח בחתיכות
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self, at_least: usize) -> Option<Vec<u8>> {
+        let mut buffer = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        (!buffer.is_empty() && buffer.len() >= at_least).then(|| std::mem::take(&mut *buffer))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct ExportSink {
+    buffer: SharedBuffer,
+    sender: mpsc::Sender<Result<Bytes, std::io::Error>>,
+}
+
+impl ExportSink {
+    // שולח את מה שהצטבר אם יש לפחות at_least בתים; שגיאה אם הלקוח כבר התנתק
+    async fn drain(&self, at_least: usize) -> Result<(), String> {
+        match self.buffer.take(at_least) {
+            Some(bytes) => self.sender.send(Ok(Bytes::from(bytes))).await.map_err(|_| "Client disconnected".to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+// מריץ את היצרן ברקע ומחזיר את מה שהוא כותב כזרם; שגיאה באמצע קוטעת את הזרם
+fn spawn_export<F, Fut>(produce: F) -> ExportStream
+where
+    F: FnOnce(ExportSink) -> Fut,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(EXPORT_BUFFER);
+    let sink = ExportSink { buffer: SharedBuffer::default(), sender: sender.clone() };
+    let producer = produce(sink);
+    tokio::spawn(async move {
+        if let Err(e) = producer.await {
+            let _ = sender.send(Err(std::io::Error::other(e))).await;
+        }
+    });
+    Box::pin(stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    }))
+}
+
+// העמודות שיוצאות מהטבלה, בלי העמודות הסודיות
+async fn exported_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>, String> {
+    Ok(sqlx::query(&format!("SELECT name FROM pragma_table_info('{}') ORDER BY cid", table))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .iter()
+        .map(|row| row.get::<String, _>("name"))
+        .filter(|column| !REDACTED_COLUMNS.contains(&(table, column.as_str())))
+        .collect())
+}
+
+// כותב טבלה אחת כ-CSV אל out, ומעביר ל-sink את מה שהצטבר תוך כדי
+async fn write_table_csv<W: Write>(conn: &mut SqliteConnection, table: &str, out: W, sink: &ExportSink) -> Result<(), String> {
+    let columns = exported_columns(conn, table).await?;
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(&columns).map_err(|e| e.to_string())?;
+    let sql = format!(
+        "SELECT {} FROM \"{}\"",
+        columns.iter().map(|column| format!("\"{}\"", column)).collect::<Vec<_>>().join(", "),
+        table
+    );
+    let mut rows = sqlx::query(&sql).fetch(&mut *conn);
+    while let Some(row) = rows.next().await {
+        let row = row.map_err(|e| format!("Database error: {}", e))?;
+        writer
+            .write_record((0..row.len()).map(|index| cell_text(&row, index)))
+            .map_err(|e| e.to_string())?;
+        sink.drain(EXPORT_CHUNK_BYTES).await?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+/// One table as CSV, with its column names as the header row, streamed as it is read.
+pub fn table_csv(pool: &SqlitePool, table: &str) -> ExportStream {
+    let (pool, table) = (pool.clone(), table.to_string());
+    spawn_export(move |sink| async move {
+        let mut out = sink.buffer.clone();
+        out.write_all(UTF8_BOM.as_bytes()).map_err(|e| e.to_string())?;
+        let mut conn = pool.acquire().await.map_err(|e| format!("Database error: {}", e))?;
+        write_table_csv(&mut conn, &table, out, &sink).await?;
+        sink.drain(0).await
+    })
+}
+
+/// Zip with one CSV file per table of the database, streamed as it is built. All the tables are read inside one
+/// transaction, so the bundle is a consistent snapshot even while other requests keep writing.
+pub fn build_bundle(pool: &SqlitePool) -> ExportStream {
+    let pool = pool.clone();
+    spawn_export(move |sink| async move {
+        // ב-SQLite טרנזקציה שרק קוראת מחזיקה תמונת מצב אחת מהקריאה הראשונה ועד הסוף
+        let mut tx = pool.begin().await.map_err(|e| format!("Database error: {}", e))?;
+        let tables: Vec<String> = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .iter()
+            .map(|row| row.get("name"))
+            .collect();
+
+        // בלי seek: כל קובץ נכתב עם data descriptor, וכך אפשר לשלוח את הארכיון תוך כדי בנייה
+        let mut zip = ZipWriter::new_stream(sink.buffer.clone());
+        let now = Local::now().naive_local();
+        let mut options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        // בלי זמן מפורש הקבצים בארכיון מתוארכים ל-1980
+        if let Ok(modified) = zip::DateTime::from_date_and_time(
+            now.year() as u16,
+            now.month() as u8,
+            now.day() as u8,
+            now.hour() as u8,
+            now.minute() as u8,
+            now.second() as u8,
+        ) {
+            options = options.last_modified_time(modified);
+        }
+        for table in &tables {
+            zip.start_file(format!("{}.csv", table), options).map_err(|e| e.to_string())?;
+            zip.write_all(UTF8_BOM.as_bytes()).map_err(|e| e.to_string())?;
+            write_table_csv(&mut tx, table, &mut zip, &sink).await?;
+        }
+        tx.commit().await.map_err(|e| format!("Database error: {}", e))?;
+        zip.finish().map_err(|e| e.to_string())?;
+        sink.drain(0).await
+    })
+}
+
+#[get("/bundle")]
+pub async fn export_bundle(pool: web::Data<SqlitePool>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"zoo-export-{}.zip\"", Local::now().format("%Y%m%d-%H%M")),
+        ))
+        .streaming(build_bundle(&pool))
+}
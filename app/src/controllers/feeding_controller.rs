@@ -1,53 +1,227 @@
-use actix_web::{get, post, put, delete, web, HttpResponse, Responder};
-use sqlx::{SqlitePool, Row};
-use chrono::NaiveDateTime;
-use crate::models::feeding::{Feeding, CreateFeedingRequest, UpdateFeedingRequest};
+use actix_web::{get, post, put, delete, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use chrono::{Local, NaiveDateTime};
+use crate::controllers::certification_controller::{missing_certifications, missing_certifications_message};
+use crate::controllers::assignment_controller::{default_keeper, load_active_keepers, KeeperMap};
+use crate::controllers::shift_controller::{check_on_shift, warning_header, ShiftCheck};
+use crate::controllers::inventory_controller::{consume_stock, return_stock};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::models::assignment::KeeperQuery;
+use crate::models::feeding::{
+    CreateFeedingQuery, CreateFeedingRequest, CreateFeedingRuleRequest, DoubleFeeding, Feeding, FeedingAnomalies,
+    FeedingConflict, FeedingRule, UnfedAnimal, UpdateFeedingRequest,
+};
+use crate::models::event::DeletedEntity;
+
+// מרווחי ברירת מחדל כשאין כלל מוגדר לחיה או למין שלה
+const DEFAULT_MIN_INTERVAL_MINUTES: i64 = 60;
+const DEFAULT_MAX_INTERVAL_HOURS: i64 = 24;
+
+// כלל לפי חיה גובר על כלל לפי מין (a = טבלת animals)
+fn rule_value_sql(column: &str, default: i64) -> String {
+    format!(
+        "COALESCE(
+            (SELECT r.{column} FROM feeding_rules r WHERE r.animal_id = a.id ORDER BY r.id DESC LIMIT 1),
+            (SELECT r.{column} FROM feeding_rules r WHERE r.animal_id IS NULL AND r.species = a.species ORDER BY r.id DESC LIMIT 1),
+            {default})"
+    )
+}
+
+/// Resident animals not fed within the max interval of their feeding rule (or never fed).
+pub async fn find_unfed_animals(conn: &mut SqliteConnection, keepers: &KeeperMap) -> Result<Vec<UnfedAnimal>, sqlx::Error> {
+    let unfed_sql = format!(
+        "SELECT * FROM (
+            SELECT a.id, a.name, a.species, a.enclosure_id,
+                (SELECT MAX(f.feeding_time) FROM feedings f WHERE f.animal_id = a.id) AS last_feeding_time,
+                {} AS max_interval_hours
+            FROM animals a
+            WHERE a.status = 'resident'
+        )
+        WHERE last_feeding_time IS NULL
+            OR CAST(strftime('%s', ?) AS INTEGER) - CAST(strftime('%s', last_feeding_time) AS INTEGER) > 3600 * max_interval_hours
+        ORDER BY id",
+        rule_value_sql("max_interval_hours", DEFAULT_MAX_INTERVAL_HOURS)
+    );
+
+    let rows = sqlx::query(&unfed_sql)
+        .bind(Local::now().naive_local())
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows.iter().map(|row| UnfedAnimal {
+        animal_id: row.get("id"),
+        name: row.get("name"),
+        species: row.get("species"),
+        last_feeding_time: row.get("last_feeding_time"),
+        max_interval_hours: row.get("max_interval_hours"),
+        keeper_ids: keepers.of_animal(row.get("id"), row.get("enclosure_id")),
+    }).collect())
+}
+
+// מזון וכמות באים יחד, והכמות חיובית
+fn food_amount_error(food_item_id: Option<i64>, quantity: Option<f64>) -> Option<&'static str> {
+    if food_item_id.is_some() != quantity.is_some() {
+        return Some("food_item_id and quantity must be given together");
+    }
+    if quantity.is_some_and(|q| q <= 0.0) {
+        return Some("Quantity must be positive");
+    }
+    None
+}
+
+fn feeding_from_row(row: &SqliteRow) -> Feeding {
+    Feeding {
+        id: row.get("id"),
+        animal_id: row.get("animal_id"),
+        staff_id: row.get("staff_id"),
+        feeding_time: row.get("feeding_time"),
+        food_item_id: row.get("food_item_id"),
+        quantity: row.get("quantity"),
+    }
+}
 
 #[post("/")]
-pub async fn create_feeding(pool: web::Data<SqlitePool>, feeding: web::Json<CreateFeedingRequest>) -> impl Responder {
-    match sqlx::query("INSERT INTO feedings (animal_id, staff_id, feeding_time) VALUES (?, ?, ?)")
-        .bind(&feeding.animal_id)
-        .bind(&feeding.staff_id)
-        .bind(&feeding.feeding_time)
-        .execute(&**pool)
+pub async fn create_feeding(
+    pool: web::Data<SqlitePool>,
+    query: web::Query<CreateFeedingQuery>,
+    feeding: web::Json<CreateFeedingRequest>,
+) -> impl Responder {
+    if let Some(message) = food_amount_error(feeding.food_item_id, feeding.quantity) {
+        return HttpResponse::BadRequest().body(message);
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // מאכילים רק חיות שנמצאות בגן
+    let enclosure_id: i64 = match sqlx::query("SELECT status, enclosure_id FROM animals WHERE id = ?")
+        .bind(feeding.animal_id)
+        .fetch_optional(&mut *tx)
         .await
     {
-        Ok(result) => {
+        Ok(Some(row)) if row.get::<String, _>("status") == "resident" => row.get("enclosure_id"),
+        Ok(Some(_)) => return HttpResponse::Conflict().body("Animal is not resident"),
+        Ok(None) => return HttpResponse::NotFound().body("Animal not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // בלי staff_id - השומר האחראי על המתחם ביום ההאכלה
+    let staff_id = match feeding.staff_id {
+        Some(staff_id) => staff_id,
+        None => match default_keeper(&mut tx, feeding.animal_id, enclosure_id, feeding.feeding_time.date()).await {
+            Ok(Some(staff_id)) => staff_id,
+            Ok(None) => return HttpResponse::BadRequest().body("staff_id is required (no keeper is assigned to the enclosure)"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        },
+    };
+
+    // האם העובד היה במשמרת - לפי הגדרת shift_check (off / warn / reject)
+    let shift_warning = match check_on_shift(&mut tx, staff_id, feeding.feeding_time).await {
+        Ok(ShiftCheck::Ok) => None,
+        Ok(ShiftCheck::Warn(message)) => Some(message),
+        Ok(ShiftCheck::Reject(message)) => return HttpResponse::Conflict().body(message),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // הסמכות שנדרשות לעבודה עם המין / המתחם של החיה
+    match missing_certifications(&mut tx, staff_id, feeding.animal_id, "feeding", feeding.feeding_time.date()).await {
+        Ok(missing) if missing.is_empty() => {}
+        Ok(missing) => return HttpResponse::Forbidden().body(missing_certifications_message(&missing)),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    // בדיקת האכלה כפולה - אלא אם כן force=true
+    if !query.force.unwrap_or(false) {
+        let conflict_sql = format!(
+            "SELECT f.id, f.animal_id, f.staff_id, f.feeding_time, f.food_item_id, f.quantity FROM feedings f
+            JOIN animals a ON a.id = f.animal_id
+            WHERE f.animal_id = ?
+            AND ABS(CAST(strftime('%s', f.feeding_time) AS INTEGER) - CAST(strftime('%s', ?) AS INTEGER)) < 60 * {}
+            ORDER BY ABS(CAST(strftime('%s', f.feeding_time) AS INTEGER) - CAST(strftime('%s', ?) AS INTEGER))
+            LIMIT 1",
+            rule_value_sql("min_interval_minutes", DEFAULT_MIN_INTERVAL_MINUTES)
+        );
+        match sqlx::query(&conflict_sql)
+            .bind(feeding.animal_id)
+            .bind(feeding.feeding_time)
+            .bind(feeding.feeding_time)
+            .fetch_optional(&mut *tx)
+            .await
+        {
+            Ok(Some(row)) => {
+                let conflict = FeedingConflict {
+                    message: "Animal was already fed within the minimum interval (use ?force=true to override)".to_string(),
+                    conflicting_feeding: Feeding {
+                        id: row.get("id"),
+                        animal_id: row.get("animal_id"),
+                        staff_id: row.get("staff_id"),
+                        feeding_time: row.get("feeding_time"),
+                        food_item_id: row.get("food_item_id"),
+                        quantity: row.get("quantity"),
+                    },
+                };
+                return HttpResponse::Conflict().json(conflict);
+            }
+            Ok(None) => {}
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+
+    let result = match sqlx::query("INSERT INTO feedings (animal_id, staff_id, feeding_time, food_item_id, quantity) VALUES (?, ?, ?, ?, ?)")
+        .bind(feeding.animal_id)
+        .bind(staff_id)
+        .bind(feeding.feeding_time)
+        .bind(feeding.food_item_id)
+        .bind(feeding.quantity)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // הורדת כמות המזון מהמלאי
+    if let (Some(food_item_id), Some(quantity)) = (feeding.food_item_id, feeding.quantity) {
+        match consume_stock(&mut tx, result.last_insert_rowid(), food_item_id, quantity, feeding.feeding_time).await {
+            Ok(true) => {}
+            Ok(false) => return HttpResponse::Conflict().body("Not enough food in stock"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+
+    match tx.commit().await {
+        Ok(_) => {
             let new_feed = Feeding {
                 id: result.last_insert_rowid(),
-                animal_id: feeding.animal_id.clone(),
-                staff_id: feeding.staff_id.clone(),
-                feeding_time: feeding.feeding_time.clone(),
+                animal_id: feeding.animal_id,
+                staff_id,
+                feeding_time: feeding.feeding_time,
+                food_item_id: feeding.food_item_id,
+                quantity: feeding.quantity,
             };
-            HttpResponse::Created().json(new_feed)
+            let mut response = HttpResponse::Created();
+            if let Some(message) = shift_warning {
+                response.insert_header(warning_header(&message));
+            }
+            emit_event(&pool, "feeding.created", &new_feed).await;
+            response.json(new_feed)
         }
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
 
 #[get("/")]
-pub async fn get_feedings(pool: web::Data<SqlitePool>) -> impl Responder {
-    match sqlx::query("SELECT id, animal_id, staff_id, feeding_time FROM feedings ORDER BY id")
-        .fetch_all(&**pool)
-        .await
-    {
-        Ok(rows) => {
-            let feedings: Vec<Feeding> = rows.iter().map(|row| Feeding {
-                id: row.get("id"),
-                animal_id: row.get("animal_id"),
-                staff_id: row.get("staff_id"),
-                feeding_time: row.get("feeding_time"),
-            }).collect();
-            HttpResponse::Ok().json(feedings)
-        }
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    }
+pub async fn get_feedings(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, animal_id, staff_id, feeding_time, food_item_id, quantity FROM feedings ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], feeding_from_row, "feedings").await
 }
 
 #[get("/{id}")]
 pub async fn get_feeding_by_id(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
     let id = path.into_inner();
-    match sqlx::query("SELECT id, animal_id, staff_id, feeding_time FROM feedings WHERE id = ?")
+    match sqlx::query("SELECT id, animal_id, staff_id, feeding_time, food_item_id, quantity FROM feedings WHERE id = ?")
         .bind(id)
         .fetch_optional(&**pool)
         .await
@@ -58,6 +232,8 @@ pub async fn get_feeding_by_id(pool: web::Data<SqlitePool>, path: web::Path<i64>
                 animal_id: row.get("animal_id"),
                 staff_id: row.get("staff_id"),
                 feeding_time: row.get("feeding_time"),
+                food_item_id: row.get("food_item_id"),
+                quantity: row.get("quantity"),
             };
             HttpResponse::Ok().json(feeding)
         }
@@ -73,27 +249,74 @@ pub async fn update_feeding(
     updated: web::Json<UpdateFeedingRequest>,
 ) -> impl Responder {
     let id = path.into_inner();
+    if let Some(message) = food_amount_error(updated.food_item_id, updated.quantity) {
+        return HttpResponse::BadRequest().body(message);
+    }
 
-    match sqlx::query("UPDATE feedings SET animal_id = ?, staff_id = ?, feeding_time = ? WHERE id = ?")
-        .bind(&updated.animal_id)
-        .bind(&updated.staff_id)
-        .bind(&updated.feeding_time)
-        .bind(&id)
-        .execute(&**pool)
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // העובד החדש נבדק כמו ביצירה - לפי הגדרת shift_check (off / warn / reject)
+    let shift_warning = match check_on_shift(&mut tx, updated.staff_id, updated.feeding_time).await {
+        Ok(ShiftCheck::Ok) => None,
+        Ok(ShiftCheck::Warn(message)) => Some(message),
+        Ok(ShiftCheck::Reject(message)) => return HttpResponse::Conflict().body(message),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // הסמכות שנדרשות לעבודה עם המין / המתחם של החיה - לפי העובד והחיה החדשים
+    match missing_certifications(&mut tx, updated.staff_id, updated.animal_id, "feeding", updated.feeding_time.date()).await {
+        Ok(missing) if missing.is_empty() => {}
+        Ok(missing) => return HttpResponse::Forbidden().body(missing_certifications_message(&missing)),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    // מחזירים למלאי את מה שההאכלה לקחה ומורידים מחדש לפי הערכים החדשים
+    if let Err(e) = return_stock(&mut tx, id).await {
+        return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+    }
+
+    match sqlx::query("UPDATE feedings SET animal_id = ?, staff_id = ?, feeding_time = ?, food_item_id = ?, quantity = ? WHERE id = ?")
+        .bind(updated.animal_id)
+        .bind(updated.staff_id)
+        .bind(updated.feeding_time)
+        .bind(updated.food_item_id)
+        .bind(updated.quantity)
+        .bind(id)
+        .execute(&mut *tx)
         .await
     {
-        Ok(result) => {
-            if result.rows_affected() > 0 {
-                let updated_feeding = Feeding {
-                    id: id,
-                    animal_id: updated.animal_id.clone(),
-                    staff_id: updated.staff_id.clone(),
-                    feeding_time: updated.feeding_time,
-                };
-                HttpResponse::Ok().json(updated_feeding)
-            } else {
-                HttpResponse::NotFound().body("Feeding not found")
+        Ok(result) if result.rows_affected() > 0 => {}
+        Ok(_) => return HttpResponse::NotFound().body("Feeding not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    if let (Some(food_item_id), Some(quantity)) = (updated.food_item_id, updated.quantity) {
+        match consume_stock(&mut tx, id, food_item_id, quantity, updated.feeding_time).await {
+            Ok(true) => {}
+            Ok(false) => return HttpResponse::Conflict().body("Not enough food in stock"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+
+    match tx.commit().await {
+        Ok(_) => {
+            let updated_feeding = Feeding {
+                id,
+                animal_id: updated.animal_id,
+                staff_id: updated.staff_id,
+                feeding_time: updated.feeding_time,
+                food_item_id: updated.food_item_id,
+                quantity: updated.quantity,
+            };
+            let mut response = HttpResponse::Ok();
+            if let Some(message) = shift_warning {
+                response.insert_header(warning_header(&message));
             }
+            emit_event(&pool, "feeding.updated", &updated_feeding).await;
+            response.json(updated_feeding)
         }
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
@@ -102,13 +325,31 @@ pub async fn update_feeding(
 #[delete("/{id}")]
 pub async fn delete_feeding(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
     let id = path.into_inner();
-    match sqlx::query("DELETE FROM feedings WHERE id = ?")
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // המזון שנרשם להאכלה חוזר למלאי
+    if let Err(e) = return_stock(&mut tx, id).await {
+        return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+    }
+
+    let animal_id: i64 = match sqlx::query("DELETE FROM feedings WHERE id = ? RETURNING animal_id")
         .bind(id)
-        .execute(&**pool)
+        .fetch_optional(&mut *tx)
         .await
     {
-        Ok(res) if res.rows_affected() > 0 => HttpResponse::Ok().body("Deleted"),
-        Ok(_) => HttpResponse::NotFound().body("Feeding not found"),
+        Ok(Some(row)) => row.get("animal_id"),
+        Ok(None) => return HttpResponse::NotFound().body("Feeding not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match tx.commit().await {
+        Ok(_) => {
+            emit_event(&pool, "feeding.deleted", &DeletedEntity { id, animal_id: Some(animal_id), enclosure_id: None }).await;
+            HttpResponse::Ok().body("Deleted")
+        }
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
@@ -117,6 +358,7 @@ pub async fn delete_feeding(pool: web::Data<SqlitePool>, path: web::Path<i64>) -
 pub async fn get_feedings_after(
     pool: web::Data<SqlitePool>,
     path: web::Path<String>,
+    req: HttpRequest,
 ) -> impl Responder {
     let date_str = path.into_inner();
 
@@ -126,22 +368,124 @@ pub async fn get_feedings_after(
             .body("Invalid date format (use YYYY-MM-DDTHH:MM:SS)"),
     };
 
-    match sqlx::query("SELECT id, animal_id, staff_id, feeding_time FROM feedings WHERE feeding_time > ? ORDER BY feeding_time")
-        .bind(date_limit)
-        .fetch_all(&**pool)
+    let sql = "SELECT id, animal_id, staff_id, feeding_time, food_item_id, quantity FROM feedings WHERE feeding_time > ? ORDER BY feeding_time".to_string();
+    query_response(&pool, &req, sql, sql_args![date_limit], feeding_from_row, "feedings").await
+}
+
+#[post("/rules")]
+pub async fn create_feeding_rule(pool: web::Data<SqlitePool>, rule: web::Json<CreateFeedingRuleRequest>) -> impl Responder {
+    if rule.species.is_some() == rule.animal_id.is_some() {
+        return HttpResponse::BadRequest().body("Give exactly one of species or animal_id");
+    }
+    if rule.min_interval_minutes < 0 || rule.max_interval_hours <= 0 {
+        return HttpResponse::BadRequest().body("Intervals must be positive");
+    }
+
+    match sqlx::query("INSERT INTO feeding_rules (species, animal_id, min_interval_minutes, max_interval_hours) VALUES (?, ?, ?, ?)")
+        .bind(&rule.species)
+        .bind(rule.animal_id)
+        .bind(rule.min_interval_minutes)
+        .bind(rule.max_interval_hours)
+        .execute(&**pool)
         .await
     {
-        Ok(rows) => {
-            let feedings: Vec<Feeding> = rows.iter().map(|row| Feeding {
-                id: row.get("id"),
-                animal_id: row.get("animal_id"),
-                staff_id: row.get("staff_id"),
-                feeding_time: row.get("feeding_time"),
-            }).collect();
+        Ok(result) => {
+            let new_rule = FeedingRule {
+                id: result.last_insert_rowid(),
+                species: rule.species.clone(),
+                animal_id: rule.animal_id,
+                min_interval_minutes: rule.min_interval_minutes,
+                max_interval_hours: rule.max_interval_hours,
+            };
+            emit_event(&pool, "feeding_rule.created", &new_rule).await;
+            HttpResponse::Created().json(new_rule)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
 
-            HttpResponse::Ok().json(feedings)
+fn feeding_rule_from_row(row: &SqliteRow) -> FeedingRule {
+    FeedingRule {
+        id: row.get("id"),
+        species: row.get("species"),
+        animal_id: row.get("animal_id"),
+        min_interval_minutes: row.get("min_interval_minutes"),
+        max_interval_hours: row.get("max_interval_hours"),
+    }
+}
+
+#[get("/rules")]
+pub async fn get_feeding_rules(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, species, animal_id, min_interval_minutes, max_interval_hours FROM feeding_rules ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], feeding_rule_from_row, "feeding_rules").await
+}
+
+#[delete("/rules/{id}")]
+pub async fn delete_feeding_rule(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query("DELETE FROM feeding_rules WHERE id = ? RETURNING animal_id")
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => {
+            emit_event(&pool, "feeding_rule.deleted", &DeletedEntity { id, animal_id: row.get("animal_id"), enclosure_id: None }).await;
+            HttpResponse::Ok().body("Deleted")
         }
-        Err(e) => HttpResponse::InternalServerError()
-            .body(format!("Database error: {}", e)),
+        Ok(None) => HttpResponse::NotFound().body("Feeding rule not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
+
+#[get("/anomalies")]
+pub async fn get_feeding_anomalies(pool: web::Data<SqlitePool>, query: web::Query<KeeperQuery>) -> impl Responder {
+    // כל חריגה מנותבת לשומרים של החיה ושל המתחם שלה
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let keepers = match load_active_keepers(&mut conn, Local::now().date_naive()).await {
+        Ok(keepers) => keepers,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let double_sql = format!(
+        "SELECT f1.animal_id, r.enclosure_id, f1.id AS first_id, f2.id AS second_id,
+            f1.feeding_time AS first_time, f2.feeding_time AS second_time, r.min_interval_minutes
+        FROM (SELECT a.id AS animal_id, a.enclosure_id, {} AS min_interval_minutes FROM animals a) r
+        JOIN feedings f1 ON f1.animal_id = r.animal_id
+        JOIN feedings f2 ON f2.animal_id = f1.animal_id
+            AND (f2.feeding_time > f1.feeding_time OR (f2.feeding_time = f1.feeding_time AND f2.id > f1.id))
+        WHERE CAST(strftime('%s', f2.feeding_time) AS INTEGER) - CAST(strftime('%s', f1.feeding_time) AS INTEGER) < 60 * r.min_interval_minutes
+        ORDER BY f1.feeding_time",
+        rule_value_sql("min_interval_minutes", DEFAULT_MIN_INTERVAL_MINUTES)
+    );
+
+    let mut double_feedings: Vec<DoubleFeeding> = match sqlx::query(&double_sql)
+        .fetch_all(&mut *conn)
+        .await
+    {
+        Ok(rows) => rows.iter().map(|row| DoubleFeeding {
+            animal_id: row.get("animal_id"),
+            first_feeding_id: row.get("first_id"),
+            second_feeding_id: row.get("second_id"),
+            first_feeding_time: row.get("first_time"),
+            second_feeding_time: row.get("second_time"),
+            min_interval_minutes: row.get("min_interval_minutes"),
+            keeper_ids: keepers.of_animal(row.get("animal_id"), row.get("enclosure_id")),
+        }).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let mut unfed_animals = match find_unfed_animals(&mut conn, &keepers).await {
+        Ok(unfed_animals) => unfed_animals,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    if let Some(keeper_id) = query.keeper_id {
+        double_feedings.retain(|d| d.keeper_ids.contains(&keeper_id));
+        unfed_animals.retain(|u| u.keeper_ids.contains(&keeper_id));
+    }
+
+    HttpResponse::Ok().json(FeedingAnomalies { double_feedings, unfed_animals })
+}
@@ -0,0 +1,181 @@
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+use crate::controllers::animal_controller::{insert_animal, CreateAnimalError};
+use crate::controllers::enclosure_controller::insert_enclosure;
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::staff_controller::insert_staff;
+use crate::models::animal::CreateAnimalRequest;
+use crate::models::enclosure::CreateEnclosureRequest;
+use crate::models::import::{ImportQuery, ImportRowError, ImportSummary};
+use crate::models::staff::CreateStaffRequest;
+
+pub const IMPORT_ENTITIES: [&str; 3] = ["animals", "staff", "enclosures"];
+const FORMATS: [&str; 2] = ["csv", "jsonl"];
+
+pub struct ImportResult {
+    pub summary: ImportSummary,
+    pub event: &'static str,
+    pub created: Vec<serde_json::Value>,
+}
+
+// שורה שאפשר לייבא - נבדקת ונשמרת כמו ב-POST של הישות
+trait Importable: DeserializeOwned {
+    type Created: Serialize;
+    const EVENT: &'static str;
+
+    // שגיאה חיצונית עוצרת את כל הייבוא; שגיאה פנימית שייכת לשורה
+    async fn insert(&self, conn: &mut SqliteConnection) -> Result<Result<Self::Created, String>, sqlx::Error>;
+}
+
+// הפרת אילוץ (מפתח זר, ייחודיות, CHECK) היא בעיה של השורה ולא של השרת
+fn constraint_error<T>(result: Result<T, sqlx::Error>) -> Result<Result<T, String>, sqlx::Error> {
+    match result {
+        Ok(created) => Ok(Ok(created)),
+        Err(e) => match e.as_database_error() {
+            Some(db_error) => Ok(Err(db_error.message().to_string())),
+            None => Err(e),
+        },
+    }
+}
+
+impl Importable for CreateAnimalRequest {
+    type Created = crate::models::animal::Animal;
+    const EVENT: &'static str = "animal.created";
+
+    async fn insert(&self, conn: &mut SqliteConnection) -> Result<Result<Self::Created, String>, sqlx::Error> {
+        match insert_animal(conn, self).await {
+            Ok(animal) => Ok(Ok(animal)),
+            Err(CreateAnimalError::Invalid(message)) | Err(CreateAnimalError::Conflict(message)) => Ok(Err(message.to_string())),
+            Err(CreateAnimalError::Database(e)) => constraint_error(Err(e)),
+        }
+    }
+}
+
+impl Importable for CreateStaffRequest {
+    type Created = crate::models::staff::Staff;
+    const EVENT: &'static str = "staff.created";
+
+    async fn insert(&self, conn: &mut SqliteConnection) -> Result<Result<Self::Created, String>, sqlx::Error> {
+        constraint_error(insert_staff(conn, self).await)
+    }
+}
+
+impl Importable for CreateEnclosureRequest {
+    type Created = crate::models::enclosure::Enclosure;
+    const EVENT: &'static str = "enclosure.created";
+
+    async fn insert(&self, conn: &mut SqliteConnection) -> Result<Result<Self::Created, String>, sqlx::Error> {
+        constraint_error(insert_enclosure(conn, self).await)
+    }
+}
+
+/// Picks csv or jsonl: an explicit format wins, otherwise a hint (content type or file name) mentioning json means jsonl.
+pub fn import_format(requested: Option<&str>, hint: &str) -> Result<&'static str, String> {
+    match requested {
+        Some(format) => FORMATS
+            .iter()
+            .find(|f| f.eq_ignore_ascii_case(format))
+            .copied()
+            .ok_or_else(|| "Invalid format (use csv or jsonl)".to_string()),
+        None if hint.contains("json") => Ok("jsonl"),
+        None => Ok("csv"),
+    }
+}
+
+// כל שורה מפוענחת בנפרד, עם מספר השורה בקובץ
+fn parse_rows<T: DeserializeOwned>(data: &str, format: &str) -> Vec<(usize, Result<T, String>)> {
+    if format == "jsonl" {
+        return data
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| (index + 1, serde_json::from_str(line).map_err(|e| e.to_string())))
+            .collect();
+    }
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data.as_bytes());
+    reader
+        .deserialize()
+        .enumerate()
+        // שורה 1 היא הכותרת
+        .map(|(index, result)| (index + 2, result.map_err(|e: csv::Error| e.to_string())))
+        .collect()
+}
+
+async fn import_rows<T: Importable>(pool: &SqlitePool, entity: &str, data: &str, format: &str, dry_run: bool) -> Result<ImportResult, sqlx::Error> {
+    let rows = parse_rows::<T>(data, format);
+    let mut tx = pool.begin().await?;
+    let mut errors = Vec::new();
+    let mut created = Vec::new();
+
+    // כל השורות בטרנזקציה אחת, כך שגם התנגשויות בין שורות בקובץ נתפסות
+    for (row, parsed) in &rows {
+        let result = match parsed {
+            Ok(request) => request.insert(&mut tx).await?,
+            Err(e) => Err(e.clone()),
+        };
+        match result {
+            Ok(record) => created.push(serde_json::to_value(record).unwrap_or_default()),
+            Err(error) => errors.push(ImportRowError { row: *row, error }),
+        }
+    }
+
+    // הכול או כלום: שגיאה אחת או הרצת ניסיון מבטלות הכול
+    let imported = if errors.is_empty() && !dry_run {
+        tx.commit().await?;
+        created.len()
+    } else {
+        tx.rollback().await?;
+        created.clear();
+        0
+    };
+
+    Ok(ImportResult {
+        summary: ImportSummary { entity: entity.to_string(), dry_run, rows: rows.len(), imported, errors },
+        event: T::EVENT,
+        created,
+    })
+}
+
+/// Validates every row and stores them all in one transaction, or none if any row fails or on a dry run.
+pub async fn import_entity(pool: &SqlitePool, entity: &str, data: &str, format: &str, dry_run: bool) -> Result<ImportResult, sqlx::Error> {
+    match entity {
+        "animals" => import_rows::<CreateAnimalRequest>(pool, entity, data, format, dry_run).await,
+        "staff" => import_rows::<CreateStaffRequest>(pool, entity, data, format, dry_run).await,
+        _ => import_rows::<CreateEnclosureRequest>(pool, entity, data, format, dry_run).await,
+    }
+}
+
+#[post("/{entity}")]
+pub async fn import_data(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<String>,
+    query: web::Query<ImportQuery>,
+    req: HttpRequest,
+    body: String,
+) -> impl Responder {
+    let entity = path.into_inner();
+    if !IMPORT_ENTITIES.contains(&entity.as_str()) {
+        return HttpResponse::NotFound().body(format!("Cannot import {} (use {})", entity, IMPORT_ENTITIES.join(", ")));
+    }
+    let content_type = req
+        .headers()
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    let format = match import_format(query.format.as_deref(), content_type) {
+        Ok(format) => format,
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
+
+    match import_entity(&pool, &entity, &body, format, query.dry_run).await {
+        Ok(result) if result.summary.errors.is_empty() => {
+            for record in &result.created {
+                emit_event(&pool, result.event, record).await;
+            }
+            HttpResponse::Ok().json(result.summary)
+        }
+        Ok(result) => HttpResponse::UnprocessableEntity().json(result.summary),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
@@ -0,0 +1,327 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::models::inventory::{
+    CreateFeedingScheduleRequest, CreateFoodItemRequest, CreateStockLotRequest, CreateSupplierRequest,
+    ExpiringQuery, FeedingSchedule, FoodItem, StockForecast, StockLevel, StockLot, Supplier,
+};
+
+// ימי ברירת מחדל לחיפוש מלאי שפג תוקפו בקרוב
+const DEFAULT_EXPIRING_DAYS: i64 = 14;
+const MAX_EXPIRING_DAYS: i64 = 3650;
+// כמויות נשמרות כ-REAL; הפרש קטן מזה הוא שארית עיגול ולא מחסור
+const STOCK_EPSILON: f64 = 1e-9;
+
+/// Deducts `quantity` of a food item from the stock lots that are still valid at `at`,
+/// earliest expiry first, and records which lots the feeding took from. Returns `false`
+/// (and changes nothing) when there is not enough stock.
+pub async fn consume_stock(
+    conn: &mut SqliteConnection,
+    feeding_id: i64,
+    food_item_id: i64,
+    quantity: f64,
+    at: NaiveDateTime,
+) -> Result<bool, sqlx::Error> {
+    let lots = sqlx::query(
+        "SELECT id, quantity FROM stock_lots
+        WHERE food_item_id = ? AND quantity > 0 AND (expiry_date IS NULL OR expiry_date > ?)
+        ORDER BY expiry_date IS NULL, expiry_date, received_date, id",
+    )
+    .bind(food_item_id)
+    .bind(at)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let available: f64 = lots.iter().map(|row| row.get::<f64, _>("quantity")).sum();
+    if available + STOCK_EPSILON < quantity {
+        return Ok(false);
+    }
+
+    let mut remaining = quantity;
+    for lot in lots {
+        if remaining <= STOCK_EPSILON {
+            break;
+        }
+        let lot_id: i64 = lot.get("id");
+        let lot_quantity: f64 = lot.get("quantity");
+        let taken = lot_quantity.min(remaining);
+        sqlx::query("UPDATE stock_lots SET quantity = quantity - ? WHERE id = ?")
+            .bind(taken)
+            .bind(lot_id)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("INSERT INTO feeding_stock (feeding_id, stock_lot_id, quantity) VALUES (?, ?, ?)")
+            .bind(feeding_id)
+            .bind(lot_id)
+            .bind(taken)
+            .execute(&mut *conn)
+            .await?;
+        remaining -= taken;
+    }
+    Ok(true)
+}
+
+/// Gives back to stock whatever a feeding took, to the lots it came from. Feedings with no
+/// recorded lots (no food item, or none of its lots was valid when they happened) return nothing.
+pub async fn return_stock(conn: &mut SqliteConnection, feeding_id: i64) -> Result<(), sqlx::Error> {
+    let taken = sqlx::query("SELECT stock_lot_id, quantity FROM feeding_stock WHERE feeding_id = ?")
+        .bind(feeding_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    for row in &taken {
+        sqlx::query("UPDATE stock_lots SET quantity = quantity + ? WHERE id = ?")
+            .bind(row.get::<f64, _>("quantity"))
+            .bind(row.get::<i64, _>("stock_lot_id"))
+            .execute(&mut *conn)
+            .await?;
+    }
+    sqlx::query("DELETE FROM feeding_stock WHERE feeding_id = ?")
+        .bind(feeding_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+#[post("/suppliers")]
+pub async fn create_supplier(pool: web::Data<SqlitePool>, supplier: web::Json<CreateSupplierRequest>) -> impl Responder {
+    match sqlx::query("INSERT INTO suppliers (name, phone) VALUES (?, ?)")
+        .bind(&supplier.name)
+        .bind(&supplier.phone)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_supplier = Supplier {
+                id: result.last_insert_rowid(),
+                name: supplier.name.clone(),
+                phone: supplier.phone.clone(),
+            };
+            emit_event(&pool, "supplier.created", &new_supplier).await;
+            HttpResponse::Created().json(new_supplier)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn supplier_from_row(row: &SqliteRow) -> Supplier {
+    Supplier {
+        id: row.get("id"),
+        name: row.get("name"),
+        phone: row.get("phone"),
+    }
+}
+
+#[get("/suppliers")]
+pub async fn get_suppliers(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, name, phone FROM suppliers ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], supplier_from_row, "suppliers").await
+}
+
+#[post("/items")]
+pub async fn create_food_item(pool: web::Data<SqlitePool>, item: web::Json<CreateFoodItemRequest>) -> impl Responder {
+    if item.reorder_threshold < 0.0 {
+        return HttpResponse::BadRequest().body("Reorder threshold must not be negative");
+    }
+    match sqlx::query("INSERT INTO food_items (name, unit, reorder_threshold) VALUES (?, ?, ?)")
+        .bind(&item.name)
+        .bind(&item.unit)
+        .bind(item.reorder_threshold)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_item = FoodItem {
+                id: result.last_insert_rowid(),
+                name: item.name.clone(),
+                unit: item.unit.clone(),
+                reorder_threshold: item.reorder_threshold,
+            };
+            emit_event(&pool, "food_item.created", &new_item).await;
+            HttpResponse::Created().json(new_item)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn food_item_from_row(row: &SqliteRow) -> FoodItem {
+    FoodItem {
+        id: row.get("id"),
+        name: row.get("name"),
+        unit: row.get("unit"),
+        reorder_threshold: row.get("reorder_threshold"),
+    }
+}
+
+#[get("/items")]
+pub async fn get_food_items(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, name, unit, reorder_threshold FROM food_items ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], food_item_from_row, "food_items").await
+}
+
+#[post("/lots")]
+pub async fn create_stock_lot(pool: web::Data<SqlitePool>, lot: web::Json<CreateStockLotRequest>) -> impl Responder {
+    if lot.quantity <= 0.0 {
+        return HttpResponse::BadRequest().body("Quantity must be positive");
+    }
+
+    match sqlx::query("INSERT INTO stock_lots (food_item_id, supplier_id, quantity, received_date, expiry_date) VALUES (?, ?, ?, ?, ?)")
+        .bind(lot.food_item_id)
+        .bind(lot.supplier_id)
+        .bind(lot.quantity)
+        .bind(lot.received_date)
+        .bind(lot.expiry_date)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_lot = StockLot {
+                id: result.last_insert_rowid(),
+                food_item_id: lot.food_item_id,
+                supplier_id: lot.supplier_id,
+                quantity: lot.quantity,
+                received_date: lot.received_date,
+                expiry_date: lot.expiry_date,
+            };
+            emit_event(&pool, "stock_lot.created", &new_lot).await;
+            HttpResponse::Created().json(new_lot)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn stock_lot_from_row(row: &SqliteRow) -> StockLot {
+    StockLot {
+        id: row.get("id"),
+        food_item_id: row.get("food_item_id"),
+        supplier_id: row.get("supplier_id"),
+        quantity: row.get("quantity"),
+        received_date: row.get("received_date"),
+        expiry_date: row.get("expiry_date"),
+    }
+}
+
+#[get("/lots")]
+pub async fn get_stock_lots(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, food_item_id, supplier_id, quantity, received_date, expiry_date FROM stock_lots ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], stock_lot_from_row, "stock_lots").await
+}
+
+#[post("/schedules")]
+pub async fn create_feeding_schedule(
+    pool: web::Data<SqlitePool>,
+    schedule: web::Json<CreateFeedingScheduleRequest>,
+) -> impl Responder {
+    if schedule.quantity <= 0.0 {
+        return HttpResponse::BadRequest().body("Quantity must be positive");
+    }
+    if schedule.feedings_per_day <= 0 {
+        return HttpResponse::BadRequest().body("Feedings per day must be positive");
+    }
+    match sqlx::query("INSERT INTO feeding_schedules (animal_id, food_item_id, quantity, feedings_per_day) VALUES (?, ?, ?, ?)")
+        .bind(schedule.animal_id)
+        .bind(schedule.food_item_id)
+        .bind(schedule.quantity)
+        .bind(schedule.feedings_per_day)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_schedule = FeedingSchedule {
+                id: result.last_insert_rowid(),
+                animal_id: schedule.animal_id,
+                food_item_id: schedule.food_item_id,
+                quantity: schedule.quantity,
+                feedings_per_day: schedule.feedings_per_day,
+            };
+            emit_event(&pool, "feeding_schedule.created", &new_schedule).await;
+            HttpResponse::Created().json(new_schedule)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn feeding_schedule_from_row(row: &SqliteRow) -> FeedingSchedule {
+    FeedingSchedule {
+        id: row.get("id"),
+        animal_id: row.get("animal_id"),
+        food_item_id: row.get("food_item_id"),
+        quantity: row.get("quantity"),
+        feedings_per_day: row.get("feedings_per_day"),
+    }
+}
+
+#[get("/schedules")]
+pub async fn get_feeding_schedules(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, animal_id, food_item_id, quantity, feedings_per_day FROM feeding_schedules ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], feeding_schedule_from_row, "feeding_schedules").await
+}
+
+fn stock_level_from_row(row: &SqliteRow) -> StockLevel {
+    StockLevel {
+        food_item_id: row.get("id"),
+        name: row.get("name"),
+        unit: row.get("unit"),
+        quantity: row.get("quantity"),
+        reorder_threshold: row.get("reorder_threshold"),
+    }
+}
+
+#[get("/low_stock")]
+pub async fn get_low_stock(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let now = Local::now().naive_local();
+
+    let sql = "SELECT f.id, f.name, f.unit, f.reorder_threshold, COALESCE(SUM(l.quantity), 0.0) AS quantity
+        FROM food_items f
+        LEFT JOIN stock_lots l ON l.food_item_id = f.id AND (l.expiry_date IS NULL OR l.expiry_date > ?)
+        GROUP BY f.id
+        HAVING COALESCE(SUM(l.quantity), 0.0) <= f.reorder_threshold
+        ORDER BY f.id".to_string();
+    query_response(&pool, &req, sql, sql_args![now], stock_level_from_row, "low_stock").await
+}
+
+#[get("/expiring")]
+pub async fn get_expiring_stock(pool: web::Data<SqlitePool>, query: web::Query<ExpiringQuery>, req: HttpRequest) -> impl Responder {
+    let days = query.days.unwrap_or(DEFAULT_EXPIRING_DAYS);
+    if !(0..=MAX_EXPIRING_DAYS).contains(&days) {
+        return HttpResponse::BadRequest().body(format!("Days must be between 0 and {}", MAX_EXPIRING_DAYS));
+    }
+    let Some(limit) = TimeDelta::try_days(days).and_then(|delta| Local::now().naive_local().checked_add_signed(delta)) else {
+        return HttpResponse::BadRequest().body("Days is out of range");
+    };
+
+    let sql = "SELECT id, food_item_id, supplier_id, quantity, received_date, expiry_date FROM stock_lots
+        WHERE quantity > 0 AND expiry_date IS NOT NULL AND expiry_date <= ?
+        ORDER BY expiry_date".to_string();
+    query_response(&pool, &req, sql, sql_args![limit], stock_lot_from_row, "expiring_stock").await
+}
+
+fn stock_forecast_from_row(row: &SqliteRow) -> StockForecast {
+    let quantity: f64 = row.get("quantity");
+    let daily_consumption: f64 = row.get("daily_consumption");
+    StockForecast {
+        food_item_id: row.get("id"),
+        name: row.get("name"),
+        unit: row.get("unit"),
+        quantity,
+        daily_consumption,
+        days_remaining: (daily_consumption > 0.0).then(|| quantity / daily_consumption),
+    }
+}
+
+#[get("/forecast")]
+pub async fn get_stock_forecast(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let now = Local::now().naive_local();
+
+    let sql = "SELECT f.id, f.name, f.unit,
+            (SELECT COALESCE(SUM(l.quantity), 0.0) FROM stock_lots l
+                WHERE l.food_item_id = f.id AND (l.expiry_date IS NULL OR l.expiry_date > ?)) AS quantity,
+            (SELECT COALESCE(SUM(s.quantity * s.feedings_per_day), 0.0) FROM feeding_schedules s
+                JOIN animals a ON a.id = s.animal_id
+                WHERE s.food_item_id = f.id AND a.status = 'resident') AS daily_consumption
+        FROM food_items f
+        ORDER BY f.id".to_string();
+    query_response(&pool, &req, sql, sql_args![now], stock_forecast_from_row, "stock_forecast").await
+}
@@ -0,0 +1,339 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use chrono::Local;
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{list_response, query_response, sql_args};
+use crate::models::maintenance::{
+    CreateDefectRequest, CreateInspectionRequest, Defect, DefectListQuery, DefectStatusRequest, Inspection,
+    InspectionDetails, InspectionItem,
+};
+
+const DEFECT_COLUMNS: &str = "id, enclosure_id, inspection_id, description, severity, status, reported_date, resolved_date";
+const SEVERITIES: [&str; 4] = ["low", "medium", "high", "critical"];
+// פריט שנכשל בבדיקה בלי חומרה מפורשת
+const DEFAULT_ITEM_SEVERITY: &str = "medium";
+
+/// SQL expression that is true while the enclosure `enclosure_id_sql` has an unresolved high or critical defect.
+pub fn out_of_service_sql(enclosure_id_sql: &str) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM enclosure_defects d WHERE d.enclosure_id = {} AND d.status != 'resolved' AND d.severity IN ('high', 'critical'))",
+        enclosure_id_sql
+    )
+}
+
+pub async fn is_out_of_service(conn: &mut SqliteConnection, enclosure_id: i64) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(&format!("SELECT {} AS out_of_service", out_of_service_sql("?")))
+        .bind(enclosure_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(row.get("out_of_service"))
+}
+
+fn defect_from_row(row: &SqliteRow) -> Defect {
+    Defect {
+        id: row.get("id"),
+        enclosure_id: row.get("enclosure_id"),
+        inspection_id: row.get("inspection_id"),
+        description: row.get("description"),
+        severity: row.get("severity"),
+        status: row.get("status"),
+        reported_date: row.get("reported_date"),
+        resolved_date: row.get("resolved_date"),
+    }
+}
+
+fn item_from_row(row: &SqliteRow) -> InspectionItem {
+    InspectionItem {
+        id: row.get("id"),
+        inspection_id: row.get("inspection_id"),
+        item: row.get("item"),
+        passed: row.get("passed"),
+        notes: row.get("notes"),
+    }
+}
+
+#[post("/{id}/inspections")]
+pub async fn create_inspection(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    inspection: web::Json<CreateInspectionRequest>,
+) -> impl Responder {
+    let enclosure_id = path.into_inner();
+    if inspection.items.is_empty() {
+        return HttpResponse::BadRequest().body("An inspection needs at least one checklist item");
+    }
+    if inspection.items.iter().any(|i| i.item.trim().is_empty()) {
+        return HttpResponse::BadRequest().body("Checklist item name is required");
+    }
+    if inspection.items.iter().any(|i| i.severity.as_deref().is_some_and(|s| !SEVERITIES.contains(&s))) {
+        return HttpResponse::BadRequest().body("Invalid severity (use low, medium, high or critical)");
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match sqlx::query("SELECT id FROM enclosures WHERE id = ?")
+        .bind(enclosure_id)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Enclosure not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    let inspection_id = match sqlx::query("INSERT INTO enclosure_inspections (enclosure_id, inspection_date, inspector_id, notes) VALUES (?, ?, ?, ?)")
+        .bind(enclosure_id)
+        .bind(inspection.inspection_date)
+        .bind(inspection.inspector_id)
+        .bind(&inspection.notes)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(result) => result.last_insert_rowid(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let mut items = Vec::new();
+    let mut defects = Vec::new();
+    for item in &inspection.items {
+        match sqlx::query("INSERT INTO inspection_items (inspection_id, item, passed, notes) VALUES (?, ?, ?, ?)")
+            .bind(inspection_id)
+            .bind(&item.item)
+            .bind(item.passed)
+            .bind(&item.notes)
+            .execute(&mut *tx)
+            .await
+        {
+            Ok(result) => items.push(InspectionItem {
+                id: result.last_insert_rowid(),
+                inspection_id,
+                item: item.item.clone(),
+                passed: item.passed,
+                notes: item.notes.clone(),
+            }),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+
+        // כל פריט שנכשל נפתח כתקלה
+        if item.passed {
+            continue;
+        }
+        let description = match &item.notes {
+            Some(notes) => format!("{}: {}", item.item, notes),
+            None => item.item.clone(),
+        };
+        let severity = item.severity.as_deref().unwrap_or(DEFAULT_ITEM_SEVERITY);
+        match sqlx::query(&format!(
+            "INSERT INTO enclosure_defects (enclosure_id, inspection_id, description, severity, status, reported_date)
+            VALUES (?, ?, ?, ?, 'open', ?) RETURNING {}",
+            DEFECT_COLUMNS
+        ))
+        .bind(enclosure_id)
+        .bind(inspection_id)
+        .bind(&description)
+        .bind(severity)
+        .bind(inspection.inspection_date)
+        .fetch_one(&mut *tx)
+        .await
+        {
+            Ok(row) => defects.push(defect_from_row(&row)),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+
+    if let Err(e) = tx.commit().await {
+        return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+    }
+    // פריטים שנכשלו נפתחו כתקלות, וגם הן אירועים
+    for defect in &defects {
+        emit_event(&pool, "defect.created", defect).await;
+    }
+    HttpResponse::Created().json(InspectionDetails {
+        inspection: Inspection {
+            id: inspection_id,
+            enclosure_id,
+            inspection_date: inspection.inspection_date,
+            inspector_id: inspection.inspector_id,
+            notes: inspection.notes.clone(),
+        },
+        items,
+        defects,
+    })
+}
+
+#[get("/{id}/inspections")]
+pub async fn get_inspections(pool: web::Data<SqlitePool>, path: web::Path<i64>, req: HttpRequest) -> impl Responder {
+    let enclosure_id = path.into_inner();
+
+    let inspections: Vec<Inspection> = match sqlx::query("SELECT id, enclosure_id, inspection_date, inspector_id, notes
+        FROM enclosure_inspections WHERE enclosure_id = ? ORDER BY inspection_date DESC, id DESC")
+        .bind(enclosure_id)
+        .fetch_all(&**pool)
+        .await
+    {
+        Ok(rows) => rows.iter().map(|row| Inspection {
+            id: row.get("id"),
+            enclosure_id: row.get("enclosure_id"),
+            inspection_date: row.get("inspection_date"),
+            inspector_id: row.get("inspector_id"),
+            notes: row.get("notes"),
+        }).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let items: Vec<InspectionItem> = match sqlx::query("SELECT i.id, i.inspection_id, i.item, i.passed, i.notes
+        FROM inspection_items i
+        JOIN enclosure_inspections n ON n.id = i.inspection_id
+        WHERE n.enclosure_id = ? ORDER BY i.id")
+        .bind(enclosure_id)
+        .fetch_all(&**pool)
+        .await
+    {
+        Ok(rows) => rows.iter().map(item_from_row).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let defects: Vec<Defect> = match sqlx::query(&format!(
+        "SELECT {} FROM enclosure_defects WHERE enclosure_id = ? AND inspection_id IS NOT NULL ORDER BY id",
+        DEFECT_COLUMNS
+    ))
+    .bind(enclosure_id)
+    .fetch_all(&**pool)
+    .await
+    {
+        Ok(rows) => rows.iter().map(defect_from_row).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let details: Vec<InspectionDetails> = inspections.into_iter().map(|inspection| InspectionDetails {
+        items: items.iter().filter(|i| i.inspection_id == inspection.id).cloned().collect(),
+        defects: defects.iter().filter(|d| d.inspection_id == Some(inspection.id)).cloned().collect(),
+        inspection,
+    }).collect();
+    list_response(&details, &req, "inspections")
+}
+
+#[post("/{id}/defects")]
+pub async fn create_defect(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    defect: web::Json<CreateDefectRequest>,
+) -> impl Responder {
+    let enclosure_id = path.into_inner();
+    if defect.description.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Description is required");
+    }
+    if !SEVERITIES.contains(&defect.severity.as_str()) {
+        return HttpResponse::BadRequest().body("Invalid severity (use low, medium, high or critical)");
+    }
+
+    match sqlx::query("SELECT id FROM enclosures WHERE id = ?")
+        .bind(enclosure_id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Enclosure not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    match sqlx::query(&format!(
+        "INSERT INTO enclosure_defects (enclosure_id, description, severity, status, reported_date) VALUES (?, ?, ?, 'open', ?) RETURNING {}",
+        DEFECT_COLUMNS
+    ))
+    .bind(enclosure_id)
+    .bind(&defect.description)
+    .bind(&defect.severity)
+    .bind(defect.reported_date.unwrap_or_else(|| Local::now().date_naive()))
+    .fetch_one(&**pool)
+    .await
+    {
+        Ok(row) => {
+            let new_defect = defect_from_row(&row);
+            emit_event(&pool, "defect.created", &new_defect).await;
+            HttpResponse::Created().json(new_defect)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/{id}/defects")]
+pub async fn get_defects(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    query: web::Query<DefectListQuery>,
+    req: HttpRequest,
+) -> impl Responder {
+    let enclosure_id = path.into_inner();
+    let sql = format!(
+        "SELECT {} FROM enclosure_defects WHERE enclosure_id = ? AND (? IS NULL OR status = ?) ORDER BY reported_date, id",
+        DEFECT_COLUMNS
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![enclosure_id, query.status.clone(), query.status.clone()],
+        defect_from_row,
+        "defects",
+    )
+    .await
+}
+
+#[post("/{id}/defects/{defect_id}/status")]
+pub async fn update_defect_status(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<(i64, i64)>,
+    change: web::Json<DefectStatusRequest>,
+) -> impl Responder {
+    let (enclosure_id, defect_id) = path.into_inner();
+
+    let defect = match sqlx::query(&format!("SELECT {} FROM enclosure_defects WHERE id = ? AND enclosure_id = ?", DEFECT_COLUMNS))
+        .bind(defect_id)
+        .bind(enclosure_id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => defect_from_row(&row),
+        Ok(None) => return HttpResponse::NotFound().body("Defect not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // open -> in_repair -> resolved; תקלה פשוטה אפשר לסגור ישר מ-open
+    let allowed = matches!(
+        (defect.status.as_str(), change.status.as_str()),
+        ("open", "in_repair") | ("open", "resolved") | ("in_repair", "resolved")
+    );
+    if !allowed {
+        return HttpResponse::Conflict().body(format!("Cannot move a defect from {} to {}", defect.status, change.status));
+    }
+
+    let resolved_date = match change.status.as_str() {
+        "resolved" => match change.resolved_date {
+            Some(date) if date < defect.reported_date => {
+                return HttpResponse::BadRequest().body("Defect cannot be resolved before it was reported")
+            }
+            Some(date) => Some(date),
+            None => Some(Local::now().date_naive()),
+        },
+        _ => None,
+    };
+
+    match sqlx::query(&format!("UPDATE enclosure_defects SET status = ?, resolved_date = ? WHERE id = ? RETURNING {}", DEFECT_COLUMNS))
+        .bind(&change.status)
+        .bind(resolved_date)
+        .bind(defect_id)
+        .fetch_one(&**pool)
+        .await
+    {
+        Ok(row) => {
+            let updated_defect = defect_from_row(&row);
+            emit_event(&pool, "defect.updated", &updated_defect).await;
+            HttpResponse::Ok().json(updated_defect)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
@@ -0,0 +1,240 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqlitePool, Row};
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use crate::controllers::assignment_controller::load_active_keepers;
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{list_response, query_response, sql_args};
+use crate::models::assignment::KeeperQuery;
+use crate::models::measurement::{
+    CreateMeasurementRequest, CreateWeightLossThresholdRequest, Measurement, WeightLossAlert, WeightLossThreshold,
+    WeightTrend,
+};
+
+// סף ברירת מחדל לירידה במשקל כשאין סף מוגדר למין
+const DEFAULT_MAX_LOSS_PCT: f64 = 10.0;
+const DEFAULT_WINDOW_DAYS: i64 = 30;
+const MAX_WINDOW_DAYS: i64 = 3650;
+
+/// Percentage change between the latest weighing and the earliest one taken in the
+/// `days` before it. `weights` must be sorted by time; `None` if there is nothing to compare
+/// or the window reaches past the representable dates.
+fn weight_change_pct(weights: &[(NaiveDateTime, f64)], days: i64) -> Option<f64> {
+    let (latest_time, latest_weight) = *weights.last()?;
+    let since = latest_time.checked_sub_signed(TimeDelta::try_days(days)?)?;
+    let (_, reference_weight) = *weights
+        .iter()
+        .find(|(time, _)| *time >= since && *time < latest_time)?;
+    (reference_weight > 0.0).then(|| (latest_weight - reference_weight) / reference_weight * 100.0)
+}
+
+/// Animals whose weight dropped by more than their species threshold within its window.
+pub async fn find_weight_loss_alerts(pool: &SqlitePool) -> Result<Vec<WeightLossAlert>, sqlx::Error> {
+    let rows = sqlx::query("SELECT m.animal_id, a.name, a.species, a.enclosure_id, m.weight_kg, m.taken_at,
+            COALESCE(t.max_loss_pct, ?) AS max_loss_pct, COALESCE(t.window_days, ?) AS window_days
+        FROM measurements m
+        JOIN animals a ON a.id = m.animal_id
+        LEFT JOIN weight_loss_thresholds t ON t.species = a.species
+        WHERE m.weight_kg IS NOT NULL
+        ORDER BY m.animal_id, m.taken_at, m.id")
+        .bind(DEFAULT_MAX_LOSS_PCT)
+        .bind(DEFAULT_WINDOW_DAYS)
+        .fetch_all(pool)
+        .await?;
+    // כל התראה מנותבת לשומרים של החיה ושל המתחם שלה
+    let keepers = load_active_keepers(&mut *pool.acquire().await?, Local::now().date_naive()).await?;
+
+    // try_get ולא get: שורה פגומה צריכה להיכשל כשגיאה ולא להפיל את מנוע ההתראות
+    let mut alerts = Vec::new();
+    for animal_rows in rows.chunk_by(|a, b| a.try_get::<i64, _>("animal_id").ok() == b.try_get::<i64, _>("animal_id").ok()) {
+        let weights = animal_rows
+            .iter()
+            .map(|row| Ok((row.try_get("taken_at")?, row.try_get("weight_kg")?)))
+            .collect::<Result<Vec<(NaiveDateTime, f64)>, sqlx::Error>>()?;
+        let Some(last) = animal_rows.last() else {
+            continue;
+        };
+        let max_loss_pct: f64 = last.try_get("max_loss_pct")?;
+        let window_days: i64 = last.try_get("window_days")?;
+
+        if let Some(change_pct) = weight_change_pct(&weights, window_days)
+            && -change_pct >= max_loss_pct
+        {
+            let animal_id: i64 = last.try_get("animal_id")?;
+            alerts.push(WeightLossAlert {
+                animal_id,
+                name: last.try_get("name")?,
+                species: last.try_get("species")?,
+                latest_weight_kg: last.try_get("weight_kg")?,
+                change_pct,
+                max_loss_pct,
+                window_days,
+                keeper_ids: keepers.of_animal(animal_id, last.try_get("enclosure_id")?),
+            });
+        }
+    }
+    Ok(alerts)
+}
+
+#[post("/{id}/measurements")]
+pub async fn create_measurement(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    measurement: web::Json<CreateMeasurementRequest>,
+) -> impl Responder {
+    let animal_id = path.into_inner();
+
+    if measurement.weight_kg.is_none() && measurement.body_condition_score.is_none() && measurement.length_cm.is_none() {
+        return HttpResponse::BadRequest().body("At least one of weight_kg, body_condition_score or length_cm is required");
+    }
+    if measurement.weight_kg.is_some_and(|w| w <= 0.0) || measurement.length_cm.is_some_and(|l| l <= 0.0) {
+        return HttpResponse::BadRequest().body("Weight and length must be positive");
+    }
+    if measurement.body_condition_score.is_some_and(|s| !(1..=9).contains(&s)) {
+        return HttpResponse::BadRequest().body("Body condition score must be between 1 and 9");
+    }
+
+    match sqlx::query("INSERT INTO measurements (animal_id, weight_kg, body_condition_score, length_cm, taken_by, taken_at) VALUES (?, ?, ?, ?, ?, ?)")
+        .bind(animal_id)
+        .bind(measurement.weight_kg)
+        .bind(measurement.body_condition_score)
+        .bind(measurement.length_cm)
+        .bind(measurement.taken_by)
+        .bind(measurement.taken_at)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_measurement = Measurement {
+                id: result.last_insert_rowid(),
+                animal_id,
+                weight_kg: measurement.weight_kg,
+                body_condition_score: measurement.body_condition_score,
+                length_cm: measurement.length_cm,
+                taken_by: measurement.taken_by,
+                taken_at: measurement.taken_at,
+            };
+            emit_event(&pool, "measurement.created", &new_measurement).await;
+            HttpResponse::Created().json(new_measurement)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn measurement_from_row(row: &SqliteRow) -> Measurement {
+    Measurement {
+        id: row.get("id"),
+        animal_id: row.get("animal_id"),
+        weight_kg: row.get("weight_kg"),
+        body_condition_score: row.get("body_condition_score"),
+        length_cm: row.get("length_cm"),
+        taken_by: row.get("taken_by"),
+        taken_at: row.get("taken_at"),
+    }
+}
+
+#[get("/{id}/measurements")]
+pub async fn get_measurements(pool: web::Data<SqlitePool>, path: web::Path<i64>, req: HttpRequest) -> impl Responder {
+    let animal_id = path.into_inner();
+    let sql = "SELECT id, animal_id, weight_kg, body_condition_score, length_cm, taken_by, taken_at FROM measurements
+        WHERE animal_id = ? ORDER BY taken_at, id".to_string();
+    query_response(&pool, &req, sql, sql_args![animal_id], measurement_from_row, "measurements").await
+}
+
+#[get("/{id}/measurements/trends")]
+pub async fn get_measurement_trends(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let animal_id = path.into_inner();
+    match sqlx::query("SELECT id, animal_id, weight_kg, body_condition_score, length_cm, taken_by, taken_at FROM measurements
+        WHERE animal_id = ? ORDER BY taken_at, id")
+        .bind(animal_id)
+        .fetch_all(&**pool)
+        .await
+    {
+        Ok(rows) => {
+            let measurements: Vec<Measurement> = rows.iter().map(|row| Measurement {
+                id: row.get("id"),
+                animal_id: row.get("animal_id"),
+                weight_kg: row.get("weight_kg"),
+                body_condition_score: row.get("body_condition_score"),
+                length_cm: row.get("length_cm"),
+                taken_by: row.get("taken_by"),
+                taken_at: row.get("taken_at"),
+            }).collect();
+
+            let weights: Vec<(NaiveDateTime, f64)> = measurements
+                .iter()
+                .filter_map(|m| m.weight_kg.map(|w| (m.taken_at, w)))
+                .collect();
+
+            let trend = WeightTrend {
+                animal_id,
+                change_30d_pct: weight_change_pct(&weights, 30),
+                change_90d_pct: weight_change_pct(&weights, 90),
+                latest: measurements.last().cloned(),
+            };
+            HttpResponse::Ok().json(trend)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/thresholds")]
+pub async fn set_weight_loss_threshold(
+    pool: web::Data<SqlitePool>,
+    threshold: web::Json<CreateWeightLossThresholdRequest>,
+) -> impl Responder {
+    if threshold.max_loss_pct <= 0.0 {
+        return HttpResponse::BadRequest().body("Threshold must be positive");
+    }
+    if !(1..=MAX_WINDOW_DAYS).contains(&threshold.window_days) {
+        return HttpResponse::BadRequest().body(format!("Window must be between 1 and {} days", MAX_WINDOW_DAYS));
+    }
+
+    match sqlx::query("INSERT INTO weight_loss_thresholds (species, max_loss_pct, window_days) VALUES (?, ?, ?)
+        ON CONFLICT (species) DO UPDATE SET max_loss_pct = excluded.max_loss_pct, window_days = excluded.window_days
+        RETURNING id")
+        .bind(&threshold.species)
+        .bind(threshold.max_loss_pct)
+        .bind(threshold.window_days)
+        .fetch_one(&**pool)
+        .await
+    {
+        Ok(row) => {
+            let new_threshold = WeightLossThreshold {
+                id: row.get("id"),
+                species: threshold.species.clone(),
+                max_loss_pct: threshold.max_loss_pct,
+                window_days: threshold.window_days,
+            };
+            HttpResponse::Ok().json(new_threshold)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn weight_loss_threshold_from_row(row: &SqliteRow) -> WeightLossThreshold {
+    WeightLossThreshold {
+        id: row.get("id"),
+        species: row.get("species"),
+        max_loss_pct: row.get("max_loss_pct"),
+        window_days: row.get("window_days"),
+    }
+}
+
+#[get("/thresholds")]
+pub async fn get_weight_loss_thresholds(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, species, max_loss_pct, window_days FROM weight_loss_thresholds ORDER BY species".to_string();
+    query_response(&pool, &req, sql, sql_args![], weight_loss_threshold_from_row, "weight_loss_thresholds").await
+}
+
+#[get("/alerts")]
+pub async fn get_weight_loss_alerts(pool: web::Data<SqlitePool>, query: web::Query<KeeperQuery>, req: HttpRequest) -> impl Responder {
+    match find_weight_loss_alerts(&pool).await {
+        Ok(mut alerts) => {
+            if let Some(keeper_id) = query.keeper_id {
+                alerts.retain(|alert| alert.keeper_ids.contains(&keeper_id));
+            }
+            list_response(&alerts, &req, "weight_loss_alerts")
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
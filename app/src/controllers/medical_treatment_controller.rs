@@ -1,57 +1,278 @@
-use actix_web::{get, post, put, delete, web, HttpResponse, Responder};
-use sqlx::{SqlitePool, Row};
-use crate::models::medical_treatment::{CreateMedicalTreatmentRequest, MedicalTreatment, UpdateMedicalTreatmentRequest};
+use std::collections::HashMap;
+use actix_web::{get, post, put, delete, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use crate::controllers::certification_controller::{missing_certifications, missing_certifications_message};
+use crate::controllers::animal_controller::{animal_from_row, ANIMAL_COLUMNS};
+use crate::controllers::shift_controller::{check_on_shift, warning_header, ShiftCheck};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::db::references_to;
+use crate::models::medical_treatment::{
+    CreateDiagnosisRequest, CreateMedicalTreatmentRequest, CreateMedicationRequest, Diagnosis, MedicalRecord,
+    MedicalTreatment, MedicalTreatmentDetails, Medication, UpdateDiagnosisRequest, UpdateMedicalTreatmentRequest,
+    UpdateMedicationRequest,
+};
+use crate::models::event::DeletedEntity;
+
+// טוען אבחנות ותרופות לטיפולים שנבחרו לפי `filter` (על טבלת medical_treatments m)
+async fn attach_details(
+    pool: &SqlitePool,
+    treatments: Vec<MedicalTreatment>,
+    filter: &str,
+    id: i64,
+) -> Result<Vec<MedicalTreatmentDetails>, sqlx::Error> {
+    let diagnosis_rows = sqlx::query(&format!(
+        "SELECT d.id, d.treatment_id, d.code, d.description FROM treatment_diagnoses d
+        JOIN medical_treatments m ON m.id = d.treatment_id WHERE {} ORDER BY d.id",
+        filter
+    ))
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    let medication_rows = sqlx::query(&format!(
+        "SELECT t.id, t.treatment_id, t.name, t.dose, t.unit, t.route, t.frequency, t.duration_days FROM treatment_medications t
+        JOIN medical_treatments m ON m.id = t.treatment_id WHERE {} ORDER BY t.id",
+        filter
+    ))
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut diagnoses: HashMap<i64, Vec<Diagnosis>> = HashMap::new();
+    for row in diagnosis_rows {
+        let diagnosis = Diagnosis {
+            id: row.get("id"),
+            treatment_id: row.get("treatment_id"),
+            code: row.get("code"),
+            description: row.get("description"),
+        };
+        diagnoses.entry(diagnosis.treatment_id).or_default().push(diagnosis);
+    }
+
+    let mut medications: HashMap<i64, Vec<Medication>> = HashMap::new();
+    for row in medication_rows {
+        let medication = Medication {
+            id: row.get("id"),
+            treatment_id: row.get("treatment_id"),
+            name: row.get("name"),
+            dose: row.get("dose"),
+            unit: row.get("unit"),
+            route: row.get("route"),
+            frequency: row.get("frequency"),
+            duration_days: row.get("duration_days"),
+        };
+        medications.entry(medication.treatment_id).or_default().push(medication);
+    }
+
+    Ok(treatments.into_iter().map(|treatment| MedicalTreatmentDetails {
+        diagnoses: diagnoses.remove(&treatment.id).unwrap_or_default(),
+        medications: medications.remove(&treatment.id).unwrap_or_default(),
+        treatment,
+    }).collect())
+}
+
+async fn insert_diagnosis(
+    conn: &mut SqliteConnection,
+    treatment_id: i64,
+    diagnosis: &CreateDiagnosisRequest,
+) -> Result<Diagnosis, sqlx::Error> {
+    let result = sqlx::query("INSERT INTO treatment_diagnoses (treatment_id, code, description) VALUES (?, ?, ?)")
+        .bind(treatment_id)
+        .bind(&diagnosis.code)
+        .bind(&diagnosis.description)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(Diagnosis {
+        id: result.last_insert_rowid(),
+        treatment_id,
+        code: diagnosis.code.clone(),
+        description: diagnosis.description.clone(),
+    })
+}
+
+async fn insert_medication(
+    conn: &mut SqliteConnection,
+    treatment_id: i64,
+    medication: &CreateMedicationRequest,
+) -> Result<Medication, sqlx::Error> {
+    let result = sqlx::query("INSERT INTO treatment_medications (treatment_id, name, dose, unit, route, frequency, duration_days) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(treatment_id)
+        .bind(&medication.name)
+        .bind(medication.dose)
+        .bind(&medication.unit)
+        .bind(&medication.route)
+        .bind(&medication.frequency)
+        .bind(medication.duration_days)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(Medication {
+        id: result.last_insert_rowid(),
+        treatment_id,
+        name: medication.name.clone(),
+        dose: medication.dose,
+        unit: medication.unit.clone(),
+        route: medication.route.clone(),
+        frequency: medication.frequency.clone(),
+        duration_days: medication.duration_days,
+    })
+}
+
+fn invalid_medication(dose: f64, duration_days: i32) -> bool {
+    dose <= 0.0 || duration_days <= 0
+}
+
+fn treatment_from_row(row: &SqliteRow) -> MedicalTreatment {
+    MedicalTreatment {
+        id: row.get("id"),
+        animal_id: row.get("animal_id"),
+        staff_id: row.get("staff_id"),
+        date: row.get("date"),
+        description: row.get("description"),
+        procedure_type: row.get("procedure_type"),
+        follow_up_date: row.get("follow_up_date"),
+        outcome: row.get("outcome"),
+    }
+}
+
+pub enum CreateTreatmentError {
+    Invalid(&'static str),
+    NotFound(&'static str),
+    Forbidden(String),
+    Conflict(String),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for CreateTreatmentError {
+    fn from(e: sqlx::Error) -> Self {
+        CreateTreatmentError::Database(e)
+    }
+}
+
+impl CreateTreatmentError {
+    pub fn response(self) -> HttpResponse {
+        match self {
+            CreateTreatmentError::Invalid(message) => HttpResponse::BadRequest().body(message),
+            CreateTreatmentError::NotFound(message) => HttpResponse::NotFound().body(message),
+            CreateTreatmentError::Forbidden(message) => HttpResponse::Forbidden().body(message),
+            CreateTreatmentError::Conflict(message) => HttpResponse::Conflict().body(message),
+            CreateTreatmentError::Database(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+}
+
+/// Validates and stores a new treatment with its diagnoses and medications, with the same rules as
+/// `POST /medical_treatments/`. Also returns the shift warning the response should carry, if any.
+pub async fn insert_treatment(
+    conn: &mut SqliteConnection,
+    treatment: &CreateMedicalTreatmentRequest,
+) -> Result<(MedicalTreatmentDetails, Option<String>), CreateTreatmentError> {
+    if treatment.medications.iter().any(|medication| invalid_medication(medication.dose, medication.duration_days)) {
+        return Err(CreateTreatmentError::Invalid("Medication dose and duration must be positive"));
+    }
+
+    let row = sqlx::query("SELECT
+            EXISTS (SELECT 1 FROM animals WHERE id = ?) AS animal_found,
+            EXISTS (SELECT 1 FROM staff WHERE id = ?) AS staff_found")
+        .bind(treatment.animal_id)
+        .bind(treatment.staff_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !row.get::<bool, _>("animal_found") {
+        return Err(CreateTreatmentError::NotFound("Animal not found"));
+    }
+    if !row.get::<bool, _>("staff_found") {
+        return Err(CreateTreatmentError::NotFound("Staff not found"));
+    }
+
+    // האם העובד היה במשמרת - לפי הגדרת shift_check (off / warn / reject)
+    let shift_warning = match check_on_shift(&mut *conn, treatment.staff_id, treatment.date).await? {
+        ShiftCheck::Ok => None,
+        ShiftCheck::Warn(message) => Some(message),
+        ShiftCheck::Reject(message) => return Err(CreateTreatmentError::Conflict(message)),
+    };
+
+    // הסמכות שנדרשות לעבודה עם המין / המתחם של החיה
+    let missing = missing_certifications(&mut *conn, treatment.staff_id, treatment.animal_id, "medical_treatment", treatment.date.date()).await?;
+    if !missing.is_empty() {
+        return Err(CreateTreatmentError::Forbidden(missing_certifications_message(&missing)));
+    }
+
+    let id = sqlx::query(
+        "INSERT INTO medical_treatments (animal_id, staff_id, date, description, procedure_type, follow_up_date, outcome) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(treatment.animal_id)
+    .bind(treatment.staff_id)
+    .bind(treatment.date)
+    .bind(&treatment.description)
+    .bind(&treatment.procedure_type)
+    .bind(treatment.follow_up_date)
+    .bind(&treatment.outcome)
+    .execute(&mut *conn)
+    .await?
+    .last_insert_rowid();
+
+    let mut diagnoses = Vec::new();
+    for diagnosis in &treatment.diagnoses {
+        diagnoses.push(insert_diagnosis(&mut *conn, id, diagnosis).await?);
+    }
+
+    let mut medications = Vec::new();
+    for medication in &treatment.medications {
+        medications.push(insert_medication(&mut *conn, id, medication).await?);
+    }
+
+    let details = MedicalTreatmentDetails {
+        treatment: MedicalTreatment {
+            id,
+            animal_id: treatment.animal_id,
+            staff_id: treatment.staff_id,
+            date: treatment.date,
+            description: treatment.description.clone(),
+            procedure_type: treatment.procedure_type.clone(),
+            follow_up_date: treatment.follow_up_date,
+            outcome: treatment.outcome.clone(),
+        },
+        diagnoses,
+        medications,
+    };
+    Ok((details, shift_warning))
+}
 
 #[post("/")]
 pub async fn create_medical_treatment(
     pool: web::Data<SqlitePool>,
     treatment: web::Json<CreateMedicalTreatmentRequest>,
 ) -> impl Responder {
-    match sqlx::query(
-        "INSERT INTO medical_treatments (animal_id, staff_id, date, description) VALUES (?, ?, ?, ?)",
-    )
-    .bind(&treatment.animal_id)
-    .bind(&treatment.staff_id)
-    .bind(&treatment.date)
-    .bind(&treatment.description)
-    .execute(&**pool)
-    .await
-    {
-        Ok(result) => {
-            let new_treatment = MedicalTreatment {
-                id: result.last_insert_rowid(),
-                animal_id: treatment.animal_id,
-                staff_id: treatment.staff_id,
-                date: treatment.date,
-                description: treatment.description.clone(),
-            };
-            HttpResponse::Created().json(new_treatment)
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let (new_treatment, shift_warning) = match insert_treatment(&mut tx, &treatment).await {
+        Ok(created) => created,
+        Err(e) => return e.response(),
+    };
+
+    match tx.commit().await {
+        Ok(_) => {
+            let mut response = HttpResponse::Created();
+            if let Some(message) = shift_warning {
+                response.insert_header(warning_header(&message));
+            }
+            emit_event(&pool, "medical_treatment.created", &new_treatment).await;
+            response.json(new_treatment)
         }
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
 
 #[get("/")]
-pub async fn get_medical_treatments(pool: web::Data<SqlitePool>) -> impl Responder {
-    match sqlx::query("SELECT id, animal_id, staff_id, date, description FROM medical_treatments ORDER BY id")
-        .fetch_all(&**pool)
-        .await
-    {
-        Ok(rows) => {
-            let treatments: Vec<MedicalTreatment> = rows
-                .iter()
-                .map(|row| MedicalTreatment {
-                    id: row.get("id"),
-                    animal_id: row.get("animal_id"),
-                    staff_id: row.get("staff_id"),
-                    date: row.get("date"),
-                    description: row.get("description"),
-                })
-                .collect();
-            HttpResponse::Ok().json(treatments)
-        }
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    }
+pub async fn get_medical_treatments(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, animal_id, staff_id, date, description, procedure_type, follow_up_date, outcome FROM medical_treatments ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], treatment_from_row, "medical_treatments").await
 }
 
 #[get("/{id}")]
@@ -60,20 +281,16 @@ pub async fn get_medical_treatment_by_id(
     path: web::Path<i64>,
 ) -> impl Responder {
     let id = path.into_inner();
-    match sqlx::query("SELECT id, animal_id, staff_id, date, description FROM medical_treatments WHERE id = ?")
-        .bind(&id)
+    match sqlx::query("SELECT id, animal_id, staff_id, date, description, procedure_type, follow_up_date, outcome FROM medical_treatments WHERE id = ?")
+        .bind(id)
         .fetch_optional(&**pool)
         .await
     {
         Ok(Some(row)) => {
-            let treatment = MedicalTreatment {
-                id: row.get("id"),
-                animal_id: row.get("animal_id"),
-                staff_id: row.get("staff_id"),
-                date: row.get("date"),
-                description: row.get("description"),
-            };
-            HttpResponse::Ok().json(treatment)
+            match attach_details(&pool, vec![treatment_from_row(&row)], "m.id = ?", id).await {
+                Ok(mut details) => HttpResponse::Ok().json(details.remove(0)),
+                Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+            }
         }
         Ok(None) => HttpResponse::NotFound().body("Medical treatment not found"),
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
@@ -87,25 +304,56 @@ pub async fn update_medical_treatment(
     updated: web::Json<UpdateMedicalTreatmentRequest>,
 ) -> impl Responder {
     let id = path.into_inner();
-    match sqlx::query("UPDATE medical_treatments SET animal_id = ?, staff_id = ?, date = ?, description = ? WHERE id = ?")
-        .bind(&updated.animal_id)
-        .bind(&updated.staff_id)
-        .bind(&updated.date)
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // העובד החדש נבדק כמו ביצירה - לפי הגדרת shift_check (off / warn / reject)
+    let shift_warning = match check_on_shift(&mut conn, updated.staff_id, updated.date).await {
+        Ok(ShiftCheck::Ok) => None,
+        Ok(ShiftCheck::Warn(message)) => Some(message),
+        Ok(ShiftCheck::Reject(message)) => return HttpResponse::Conflict().body(message),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // הסמכות שנדרשות לעבודה עם המין / המתחם של החיה - לפי העובד והחיה החדשים
+    match missing_certifications(&mut conn, updated.staff_id, updated.animal_id, "medical_treatment", updated.date.date()).await {
+        Ok(missing) if missing.is_empty() => {}
+        Ok(missing) => return HttpResponse::Forbidden().body(missing_certifications_message(&missing)),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    match sqlx::query("UPDATE medical_treatments SET animal_id = ?, staff_id = ?, date = ?, description = ?, procedure_type = ?, follow_up_date = ?, outcome = ? WHERE id = ?")
+        .bind(updated.animal_id)
+        .bind(updated.staff_id)
+        .bind(updated.date)
         .bind(&updated.description)
-        .bind(&id)
-        .execute(&**pool)
+        .bind(&updated.procedure_type)
+        .bind(updated.follow_up_date)
+        .bind(&updated.outcome)
+        .bind(id)
+        .execute(&mut *conn)
         .await
     {
         Ok(result) => {
             if result.rows_affected() > 0 {
                 let updated_treatment = MedicalTreatment {
-                    id: id,
+                    id,
                     animal_id: updated.animal_id,
                     staff_id: updated.staff_id,
                     date: updated.date,
                     description: updated.description.clone(),
+                    procedure_type: updated.procedure_type.clone(),
+                    follow_up_date: updated.follow_up_date,
+                    outcome: updated.outcome.clone(),
                 };
-                HttpResponse::Ok().json(updated_treatment)
+                let mut response = HttpResponse::Ok();
+                if let Some(message) = shift_warning {
+                    response.insert_header(warning_header(&message));
+                }
+                emit_event(&pool, "medical_treatment.updated", &updated_treatment).await;
+                response.json(updated_treatment)
             } else {
                 HttpResponse::NotFound().body("Medical treatment not found")
             }
@@ -120,18 +368,218 @@ pub async fn delete_medical_treatment(
     path: web::Path<i64>,
 ) -> impl Responder {
     let id = path.into_inner();
-    match sqlx::query("DELETE FROM medical_treatments WHERE id = ?")
-        .bind(&id)
-        .execute(&**pool)
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // אבחנות ותרופות הן חלק מהטיפול ונמחקות איתו; חיסון, הסגר או נתיחה שנשענים עליו הם היסטוריה של החיה
+    for child_table in ["treatment_diagnoses", "treatment_medications"] {
+        if let Err(e) = sqlx::query(&format!("DELETE FROM {} WHERE treatment_id = ?", child_table))
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        {
+            return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+        }
+    }
+    match references_to(&mut tx, "medical_treatments", id).await {
+        Ok(references) if references.is_empty() => {}
+        Ok(references) => {
+            return HttpResponse::Conflict().body(format!("Medical treatment is still referenced by {}", references.join(", ")))
+        }
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    let animal_id: i64 = match sqlx::query("DELETE FROM medical_treatments WHERE id = ? RETURNING animal_id")
+        .bind(id)
+        .fetch_optional(&mut *tx)
         .await
     {
-        Ok(result) => {
-            if result.rows_affected() > 0 {
-                HttpResponse::Ok().body("Deleted successfully")
-            } else {
-                HttpResponse::NotFound().body("Medical treatment not found")
-            }
+        Ok(Some(row)) => row.get("animal_id"),
+        Ok(None) => return HttpResponse::NotFound().body("Medical treatment not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match tx.commit().await {
+        Ok(_) => {
+            emit_event(&pool, "medical_treatment.deleted", &DeletedEntity { id, animal_id: Some(animal_id), enclosure_id: None }).await;
+            HttpResponse::Ok().body("Deleted successfully")
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{id}/diagnoses")]
+pub async fn add_diagnosis(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    diagnosis: web::Json<CreateDiagnosisRequest>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match sqlx::query("SELECT id FROM medical_treatments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Medical treatment not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    match insert_diagnosis(&mut conn, id, &diagnosis).await {
+        Ok(new_diagnosis) => {
+            emit_event(&pool, "diagnosis.created", &new_diagnosis).await;
+            HttpResponse::Created().json(new_diagnosis)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{id}/medications")]
+pub async fn add_medication(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    medication: web::Json<CreateMedicationRequest>,
+) -> impl Responder {
+    let id = path.into_inner();
+    if invalid_medication(medication.dose, medication.duration_days) {
+        return HttpResponse::BadRequest().body("Medication dose and duration must be positive");
+    }
+
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match sqlx::query("SELECT id FROM medical_treatments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Medical treatment not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    match insert_medication(&mut conn, id, &medication).await {
+        Ok(new_medication) => {
+            emit_event(&pool, "medication.created", &new_medication).await;
+            HttpResponse::Created().json(new_medication)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[put("/diagnoses/{id}")]
+pub async fn update_diagnosis(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    updated: web::Json<UpdateDiagnosisRequest>,
+) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query("UPDATE treatment_diagnoses SET code = ?, description = ? WHERE id = ? RETURNING treatment_id")
+        .bind(&updated.code)
+        .bind(&updated.description)
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => {
+            let diagnosis = Diagnosis {
+                id,
+                treatment_id: row.get("treatment_id"),
+                code: updated.code.clone(),
+                description: updated.description.clone(),
+            };
+            emit_event(&pool, "diagnosis.updated", &diagnosis).await;
+            HttpResponse::Ok().json(diagnosis)
+        }
+        Ok(None) => HttpResponse::NotFound().body("Diagnosis not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[delete("/diagnoses/{id}")]
+pub async fn delete_diagnosis(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query("DELETE FROM treatment_diagnoses WHERE id = ?
+        RETURNING (SELECT animal_id FROM medical_treatments WHERE id = treatment_id) AS animal_id")
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => {
+            emit_event(&pool, "diagnosis.deleted", &DeletedEntity { id, animal_id: row.get("animal_id"), enclosure_id: None }).await;
+            HttpResponse::Ok().body("Deleted successfully")
+        }
+        Ok(None) => HttpResponse::NotFound().body("Diagnosis not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[put("/medications/{id}")]
+pub async fn update_medication(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    updated: web::Json<UpdateMedicationRequest>,
+) -> impl Responder {
+    let id = path.into_inner();
+    if invalid_medication(updated.dose, updated.duration_days) {
+        return HttpResponse::BadRequest().body("Medication dose and duration must be positive");
+    }
+
+    match sqlx::query("UPDATE treatment_medications SET name = ?, dose = ?, unit = ?, route = ?, frequency = ?, duration_days = ?
+        WHERE id = ? RETURNING treatment_id")
+        .bind(&updated.name)
+        .bind(updated.dose)
+        .bind(&updated.unit)
+        .bind(&updated.route)
+        .bind(&updated.frequency)
+        .bind(updated.duration_days)
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => {
+            let medication = Medication {
+                id,
+                treatment_id: row.get("treatment_id"),
+                name: updated.name.clone(),
+                dose: updated.dose,
+                unit: updated.unit.clone(),
+                route: updated.route.clone(),
+                frequency: updated.frequency.clone(),
+                duration_days: updated.duration_days,
+            };
+            emit_event(&pool, "medication.updated", &medication).await;
+            HttpResponse::Ok().json(medication)
         }
+        Ok(None) => HttpResponse::NotFound().body("Medication not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[delete("/medications/{id}")]
+pub async fn delete_medication(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query("DELETE FROM treatment_medications WHERE id = ?
+        RETURNING (SELECT animal_id FROM medical_treatments WHERE id = treatment_id) AS animal_id")
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => {
+            emit_event(&pool, "medication.deleted", &DeletedEntity { id, animal_id: row.get("animal_id"), enclosure_id: None }).await;
+            HttpResponse::Ok().body("Deleted successfully")
+        }
+        Ok(None) => HttpResponse::NotFound().body("Medication not found"),
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
 }
@@ -139,31 +587,46 @@ pub async fn delete_medical_treatment(
 #[get("/by_staff/{first_name}/{last_name}")]
 pub async fn get_medical_treatment_by_staff(
     pool: web::Data<SqlitePool>,
-    path: web::Path<(String, String)>
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
 ) -> impl Responder {
     let (first_name, last_name) = path.into_inner();
 
-    match sqlx::query("SELECT m.* FROM medical_treatments m 
-        JOIN staff s ON m.staff_id = s.id 
-        WHERE s.first_name = ? AND s.last_name = ?")
-        .bind(first_name)
-        .bind(last_name)
+    let sql = "SELECT m.* FROM medical_treatments m
+        JOIN staff s ON m.staff_id = s.id
+        WHERE s.first_name = ? AND s.last_name = ?".to_string();
+    query_response(&pool, &req, sql, sql_args![first_name, last_name], treatment_from_row, "medical_treatments").await
+}
+
+#[get("/{id}/medical_record")]
+pub async fn get_animal_medical_record(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+) -> impl Responder {
+    let animal_id = path.into_inner();
+
+    let animal = match sqlx::query(&format!("SELECT {} FROM animals WHERE id = ?", ANIMAL_COLUMNS))
+        .bind(animal_id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => animal_from_row(&row),
+        Ok(None) => return HttpResponse::NotFound().body("Animal not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let treatments: Vec<MedicalTreatment> = match sqlx::query("SELECT id, animal_id, staff_id, date, description, procedure_type, follow_up_date, outcome
+        FROM medical_treatments WHERE animal_id = ? ORDER BY date, id")
+        .bind(animal_id)
         .fetch_all(&**pool)
         .await
     {
-        Ok(rows) => {
-            let medical_treatments: Vec<MedicalTreatment> = rows.iter().map(|row| MedicalTreatment {
-                id: row.get("id"),
-                animal_id: row.get("animal_id"),
-                staff_id: row.get("staff_id"),
-                date: row.get("date"),
-                description: row.get("description")
-            }).collect();
-
-            HttpResponse::Ok().json(medical_treatments)
-        }
-        Err(e) => HttpResponse::InternalServerError()
-            .body(format!("Database error: {}", e))
+        Ok(rows) => rows.iter().map(treatment_from_row).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match attach_details(&pool, treatments, "m.animal_id = ?", animal_id).await {
+        Ok(treatments) => HttpResponse::Ok().json(MedicalRecord { animal, treatments }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
-    
 }
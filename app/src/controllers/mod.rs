@@ -2,4 +2,24 @@ pub mod animal_controller;
 pub mod enclosure_controller;
 pub mod feeding_controller;
 pub mod staff_controller;
-pub mod medical_treatment_controller;
\ No newline at end of file
+pub mod medical_treatment_controller;
+pub mod inventory_controller;
+pub mod vaccination_controller;
+pub mod measurement_controller;
+pub mod quarantine_controller;
+pub mod breeding_controller;
+pub mod studbook_controller;
+pub mod shift_controller;
+pub mod settings_controller;
+pub mod assignment_controller;
+pub mod certification_controller;
+pub mod report_controller;
+pub mod task_controller;
+pub mod maintenance_controller;
+pub mod sensor_controller;
+pub mod alert_controller;
+pub mod webhook_controller;
+pub mod event_controller;
+pub mod import_controller;
+pub mod export_controller;
+pub mod backup_controller;
@@ -0,0 +1,243 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use crate::controllers::event_controller::{emit_event, emit_move_event};
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::controllers::maintenance_controller::is_out_of_service;
+use crate::models::quarantine::{ActiveQuarantine, ClearQuarantineRequest, Quarantine, StartQuarantineRequest};
+
+// רק וטרינר יכול לשחרר חיה מהסגר
+const VET_ROLE: &str = "Veterinarian";
+
+pub async fn is_in_quarantine(conn: &mut SqliteConnection, animal_id: i64) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT id FROM quarantines WHERE animal_id = ? AND end_date IS NULL")
+        .bind(animal_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(row.is_some())
+}
+
+pub async fn is_quarantine_enclosure(conn: &mut SqliteConnection, enclosure_id: i64) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT is_quarantine FROM enclosures WHERE id = ?")
+        .bind(enclosure_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(row.is_some_and(|row| row.get::<bool, _>("is_quarantine")))
+}
+
+// הסגר ארוך משנה הוא כמעט תמיד טעות הקלדה
+const MAX_REQUIRED_DAYS: i64 = 365;
+
+/// The first moment a quarantine may be cleared; `None` if the stored period runs past the representable dates.
+fn earliest_release(quarantine: &Quarantine) -> Option<NaiveDateTime> {
+    TimeDelta::try_days(quarantine.required_days).and_then(|delta| quarantine.start_date.checked_add_signed(delta))
+}
+
+const QUARANTINE_COLUMNS: &str = "id, animal_id, treatment_id, reason, start_date, required_days, end_date, cleared_by";
+
+fn quarantine_from_row(row: &SqliteRow) -> Quarantine {
+    Quarantine {
+        id: row.get("id"),
+        animal_id: row.get("animal_id"),
+        treatment_id: row.get("treatment_id"),
+        reason: row.get("reason"),
+        start_date: row.get("start_date"),
+        required_days: row.get("required_days"),
+        end_date: row.get("end_date"),
+        cleared_by: row.get("cleared_by"),
+    }
+}
+
+#[post("/")]
+pub async fn start_quarantine(pool: web::Data<SqlitePool>, quarantine: web::Json<StartQuarantineRequest>) -> impl Responder {
+    if !(1..=MAX_REQUIRED_DAYS).contains(&quarantine.required_days) {
+        return HttpResponse::BadRequest().body(format!("Required days must be between 1 and {}", MAX_REQUIRED_DAYS));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // חיה שמתה או עזבה את הגן לא נכנסת להסגר
+    let previous_enclosure: i64 = match sqlx::query("SELECT status, enclosure_id FROM animals WHERE id = ?")
+        .bind(quarantine.animal_id)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some(row)) => match row.get::<String, _>("status").as_str() {
+            status @ ("deceased" | "departed") => return HttpResponse::Conflict().body(format!("Animal is {}", status)),
+            _ => row.get("enclosure_id"),
+        },
+        Ok(None) => return HttpResponse::NotFound().body("Animal not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // הטיפול שבעקבותיו נכנסה להסגר חייב להיות של אותה חיה
+    if let Some(treatment_id) = quarantine.treatment_id {
+        match sqlx::query("SELECT animal_id FROM medical_treatments WHERE id = ?")
+            .bind(treatment_id)
+            .fetch_optional(&mut *tx)
+            .await
+        {
+            Ok(Some(row)) if row.get::<i64, _>("animal_id") == quarantine.animal_id => {}
+            Ok(Some(_)) => return HttpResponse::BadRequest().body("Medical treatment belongs to another animal"),
+            Ok(None) => return HttpResponse::NotFound().body("Medical treatment not found"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+
+    match is_in_quarantine(&mut tx, quarantine.animal_id).await {
+        Ok(false) => {}
+        Ok(true) => return HttpResponse::Conflict().body("Animal is already in quarantine"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    // העברה למתחם הסגר, אם צוין
+    if let Some(enclosure_id) = quarantine.enclosure_id {
+        match is_quarantine_enclosure(&mut tx, enclosure_id).await {
+            Ok(true) => {}
+            Ok(false) => return HttpResponse::BadRequest().body("Enclosure is not a quarantine enclosure"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+        match is_out_of_service(&mut tx, enclosure_id).await {
+            Ok(false) => {}
+            Ok(true) => return HttpResponse::Conflict().body("Enclosure is out of service until its defects are resolved"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+
+        if let Err(e) = sqlx::query("UPDATE animals SET enclosure_id = ? WHERE id = ?")
+            .bind(enclosure_id)
+            .bind(quarantine.animal_id)
+            .execute(&mut *tx)
+            .await
+        {
+            return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+        }
+    }
+
+    let id = match sqlx::query("INSERT INTO quarantines (animal_id, treatment_id, reason, start_date, required_days) VALUES (?, ?, ?, ?, ?)")
+        .bind(quarantine.animal_id)
+        .bind(quarantine.treatment_id)
+        .bind(&quarantine.reason)
+        .bind(quarantine.start_date)
+        .bind(quarantine.required_days)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(result) => result.last_insert_rowid(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match tx.commit().await {
+        Ok(_) => {
+            let new_quarantine = Quarantine {
+                id,
+                animal_id: quarantine.animal_id,
+                treatment_id: quarantine.treatment_id,
+                reason: quarantine.reason.clone(),
+                start_date: quarantine.start_date,
+                required_days: quarantine.required_days,
+                end_date: None,
+                cleared_by: None,
+            };
+            match quarantine.enclosure_id {
+                Some(enclosure_id) if enclosure_id != previous_enclosure => {
+                    emit_move_event(&pool, "quarantine.created", &new_quarantine, previous_enclosure).await
+                }
+                _ => emit_event(&pool, "quarantine.created", &new_quarantine).await,
+            }
+            HttpResponse::Created().json(new_quarantine)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{id}/clear")]
+pub async fn clear_quarantine(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    clearance: web::Json<ClearQuarantineRequest>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    let quarantine = match sqlx::query(&format!("SELECT {} FROM quarantines WHERE id = ?", QUARANTINE_COLUMNS))
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => quarantine_from_row(&row),
+        Ok(None) => return HttpResponse::NotFound().body("Quarantine not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    if quarantine.end_date.is_some() {
+        return HttpResponse::Conflict().body("Quarantine already cleared");
+    }
+
+    match sqlx::query("SELECT role FROM staff WHERE id = ?")
+        .bind(clearance.staff_id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) if row.get::<String, _>("role").eq_ignore_ascii_case(VET_ROLE) => {}
+        Ok(Some(_)) => return HttpResponse::Forbidden().body("Only a veterinarian can clear a quarantine"),
+        Ok(None) => return HttpResponse::NotFound().body("Staff not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    let end_date = clearance.end_date.unwrap_or_else(|| Local::now().naive_local());
+    let Some(earliest_release) = earliest_release(&quarantine) else {
+        return HttpResponse::Conflict().body("Required quarantine period is out of range");
+    };
+    if end_date < earliest_release {
+        return HttpResponse::Conflict().body(format!("Required quarantine period ends at {}", earliest_release));
+    }
+
+    match sqlx::query(&format!(
+        "UPDATE quarantines SET end_date = ?, cleared_by = ? WHERE id = ? AND end_date IS NULL RETURNING {}",
+        QUARANTINE_COLUMNS
+    ))
+    .bind(end_date)
+    .bind(clearance.staff_id)
+    .bind(id)
+    .fetch_optional(&**pool)
+    .await
+    {
+        Ok(Some(row)) => {
+            let cleared = quarantine_from_row(&row);
+            emit_event(&pool, "quarantine.updated", &cleared).await;
+            HttpResponse::Ok().json(cleared)
+        }
+        // ההסגר שוחרר בינתיים בבקשה אחרת
+        Ok(None) => HttpResponse::Conflict().body("Quarantine already cleared"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/")]
+pub async fn get_quarantines(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = format!("SELECT {} FROM quarantines ORDER BY start_date, id", QUARANTINE_COLUMNS);
+    query_response(&pool, &req, sql, sql_args![], quarantine_from_row, "quarantines").await
+}
+
+fn active_quarantine_from_row(row: &SqliteRow) -> ActiveQuarantine {
+    let quarantine = quarantine_from_row(row);
+    ActiveQuarantine {
+        earliest_release: earliest_release(&quarantine),
+        animal_name: row.get("animal_name"),
+        enclosure_id: row.get("enclosure_id"),
+        quarantine,
+    }
+}
+
+#[get("/active")]
+pub async fn get_active_quarantines(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT q.id, q.animal_id, q.treatment_id, q.reason, q.start_date, q.required_days, q.end_date, q.cleared_by,
+            a.name AS animal_name, a.enclosure_id
+        FROM quarantines q
+        JOIN animals a ON a.id = q.animal_id
+        WHERE q.end_date IS NULL
+        ORDER BY q.start_date, q.id".to_string();
+    query_response(&pool, &req, sql, sql_args![], active_quarantine_from_row, "active_quarantines").await
+}
@@ -0,0 +1,155 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqlitePool, Row};
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::models::report::{AnimalsHandled, HourlyWorkload, RoleWorkload, StaffWorkload, WorkloadQuery};
+
+const DEFAULT_RANGE_DAYS: i64 = 30;
+
+// כל פעולה של עובד - האכלה או טיפול - בשורה אחת; מסונן לפי טווח תאריכים (4 פרמטרים)
+const ACTIVITY_SQL: &str = "SELECT staff_id, animal_id, feeding_time AS at, 1 AS is_feeding FROM feedings WHERE feeding_time >= ? AND feeding_time < ?
+    UNION ALL
+    SELECT staff_id, animal_id, date AS at, 0 AS is_feeding FROM medical_treatments WHERE date >= ? AND date < ?";
+
+// טווח ברירת מחדל - 30 הימים האחרונים; `to` כולל את היום עצמו
+fn date_range(query: &WorkloadQuery) -> Result<(NaiveDateTime, NaiveDateTime), HttpResponse> {
+    let to = query.to.unwrap_or_else(|| Local::now().date_naive());
+    let from = match query.from {
+        Some(from) => from,
+        None => TimeDelta::try_days(DEFAULT_RANGE_DAYS - 1)
+            .and_then(|delta| to.checked_sub_signed(delta))
+            .ok_or_else(|| HttpResponse::BadRequest().body("to is out of range"))?,
+    };
+    if from > to {
+        return Err(HttpResponse::BadRequest().body("from must not be after to"));
+    }
+    let Some(end) = TimeDelta::try_days(1).and_then(|delta| to.checked_add_signed(delta)) else {
+        return Err(HttpResponse::BadRequest().body("to is out of range"));
+    };
+    Ok((from.and_time(Default::default()), end.and_time(Default::default())))
+}
+
+fn staff_workload_from_row(row: &SqliteRow) -> StaffWorkload {
+    StaffWorkload {
+        staff_id: row.get("staff_id"),
+        staff_name: row.get("staff_name"),
+        role: row.get("role"),
+        period_start: row.get("period_start"),
+        feedings: row.get("feedings"),
+        treatments: row.get("treatments"),
+        total: row.get("total"),
+    }
+}
+
+#[get("/workload")]
+pub async fn get_staff_workload(pool: web::Data<SqlitePool>, query: web::Query<WorkloadQuery>, req: HttpRequest) -> impl Responder {
+    let (from, to) = match date_range(&query) {
+        Ok(range) => range,
+        Err(response) => return response,
+    };
+    // שבוע מתחיל ביום שני
+    let period_sql = match query.period.as_deref().unwrap_or("day") {
+        "day" => "date(w.at)",
+        "week" => "date(w.at, 'weekday 0', '-6 days')",
+        _ => return HttpResponse::BadRequest().body("Invalid period (use day or week)"),
+    };
+
+    let sql = format!(
+        "SELECT s.id AS staff_id, s.first_name || ' ' || s.last_name AS staff_name, s.role, {} AS period_start,
+            SUM(w.is_feeding) AS feedings, SUM(1 - w.is_feeding) AS treatments, COUNT(*) AS total
+        FROM ({}) w
+        JOIN staff s ON s.id = w.staff_id
+        GROUP BY s.id, period_start
+        ORDER BY period_start, s.id",
+        period_sql, ACTIVITY_SQL
+    );
+    query_response(&pool, &req, sql, sql_args![from, to, from, to], staff_workload_from_row, "staff_workload").await
+}
+
+fn role_workload_from_row(row: &SqliteRow) -> RoleWorkload {
+    RoleWorkload {
+        role: row.get("role"),
+        staff_count: row.get("staff_count"),
+        feedings: row.get("feedings"),
+        treatments: row.get("treatments"),
+        total: row.get("total"),
+    }
+}
+
+#[get("/workload/roles")]
+pub async fn get_role_workload(pool: web::Data<SqlitePool>, query: web::Query<WorkloadQuery>, req: HttpRequest) -> impl Responder {
+    let (from, to) = match date_range(&query) {
+        Ok(range) => range,
+        Err(response) => return response,
+    };
+
+    let sql = format!(
+        "SELECT s.role, COUNT(DISTINCT s.id) AS staff_count,
+            SUM(w.is_feeding) AS feedings, SUM(1 - w.is_feeding) AS treatments, COUNT(*) AS total
+        FROM ({}) w
+        JOIN staff s ON s.id = w.staff_id
+        GROUP BY s.role
+        ORDER BY total DESC, s.role",
+        ACTIVITY_SQL
+    );
+    query_response(&pool, &req, sql, sql_args![from, to, from, to], role_workload_from_row, "role_workload").await
+}
+
+fn hourly_workload_from_row(row: &SqliteRow) -> HourlyWorkload {
+    HourlyWorkload {
+        hour: row.get("hour"),
+        feedings: row.get("feedings"),
+        treatments: row.get("treatments"),
+        total: row.get("total"),
+    }
+}
+
+#[get("/workload/hours")]
+pub async fn get_hourly_workload(pool: web::Data<SqlitePool>, query: web::Query<WorkloadQuery>, req: HttpRequest) -> impl Responder {
+    let (from, to) = match date_range(&query) {
+        Ok(range) => range,
+        Err(response) => return response,
+    };
+
+    // השעות העמוסות ביותר קודם
+    let sql = format!(
+        "SELECT CAST(strftime('%H', w.at) AS INTEGER) AS hour,
+            SUM(w.is_feeding) AS feedings, SUM(1 - w.is_feeding) AS treatments, COUNT(*) AS total
+        FROM ({}) w
+        GROUP BY hour
+        ORDER BY total DESC, hour",
+        ACTIVITY_SQL
+    );
+    query_response(&pool, &req, sql, sql_args![from, to, from, to], hourly_workload_from_row, "hourly_workload").await
+}
+
+fn animals_handled_from_row(row: &SqliteRow) -> AnimalsHandled {
+    AnimalsHandled {
+        staff_id: row.get("staff_id"),
+        staff_name: row.get("staff_name"),
+        role: row.get("role"),
+        animals_handled: row.get("animals_handled"),
+        feedings: row.get("feedings"),
+        treatments: row.get("treatments"),
+    }
+}
+
+#[get("/workload/animals")]
+pub async fn get_animals_handled(pool: web::Data<SqlitePool>, query: web::Query<WorkloadQuery>, req: HttpRequest) -> impl Responder {
+    let (from, to) = match date_range(&query) {
+        Ok(range) => range,
+        Err(response) => return response,
+    };
+
+    let sql = format!(
+        "SELECT s.id AS staff_id, s.first_name || ' ' || s.last_name AS staff_name, s.role,
+            COUNT(DISTINCT w.animal_id) AS animals_handled,
+            SUM(w.is_feeding) AS feedings, SUM(1 - w.is_feeding) AS treatments
+        FROM ({}) w
+        JOIN staff s ON s.id = w.staff_id
+        GROUP BY s.id
+        ORDER BY animals_handled DESC, s.id",
+        ACTIVITY_SQL
+    );
+    query_response(&pool, &req, sql, sql_args![from, to, from, to], animals_handled_from_row, "animals_handled").await
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use chrono::{Local, TimeDelta};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::models::sensor::{
+    CreateSpeciesRangeRequest, IngestReadingsRequest, IngestSummary, ReadingBucket, ReadingsQuery, SensorAlert,
+    SensorAlertQuery, SpeciesRange,
+};
+
+const METRICS: [&str; 2] = ["temp", "humidity"];
+const SENSOR_ALERT_COLUMNS: &str = "id, enclosure_id, sensor_id, metric, value, recorded_at, species, min_value, max_value";
+const DEFAULT_BUCKET: &str = "1h";
+const DEFAULT_READINGS_HOURS: i64 = 24;
+// דלי רחב משנה לא מסכם שום דבר שימושי
+const MAX_BUCKET_SECONDS: i64 = 366 * 24 * 60 * 60;
+
+/// Bucket width in seconds from `15m`, `1h` or `1d`, at most a year.
+fn parse_bucket(bucket: &str) -> Option<i64> {
+    let bucket = bucket.trim();
+    let multiplier = match bucket.chars().last()? {
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    bucket[..bucket.len() - 1]
+        .parse::<i64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|seconds| *seconds <= MAX_BUCKET_SECONDS)
+}
+
+fn sensor_alert_from_row(row: &SqliteRow) -> SensorAlert {
+    SensorAlert {
+        id: row.get("id"),
+        enclosure_id: row.get("enclosure_id"),
+        sensor_id: row.get("sensor_id"),
+        metric: row.get("metric"),
+        value: row.get("value"),
+        recorded_at: row.get("recorded_at"),
+        species: row.get("species"),
+        min_value: row.get("min_value"),
+        max_value: row.get("max_value"),
+    }
+}
+
+fn species_range_from_row(row: &SqliteRow) -> SpeciesRange {
+    SpeciesRange {
+        id: row.get("id"),
+        species: row.get("species"),
+        metric: row.get("metric"),
+        min_value: row.get("min_value"),
+        max_value: row.get("max_value"),
+    }
+}
+
+// הטווחים של כל המינים שחיים כרגע במתחם
+async fn enclosure_ranges(conn: &mut SqliteConnection, enclosure_id: i64) -> Result<Vec<SpeciesRange>, sqlx::Error> {
+    let rows = sqlx::query("SELECT id, species, metric, min_value, max_value FROM species_ranges
+        WHERE species IN (SELECT DISTINCT species FROM animals WHERE enclosure_id = ? AND status = 'resident')
+        ORDER BY species, metric")
+        .bind(enclosure_id)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows.iter().map(species_range_from_row).collect())
+}
+
+#[post("/")]
+pub async fn ingest_readings(pool: web::Data<SqlitePool>, batch: web::Json<IngestReadingsRequest>) -> impl Responder {
+    if let Some(index) = batch.readings.iter().position(|r| !METRICS.contains(&r.metric.as_str())) {
+        return HttpResponse::BadRequest().body(format!("Reading {}: invalid metric (use temp or humidity)", index));
+    }
+    if let Some(index) = batch.readings.iter().position(|r| r.sensor_id.trim().is_empty() || !r.value.is_finite()) {
+        return HttpResponse::BadRequest().body(format!("Reading {}: sensor_id and a numeric value are required", index));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // טווחים נטענים פעם אחת לכל מתחם באצווה
+    let mut ranges: HashMap<i64, Vec<SpeciesRange>> = HashMap::new();
+    for reading in &batch.readings {
+        if ranges.contains_key(&reading.enclosure_id) {
+            continue;
+        }
+        match sqlx::query("SELECT id FROM enclosures WHERE id = ?")
+            .bind(reading.enclosure_id)
+            .fetch_optional(&mut *tx)
+            .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return HttpResponse::NotFound().body(format!("Enclosure {} not found", reading.enclosure_id)),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+        match enclosure_ranges(&mut tx, reading.enclosure_id).await {
+            Ok(enclosure_ranges) => ranges.insert(reading.enclosure_id, enclosure_ranges),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        };
+    }
+
+    let mut stored = 0;
+    let mut alerts = Vec::new();
+    for reading in &batch.readings {
+        // קריאה שכבר נשמרה (אותו חיישן ואותו זמן) לא נספרת שוב
+        match sqlx::query("INSERT OR IGNORE INTO sensor_readings (enclosure_id, metric, recorded_at, sensor_id, value) VALUES (?, ?, ?, ?, ?)")
+            .bind(reading.enclosure_id)
+            .bind(&reading.metric)
+            .bind(reading.recorded_at)
+            .bind(reading.sensor_id.trim())
+            .bind(reading.value)
+            .execute(&mut *tx)
+            .await
+        {
+            Ok(result) if result.rows_affected() == 0 => continue,
+            Ok(_) => stored += 1,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+
+        let out_of_range = ranges[&reading.enclosure_id]
+            .iter()
+            .filter(|range| range.metric == reading.metric && (reading.value < range.min_value || reading.value > range.max_value));
+        for range in out_of_range {
+            match sqlx::query(&format!(
+                "INSERT INTO sensor_alerts (enclosure_id, sensor_id, metric, value, recorded_at, species, min_value, max_value)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?) RETURNING {}",
+                SENSOR_ALERT_COLUMNS
+            ))
+            .bind(reading.enclosure_id)
+            .bind(reading.sensor_id.trim())
+            .bind(&reading.metric)
+            .bind(reading.value)
+            .bind(reading.recorded_at)
+            .bind(&range.species)
+            .bind(range.min_value)
+            .bind(range.max_value)
+            .fetch_one(&mut *tx)
+            .await
+            {
+                Ok(row) => alerts.push(sensor_alert_from_row(&row)),
+                Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+            }
+        }
+    }
+
+    match tx.commit().await {
+        Ok(_) => {
+            // הקריאות עצמן לא נשלחות כאירועים - רק קריאה שחרגה מהטווח
+            for alert in &alerts {
+                emit_event(&pool, "sensor_alert.created", alert).await;
+            }
+            HttpResponse::Created().json(IngestSummary {
+                received: batch.readings.len(),
+                stored,
+                duplicates: batch.readings.len() - stored,
+                alerts,
+            })
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn reading_bucket_from_row(row: &SqliteRow) -> ReadingBucket {
+    ReadingBucket {
+        bucket_start: row.get("bucket_start"),
+        avg: row.get("avg"),
+        min: row.get("min"),
+        max: row.get("max"),
+        readings: row.get("readings"),
+    }
+}
+
+#[get("/{id}/readings")]
+pub async fn get_enclosure_readings(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    query: web::Query<ReadingsQuery>,
+    req: HttpRequest,
+) -> impl Responder {
+    let enclosure_id = path.into_inner();
+    if !METRICS.contains(&query.metric.as_str()) {
+        return HttpResponse::BadRequest().body("Invalid metric (use temp or humidity)");
+    }
+    let Some(bucket_seconds) = parse_bucket(query.bucket.as_deref().unwrap_or(DEFAULT_BUCKET)) else {
+        return HttpResponse::BadRequest().body("Invalid bucket (use e.g. 15m, 1h or 1d, at most 366d)");
+    };
+    // ברירת מחדל - היממה האחרונה
+    let to = query.to.unwrap_or_else(|| Local::now().naive_local());
+    let Some(from) = query.from.or_else(|| to.checked_sub_signed(TimeDelta::try_hours(DEFAULT_READINGS_HOURS)?)) else {
+        return HttpResponse::BadRequest().body("to is out of range");
+    };
+    if from > to {
+        return HttpResponse::BadRequest().body("from must not be after to");
+    }
+
+    let sql = "SELECT datetime(CAST(strftime('%s', recorded_at) AS INTEGER) / ? * ?, 'unixepoch') AS bucket_start,
+            AVG(value) AS avg, MIN(value) AS min, MAX(value) AS max, COUNT(*) AS readings
+        FROM sensor_readings
+        WHERE enclosure_id = ? AND metric = ? AND recorded_at >= ? AND recorded_at < ? AND (? IS NULL OR sensor_id = ?)
+        GROUP BY bucket_start
+        ORDER BY bucket_start".to_string();
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![
+            bucket_seconds,
+            bucket_seconds,
+            enclosure_id,
+            query.metric.clone(),
+            from,
+            to,
+            query.sensor_id.clone(),
+            query.sensor_id.clone(),
+        ],
+        reading_bucket_from_row,
+        "sensor_readings",
+    )
+    .await
+}
+
+#[post("/ranges")]
+pub async fn upsert_species_range(pool: web::Data<SqlitePool>, range: web::Json<CreateSpeciesRangeRequest>) -> impl Responder {
+    if range.species.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Species is required");
+    }
+    if !METRICS.contains(&range.metric.as_str()) {
+        return HttpResponse::BadRequest().body("Invalid metric (use temp or humidity)");
+    }
+    if range.min_value > range.max_value {
+        return HttpResponse::BadRequest().body("min_value must not be above max_value");
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let existed = match sqlx::query("SELECT id FROM species_ranges WHERE species = ? AND metric = ?")
+        .bind(range.species.trim())
+        .bind(&range.metric)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(row) => row.is_some(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // טווח קיים לאותו מין ומדד מתעדכן
+    let saved = match sqlx::query("INSERT INTO species_ranges (species, metric, min_value, max_value) VALUES (?, ?, ?, ?)
+        ON CONFLICT (species, metric) DO UPDATE SET min_value = excluded.min_value, max_value = excluded.max_value
+        RETURNING id, species, metric, min_value, max_value")
+        .bind(range.species.trim())
+        .bind(&range.metric)
+        .bind(range.min_value)
+        .bind(range.max_value)
+        .fetch_one(&mut *tx)
+        .await
+    {
+        Ok(row) => species_range_from_row(&row),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match tx.commit().await {
+        Ok(_) => {
+            let event = if existed { "species_range.updated" } else { "species_range.created" };
+            emit_event(&pool, event, &saved).await;
+            HttpResponse::Ok().json(saved)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/ranges")]
+pub async fn get_species_ranges(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, species, metric, min_value, max_value FROM species_ranges ORDER BY species, metric".to_string();
+    query_response(&pool, &req, sql, sql_args![], species_range_from_row, "species_ranges").await
+}
+
+#[get("/alerts")]
+pub async fn get_sensor_alerts(pool: web::Data<SqlitePool>, query: web::Query<SensorAlertQuery>, req: HttpRequest) -> impl Responder {
+    let sql = format!(
+        "SELECT {} FROM sensor_alerts
+        WHERE (? IS NULL OR enclosure_id = ?) AND (? IS NULL OR metric = ?)
+        ORDER BY recorded_at DESC, id DESC",
+        SENSOR_ALERT_COLUMNS
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![query.enclosure_id, query.enclosure_id, query.metric.clone(), query.metric.clone()],
+        sensor_alert_from_row,
+        "sensor_alerts",
+    )
+    .await
+}
@@ -0,0 +1,96 @@
+use actix_web::{get, put, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{SqliteConnection, SqlitePool, Row};
+use crate::controllers::export_controller::list_response;
+use crate::models::setting::{Setting, UpdateSettingRequest};
+use crate::notify::has_control_chars;
+
+// הגדרות שאפשר לשנות, עם ערך ברירת המחדל שלהן
+pub const SHIFT_CHECK: &str = "shift_check";
+pub const MIN_REST_HOURS: &str = "min_rest_hours";
+pub const ALERT_INTERVAL_MINUTES: &str = "alert_interval_minutes";
+pub const SMTP_SERVER: &str = "smtp_server";
+pub const SMTP_FROM: &str = "smtp_from";
+pub const BACKUP_DIR: &str = "backup_dir";
+pub const BACKUP_INTERVAL_HOURS: &str = "backup_interval_hours";
+pub const BACKUP_KEEP: &str = "backup_keep";
+const DEFAULTS: [(&str, &str); 8] = [
+    (SHIFT_CHECK, "warn"),
+    (MIN_REST_HOURS, "11"),
+    (ALERT_INTERVAL_MINUTES, "5"),
+    (SMTP_SERVER, "127.0.0.1:2525"),
+    (SMTP_FROM, "alerts@zoo.local"),
+    (BACKUP_DIR, "backups"),
+    (BACKUP_INTERVAL_HOURS, "24"),
+    (BACKUP_KEEP, "7"),
+];
+
+fn default_value(key: &str) -> Option<&'static str> {
+    DEFAULTS.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+fn is_valid(key: &str, value: &str) -> bool {
+    match key {
+        SHIFT_CHECK => ["off", "warn", "reject"].contains(&value),
+        // מנוחה של יותר משבוע תסמן כל משמרת כהתנגשות
+        MIN_REST_HOURS => value.parse::<u32>().is_ok_and(|hours| hours <= 168),
+        ALERT_INTERVAL_MINUTES => value.parse::<u32>().is_ok_and(|minutes| minutes > 0),
+        SMTP_SERVER => value.contains(':'),
+        SMTP_FROM => value.contains('@') && !has_control_chars(value),
+        BACKUP_DIR => !value.trim().is_empty(),
+        // 0 מכבה את הגיבוי המתוזמן
+        BACKUP_INTERVAL_HOURS => value.parse::<u32>().is_ok(),
+        BACKUP_KEEP => value.parse::<u32>().is_ok_and(|keep| keep > 0),
+        _ => false,
+    }
+}
+
+pub async fn get_setting(conn: &mut SqliteConnection, key: &str) -> Result<String, sqlx::Error> {
+    let value: Option<String> = sqlx::query("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|row| row.get("value"));
+    Ok(value.unwrap_or_else(|| default_value(key).unwrap_or_default().to_string()))
+}
+
+#[get("/")]
+pub async fn get_settings(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let mut settings = Vec::new();
+    for (key, _) in DEFAULTS {
+        match get_setting(&mut conn, key).await {
+            Ok(value) => settings.push(Setting { key: key.to_string(), value }),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+    list_response(&settings, &req, "settings")
+}
+
+#[put("/{key}")]
+pub async fn update_setting(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<String>,
+    setting: web::Json<UpdateSettingRequest>,
+) -> impl Responder {
+    let key = path.into_inner();
+    if default_value(&key).is_none() {
+        return HttpResponse::NotFound().body("Unknown setting");
+    }
+    if !is_valid(&key, &setting.value) {
+        return HttpResponse::BadRequest().body(format!("Invalid value for {}", key));
+    }
+
+    match sqlx::query("INSERT INTO settings (key, value) VALUES (?, ?) ON CONFLICT (key) DO UPDATE SET value = excluded.value")
+        .bind(&key)
+        .bind(&setting.value)
+        .execute(&**pool)
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(Setting { key, value: setting.value.clone() }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
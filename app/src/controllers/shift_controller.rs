@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use chrono::{Datelike, Days, Local, NaiveDateTime, TimeDelta};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{list_response, query_response, sql_args};
+use crate::controllers::settings_controller::{get_setting, MIN_REST_HOURS, SHIFT_CHECK};
+use crate::models::event::DeletedEntity;
+use crate::models::shift::{
+    CreateShiftQuery, CreateShiftRequest, Rota, RotaDay, RotaQuery, RotaShift, Shift, ShiftConflict,
+    ShiftConflictResponse, ShiftListQuery,
+};
+
+const SHIFT_COLUMNS: &str = "id, staff_id, start_time, end_time, enclosure_id, area, required_role";
+
+fn shift_from_row(row: &SqliteRow) -> Shift {
+    Shift {
+        id: row.get("id"),
+        staff_id: row.get("staff_id"),
+        start_time: row.get("start_time"),
+        end_time: row.get("end_time"),
+        enclosure_id: row.get("enclosure_id"),
+        area: row.get("area"),
+        required_role: row.get("required_role"),
+    }
+}
+
+/// Result of checking that a staff member was on shift when they did some work.
+pub enum ShiftCheck {
+    Ok,
+    Warn(String),
+    Reject(String),
+}
+
+pub async fn check_on_shift(conn: &mut SqliteConnection, staff_id: i64, at: NaiveDateTime) -> Result<ShiftCheck, sqlx::Error> {
+    let mode = get_setting(conn, SHIFT_CHECK).await?;
+    if mode == "off" {
+        return Ok(ShiftCheck::Ok);
+    }
+
+    let on_shift = sqlx::query("SELECT id FROM staff_shifts WHERE staff_id = ? AND start_time <= ? AND end_time >= ? LIMIT 1")
+        .bind(staff_id)
+        .bind(at)
+        .bind(at)
+        .fetch_optional(&mut *conn)
+        .await?
+        .is_some();
+    if on_shift {
+        return Ok(ShiftCheck::Ok);
+    }
+
+    let message = format!("Staff member {} was not on shift at {}", staff_id, at);
+    Ok(if mode == "reject" { ShiftCheck::Reject(message) } else { ShiftCheck::Warn(message) })
+}
+
+// אזהרה נשלחת בכותרת Warning כדי לא לשנות את גוף התשובה
+pub fn warning_header(message: &str) -> (&'static str, String) {
+    ("Warning", format!("199 - \"{}\"", message))
+}
+
+// משמרות חופפות לאותו עובד, או הפסקה קצרה מהמנוחה המינימלית בין משמרות
+fn find_conflicts(shifts: &[Shift], min_rest_hours: i64) -> Vec<ShiftConflict> {
+    let mut by_staff: HashMap<i64, Vec<&Shift>> = HashMap::new();
+    for shift in shifts {
+        by_staff.entry(shift.staff_id).or_default().push(shift);
+    }
+
+    let min_rest = TimeDelta::try_hours(min_rest_hours).unwrap_or(TimeDelta::MAX);
+    let mut conflicts = Vec::new();
+    for staff_shifts in by_staff.values_mut() {
+        staff_shifts.sort_by_key(|s| (s.start_time, s.id));
+        for (i, shift) in staff_shifts.iter().enumerate() {
+            // סוף המנוחה מעבר לתאריך האחרון שאפשר לייצג - כל משמרת אחריה קרובה מדי
+            let rest_until = shift.end_time.checked_add_signed(min_rest);
+            for other in staff_shifts[i + 1..].iter().take_while(|o| rest_until.is_none_or(|until| o.start_time < until)) {
+                let overlapping = other.start_time < shift.end_time;
+                conflicts.push(ShiftConflict {
+                    kind: if overlapping { "double_booked" } else { "insufficient_rest" }.to_string(),
+                    staff_id: shift.staff_id,
+                    shift_id: shift.id,
+                    other_shift_id: other.id,
+                    rest_hours: (!overlapping)
+                        .then(|| (other.start_time - shift.end_time).num_minutes() as f64 / 60.0),
+                });
+            }
+        }
+    }
+    conflicts.sort_by_key(|c| (c.staff_id, c.shift_id, c.other_shift_id));
+    conflicts
+}
+
+async fn min_rest_hours(conn: &mut SqliteConnection) -> Result<i64, sqlx::Error> {
+    Ok(get_setting(conn, MIN_REST_HOURS).await?.parse::<i64>().unwrap_or(0).clamp(0, 168))
+}
+
+#[post("/shifts")]
+pub async fn create_shift(
+    pool: web::Data<SqlitePool>,
+    query: web::Query<CreateShiftQuery>,
+    shift: web::Json<CreateShiftRequest>,
+) -> impl Responder {
+    if shift.end_time <= shift.start_time {
+        return HttpResponse::BadRequest().body("Shift must end after it starts");
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match sqlx::query("SELECT role FROM staff WHERE id = ?")
+        .bind(shift.staff_id)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some(row)) => {
+            let role: String = row.get("role");
+            if shift.required_role.as_ref().is_some_and(|required| !required.eq_ignore_ascii_case(&role)) {
+                return HttpResponse::BadRequest().body("Staff member does not have the required role");
+            }
+        }
+        Ok(None) => return HttpResponse::NotFound().body("Staff not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    // בדיקת התנגשויות מול שאר המשמרות של העובד - אלא אם כן force=true
+    if !query.force.unwrap_or(false) {
+        let mut shifts: Vec<Shift> = match sqlx::query(&format!("SELECT {} FROM staff_shifts WHERE staff_id = ?", SHIFT_COLUMNS))
+            .bind(shift.staff_id)
+            .fetch_all(&mut *tx)
+            .await
+        {
+            Ok(rows) => rows.iter().map(shift_from_row).collect(),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        };
+        let min_rest = match min_rest_hours(&mut tx).await {
+            Ok(hours) => hours,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        };
+        // למשמרת החדשה עדיין אין מזהה - 0 מסמן אותה
+        shifts.push(Shift {
+            id: 0,
+            staff_id: shift.staff_id,
+            start_time: shift.start_time,
+            end_time: shift.end_time,
+            enclosure_id: shift.enclosure_id,
+            area: shift.area.clone(),
+            required_role: shift.required_role.clone(),
+        });
+        let conflicts: Vec<ShiftConflict> = find_conflicts(&shifts, min_rest)
+            .into_iter()
+            .filter(|c| c.shift_id == 0 || c.other_shift_id == 0)
+            .collect();
+        if !conflicts.is_empty() {
+            return HttpResponse::Conflict().json(ShiftConflictResponse {
+                message: "Shift conflicts with the staff member's other shifts (use ?force=true to override)".to_string(),
+                conflicts,
+            });
+        }
+    }
+
+    let id = match sqlx::query("INSERT INTO staff_shifts (staff_id, start_time, end_time, enclosure_id, area, required_role) VALUES (?, ?, ?, ?, ?, ?)")
+        .bind(shift.staff_id)
+        .bind(shift.start_time)
+        .bind(shift.end_time)
+        .bind(shift.enclosure_id)
+        .bind(&shift.area)
+        .bind(&shift.required_role)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(result) => result.last_insert_rowid(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match tx.commit().await {
+        Ok(_) => {
+            let new_shift = Shift {
+                id,
+                staff_id: shift.staff_id,
+                start_time: shift.start_time,
+                end_time: shift.end_time,
+                enclosure_id: shift.enclosure_id,
+                area: shift.area.clone(),
+                required_role: shift.required_role.clone(),
+            };
+            emit_event(&pool, "shift.created", &new_shift).await;
+            HttpResponse::Created().json(new_shift)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/shifts")]
+pub async fn get_shifts(pool: web::Data<SqlitePool>, query: web::Query<ShiftListQuery>, req: HttpRequest) -> impl Responder {
+    let sql = format!("SELECT {} FROM staff_shifts WHERE ? IS NULL OR staff_id = ? ORDER BY start_time, id", SHIFT_COLUMNS);
+    query_response(&pool, &req, sql, sql_args![query.staff_id, query.staff_id], shift_from_row, "shifts").await
+}
+
+#[delete("/shifts/{id}")]
+pub async fn delete_shift(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query("DELETE FROM staff_shifts WHERE id = ? RETURNING enclosure_id")
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => {
+            emit_event(&pool, "shift.deleted", &DeletedEntity { id, animal_id: None, enclosure_id: row.get("enclosure_id") }).await;
+            HttpResponse::Ok().body("Deleted")
+        }
+        Ok(None) => HttpResponse::NotFound().body("Shift not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/shifts/conflicts")]
+pub async fn get_shift_conflicts(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let shifts: Vec<Shift> = match sqlx::query(&format!("SELECT {} FROM staff_shifts", SHIFT_COLUMNS))
+        .fetch_all(&mut *conn)
+        .await
+    {
+        Ok(rows) => rows.iter().map(shift_from_row).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    match min_rest_hours(&mut conn).await {
+        Ok(min_rest) => list_response(&find_conflicts(&shifts, min_rest), &req, "shift_conflicts"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/rota")]
+pub async fn get_rota(pool: web::Data<SqlitePool>, query: web::Query<RotaQuery>) -> impl Responder {
+    // השבוע מתחיל ביום שני; כל תאריך בשבוע מחזיר את אותו סידור
+    let day = query.week.unwrap_or_else(|| Local::now().date_naive());
+    let week = day
+        .checked_sub_days(Days::new(day.weekday().num_days_from_monday() as u64))
+        .and_then(|start| Some((start, start.checked_add_days(Days::new(7))?)));
+    let Some((week_start, week_end)) = week else {
+        return HttpResponse::BadRequest().body("Week is out of range");
+    };
+
+    match sqlx::query("SELECT s.id, s.staff_id, s.start_time, s.end_time, s.enclosure_id, s.area, s.required_role,
+            st.first_name || ' ' || st.last_name AS staff_name
+        FROM staff_shifts s
+        JOIN staff st ON st.id = s.staff_id
+        WHERE s.start_time >= ? AND s.start_time < ?
+        ORDER BY s.start_time, s.id")
+        .bind(week_start.and_hms_opt(0, 0, 0))
+        .bind(week_end.and_hms_opt(0, 0, 0))
+        .fetch_all(&**pool)
+        .await
+    {
+        Ok(rows) => {
+            let mut days: Vec<RotaDay> = week_start
+                .iter_days()
+                .take(7)
+                .map(|date| RotaDay { date, shifts: Vec::new() })
+                .collect();
+            for row in &rows {
+                let shift = shift_from_row(row);
+                let index = (shift.start_time.date() - week_start).num_days() as usize;
+                days[index].shifts.push(RotaShift { shift, staff_name: row.get("staff_name") });
+            }
+            HttpResponse::Ok().json(Rota { week_start, days })
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
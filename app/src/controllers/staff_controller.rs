@@ -1,25 +1,48 @@
-use actix_web::{get, post, put, delete, web, HttpResponse, Responder};
-use sqlx::{SqlitePool, Row};
+use actix_web::{get, post, put, delete, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqliteConnection, SqlitePool, Row};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::db::references_to;
 use crate::models::staff::{Staff, CreateStaffRequest, UpdateStaffRequest};
+use crate::models::event::DeletedEntity;
 
-#[post("/")]
-pub async fn create_staff(pool: web::Data<SqlitePool>, staff: web::Json<CreateStaffRequest>) -> impl Responder {
-    match sqlx::query("INSERT INTO staff (first_name, last_name, role, phone) VALUES (?, ?, ?, ?)")
+pub fn staff_from_row(row: &SqliteRow) -> Staff {
+    Staff {
+        id: row.get("id"),
+        first_name: row.get("first_name"),
+        last_name: row.get("last_name"),
+        role: row.get("role"),
+        phone: row.get("phone"),
+    }
+}
+
+pub async fn insert_staff(conn: &mut SqliteConnection, staff: &CreateStaffRequest) -> Result<Staff, sqlx::Error> {
+    let result = sqlx::query("INSERT INTO staff (first_name, last_name, role, phone) VALUES (?, ?, ?, ?)")
         .bind(&staff.first_name)
         .bind(&staff.last_name)
         .bind(&staff.role)
         .bind(&staff.phone)
-        .execute(&**pool)
-        .await
-    {
-        Ok(result) => {
-            let new_staff = Staff {
-                id: result.last_insert_rowid(),
-                first_name: staff.first_name.clone(),
-                last_name: staff.last_name.clone(),
-                role: staff.role.clone(),
-                phone: staff.phone.clone(),
-            };
+        .execute(&mut *conn)
+        .await?;
+    Ok(Staff {
+        id: result.last_insert_rowid(),
+        first_name: staff.first_name.clone(),
+        last_name: staff.last_name.clone(),
+        role: staff.role.clone(),
+        phone: staff.phone.clone(),
+    })
+}
+
+#[post("/")]
+pub async fn create_staff(pool: web::Data<SqlitePool>, staff: web::Json<CreateStaffRequest>) -> impl Responder {
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match insert_staff(&mut conn, &staff).await {
+        Ok(new_staff) => {
+            emit_event(&pool, "staff.created", &new_staff).await;
             HttpResponse::Created().json(new_staff)
         }
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
@@ -27,23 +50,9 @@ pub async fn create_staff(pool: web::Data<SqlitePool>, staff: web::Json<CreateSt
 }
 
 #[get("/")]
-pub async fn get_staff(pool: web::Data<SqlitePool>) -> impl Responder {
-    match sqlx::query("SELECT id, first_name, last_name, role, phone FROM staff ORDER BY id")
-        .fetch_all(&**pool)
-        .await
-    {
-        Ok(rows) => {
-            let staff: Vec<Staff> = rows.iter().map(|row| Staff {
-                id: row.get("id"),
-                first_name: row.get("first_name"),
-                last_name: row.get("last_name"),
-                role: row.get("role"),
-                phone: row.get("phone"),
-            }).collect();
-            HttpResponse::Ok().json(staff)
-        }
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    }
+pub async fn get_staff(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, first_name, last_name, role, phone FROM staff ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], staff_from_row, "staff").await
 }
 
 #[get("/{id}")]
@@ -81,7 +90,17 @@ pub async fn update_staff(pool: web::Data<SqlitePool>, path: web::Path<i64>, upd
         .execute(&**pool)
         .await
     {
-        Ok(res) if res.rows_affected() > 0 => HttpResponse::Ok().body("Updated"),
+        Ok(res) if res.rows_affected() > 0 => {
+            let staff = Staff {
+                id,
+                first_name: updated.first_name.clone(),
+                last_name: updated.last_name.clone(),
+                role: updated.role.clone(),
+                phone: updated.phone.clone(),
+            };
+            emit_event(&pool, "staff.updated", &staff).await;
+            HttpResponse::Ok().body("Updated")
+        }
         Ok(_) => HttpResponse::NotFound().body("Staff not found"),
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
@@ -90,12 +109,29 @@ pub async fn update_staff(pool: web::Data<SqlitePool>, path: web::Path<i64>, upd
 #[delete("/{id}")]
 pub async fn delete_staff(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
     let id = path.into_inner();
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // עובד שרשום בהאכלות, משמרות או משימות נשאר בהיסטוריה
+    match references_to(&mut conn, "staff", id).await {
+        Ok(references) if references.is_empty() => {}
+        Ok(references) => {
+            return HttpResponse::Conflict().body(format!("Staff member is still referenced by {}", references.join(", ")))
+        }
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
     match sqlx::query("DELETE FROM staff WHERE id = ?")
         .bind(id)
-        .execute(&**pool)
+        .execute(&mut *conn)
         .await
     {
-        Ok(res) if res.rows_affected() > 0 => HttpResponse::Ok().body("Deleted"),
+        Ok(res) if res.rows_affected() > 0 => {
+            emit_event(&pool, "staff.deleted", &DeletedEntity { id, animal_id: None, enclosure_id: None }).await;
+            HttpResponse::Ok().body("Deleted")
+        }
         Ok(_) => HttpResponse::NotFound().body("Staff not found"),
         Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     }
@@ -104,28 +140,11 @@ pub async fn delete_staff(pool: web::Data<SqlitePool>, path: web::Path<i64>) ->
 #[get("/role/{role}")]
 pub async fn get_staff_by_role(
     pool: web::Data<SqlitePool>,
-    path: web::Path<String>
+    path: web::Path<String>,
+    req: HttpRequest,
 ) -> impl Responder {
     let role: String = path.into_inner();
 
-    match sqlx::query("SELECT * FROM staff WHERE role = ?")
-        .bind(role)
-        .fetch_all(&**pool)
-        .await
-    {
-        Ok(rows) => {
-            let staff: Vec<Staff> = rows.iter().map(|row| Staff {
-                id: row.get("id"),
-                first_name: row.get("first_name"),
-                last_name: row.get("last_name"),
-                role: row.get("role"),
-                phone: row.get("phone")
-            }).collect();
-
-            HttpResponse::Ok().json(staff)
-        }
-        Err(e) => HttpResponse::InternalServerError()
-            .body(format!("Database error: {}", e))
-    }
-    
+    let sql = "SELECT * FROM staff WHERE role = ?".to_string();
+    query_response(&pool, &req, sql, sql_args![role], staff_from_row, "staff").await
 }
\ No newline at end of file
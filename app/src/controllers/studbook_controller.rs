@@ -0,0 +1,369 @@
+use std::collections::{BTreeSet, HashMap};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{SqlitePool, Row};
+use chrono::{NaiveDate, NaiveDateTime};
+use crate::controllers::animal_controller::{animal_from_row, ANIMAL_COLUMNS};
+use crate::controllers::breeding_controller::{is_ancestor, load_parents};
+use crate::controllers::event_controller::emit_event;
+use crate::models::animal::Animal;
+use crate::models::studbook::{
+    LocationEntry, StudbookCsvRow, StudbookEntry, StudbookExportQuery, StudbookImportQuery, StudbookImportSummary,
+};
+
+pub struct StudbookImport {
+    pub summary: StudbookImportSummary,
+    // חיות שקיבלו הורה מהייבוא, במצבן אחרי השינוי
+    pub updated: Vec<Animal>,
+}
+
+pub async fn build_studbook(pool: &SqlitePool, species: Option<&str>) -> Result<Vec<StudbookEntry>, sqlx::Error> {
+    let animals: Vec<Animal> = sqlx::query(&format!("SELECT {} FROM animals WHERE ? IS NULL OR species = ? ORDER BY id", ANIMAL_COLUMNS))
+        .bind(species)
+        .bind(species)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(animal_from_row)
+        .collect();
+
+    // מספרי ספר הגידול של כל החיות, כדי לכתוב אב ואם לפי המספר שלהם
+    let studbook_numbers: HashMap<i64, Option<String>> = sqlx::query("SELECT id, studbook_number FROM animals")
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| (row.get("id"), row.get("studbook_number")))
+        .collect();
+
+    let mut events: HashMap<i64, Vec<(NaiveDateTime, String, Option<String>)>> = HashMap::new();
+    for row in sqlx::query("SELECT animal_id, event_date, event_type, institution FROM animal_events ORDER BY event_date, id")
+        .fetch_all(pool)
+        .await?
+    {
+        events
+            .entry(row.get("animal_id"))
+            .or_default()
+            .push((row.get("event_date"), row.get("event_type"), row.get("institution")));
+    }
+
+    // הורה מקומי בלי מספר ספר גידול נשאר ריק - למזהה הפנימי שלנו אין משמעות אצל מוסד אחר
+    let parent_label = |parent_id: Option<i64>, external: &Option<String>| match parent_id {
+        Some(parent_id) => studbook_numbers.get(&parent_id).cloned().flatten(),
+        None => external.clone(),
+    };
+
+    Ok(animals.iter().map(|animal| {
+        let animal_events = events.remove(&animal.id).unwrap_or_default();
+        let event_date = |event_type: &str| {
+            animal_events
+                .iter()
+                .find(|(_, event, _)| event == event_type)
+                .map(|(date, _, _)| date.date())
+        };
+        StudbookEntry {
+            studbook_number: animal.studbook_number.clone(),
+            name: animal.name.clone(),
+            species: animal.species.clone(),
+            sex: animal.sex.clone(),
+            birth_date: event_date("born"),
+            sire: parent_label(animal.sire_id, &animal.sire_external),
+            dam: parent_label(animal.dam_id, &animal.dam_external),
+            death_date: event_date("deceased"),
+            locations: animal_events
+                .iter()
+                .filter(|(_, event, _)| event != "born" && event != "deceased")
+                .map(|(date, event, institution)| LocationEntry {
+                    date: date.date(),
+                    event: event.clone(),
+                    institution: institution.clone(),
+                })
+                .collect(),
+        }
+    }).collect())
+}
+
+pub fn studbook_to_csv(entries: &[StudbookEntry]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for entry in entries {
+        let location_history = entry
+            .locations
+            .iter()
+            .map(|l| format!("{}:{}:{}", l.date, l.event, l.institution.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>()
+            .join(";");
+        writer.serialize(StudbookCsvRow {
+            studbook_number: entry.studbook_number.clone(),
+            name: Some(entry.name.clone()),
+            species: entry.species.clone(),
+            sex: entry.sex.clone(),
+            birth_date: entry.birth_date,
+            sire: entry.sire.clone(),
+            dam: entry.dam.clone(),
+            location_history: Some(location_history),
+            death_date: entry.death_date,
+        })?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+pub fn studbook_to_xml(entries: &[StudbookEntry]) -> String {
+    let optional = |tag: &str, value: Option<String>| match value {
+        Some(value) => format!("    <{tag}>{}</{tag}>\n", xml_escape(&value)),
+        None => String::new(),
+    };
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<studbook>\n");
+    for entry in entries {
+        xml.push_str("  <animal>\n");
+        xml.push_str(&optional("studbook_number", entry.studbook_number.clone()));
+        xml.push_str(&optional("name", Some(entry.name.clone())));
+        xml.push_str(&optional("species", Some(entry.species.clone())));
+        xml.push_str(&optional("sex", Some(entry.sex.clone())));
+        xml.push_str(&optional("birth_date", entry.birth_date.map(|d| d.to_string())));
+        xml.push_str(&optional("sire", entry.sire.clone()));
+        xml.push_str(&optional("dam", entry.dam.clone()));
+        xml.push_str("    <locations>\n");
+        for location in &entry.locations {
+            xml.push_str(&format!(
+                "      <location date=\"{}\" event=\"{}\" institution=\"{}\"/>\n",
+                location.date,
+                xml_escape(&location.event),
+                xml_escape(location.institution.as_deref().unwrap_or(""))
+            ));
+        }
+        xml.push_str("    </locations>\n");
+        xml.push_str(&optional("death_date", entry.death_date.map(|d| d.to_string())));
+        xml.push_str("  </animal>\n");
+    }
+    xml.push_str("</studbook>\n");
+    xml
+}
+
+pub fn parse_studbook_csv(data: &str) -> Result<Vec<StudbookCsvRow>, String> {
+    let mut reader = csv::Reader::from_reader(data.as_bytes());
+    let mut rows = Vec::new();
+    for (index, result) in reader.deserialize::<StudbookCsvRow>().enumerate() {
+        // שורה 1 היא הכותרת
+        let row = result.map_err(|e| format!("Row {}: {}", index + 2, e))?;
+        if row.studbook_number.as_deref().is_none_or(|n| n.trim().is_empty()) {
+            return Err(format!("Row {}: studbook_number is required", index + 2));
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Reads records in the layout of `studbook_to_xml`; locations become the same `date:event:institution` history as in CSV.
+pub fn parse_studbook_xml(data: &str) -> Result<Vec<StudbookCsvRow>, String> {
+    let document = roxmltree::Document::parse(data).map_err(|e| format!("Invalid XML: {}", e))?;
+    let root = document.root_element();
+    if !root.has_tag_name("studbook") {
+        return Err("Root element must be <studbook>".to_string());
+    }
+
+    let mut rows = Vec::new();
+    for (index, animal) in root.children().filter(|node| node.has_tag_name("animal")).enumerate() {
+        let record = index + 1;
+        let text = |tag: &str| {
+            animal
+                .children()
+                .find(|node| node.has_tag_name(tag))
+                .and_then(|node| node.text())
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let required = |tag: &str| text(tag).ok_or_else(|| format!("Animal {}: {} is required", record, tag));
+        let date = |tag: &str| {
+            text(tag)
+                .map(|value| value.parse::<NaiveDate>().map_err(|e| format!("Animal {}: invalid {}: {}", record, tag, e)))
+                .transpose()
+        };
+        let location_history = animal.children().find(|node| node.has_tag_name("locations")).map(|locations| {
+            locations
+                .children()
+                .filter(|node| node.has_tag_name("location"))
+                .map(|location| {
+                    format!(
+                        "{}:{}:{}",
+                        location.attribute("date").unwrap_or(""),
+                        location.attribute("event").unwrap_or(""),
+                        location.attribute("institution").unwrap_or("")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(";")
+        });
+
+        rows.push(StudbookCsvRow {
+            studbook_number: Some(required("studbook_number")?),
+            name: text("name"),
+            species: required("species")?,
+            sex: required("sex")?,
+            birth_date: date("birth_date")?,
+            sire: text("sire"),
+            dam: text("dam"),
+            location_history,
+            death_date: date("death_date")?,
+        });
+    }
+    Ok(rows)
+}
+
+// פורמט מפורש גובר, ואחרת לפי סוג התוכן או סיומת הקובץ
+pub fn studbook_format(requested: Option<&str>, hint: &str) -> Result<&'static str, String> {
+    match requested {
+        Some(format) if format.eq_ignore_ascii_case("csv") => Ok("csv"),
+        Some(format) if format.eq_ignore_ascii_case("xml") => Ok("xml"),
+        Some(_) => Err("Invalid format (use csv or xml)".to_string()),
+        None if hint.contains("xml") => Ok("xml"),
+        None => Ok("csv"),
+    }
+}
+
+pub fn parse_studbook(data: &str, format: &str) -> Result<Vec<StudbookCsvRow>, String> {
+    match format {
+        "xml" => parse_studbook_xml(data),
+        _ => parse_studbook_csv(data),
+    }
+}
+
+/// Stores partner records and fills in unknown parents of our animals that share a studbook number.
+pub async fn import_studbook(pool: &SqlitePool, rows: &[StudbookCsvRow]) -> Result<StudbookImport, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut parents_linked = 0;
+    let mut linked_animals = BTreeSet::new();
+
+    for row in rows {
+        sqlx::query("INSERT INTO studbook_records (studbook_number, name, species, sex, birth_date, sire, dam, location_history, death_date)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (studbook_number) DO UPDATE SET name = excluded.name, species = excluded.species, sex = excluded.sex,
+                birth_date = excluded.birth_date, sire = excluded.sire, dam = excluded.dam,
+                location_history = excluded.location_history, death_date = excluded.death_date")
+            .bind(&row.studbook_number)
+            .bind(&row.name)
+            .bind(&row.species)
+            .bind(&row.sex)
+            .bind(row.birth_date)
+            .bind(&row.sire)
+            .bind(&row.dam)
+            .bind(&row.location_history)
+            .bind(row.death_date)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let local_numbers: HashMap<String, i64> = sqlx::query("SELECT id, studbook_number FROM animals WHERE studbook_number IS NOT NULL")
+        .fetch_all(&mut *tx)
+        .await?
+        .iter()
+        .map(|row| (row.get("studbook_number"), row.get("id")))
+        .collect();
+    let mut parents = load_parents(&mut tx).await?;
+
+    for row in rows {
+        let Some(animal_id) = row.studbook_number.as_ref().and_then(|n| local_numbers.get(n)).copied() else {
+            continue;
+        };
+        let known = sqlx::query("SELECT sire_id IS NOT NULL OR sire_external IS NOT NULL AS has_sire,
+                dam_id IS NOT NULL OR dam_external IS NOT NULL AS has_dam FROM animals WHERE id = ?")
+            .bind(animal_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        for (column, parent, has_parent) in [
+            ("sire", &row.sire, known.get::<bool, _>("has_sire")),
+            ("dam", &row.dam, known.get::<bool, _>("has_dam")),
+        ] {
+            let Some(parent) = parent.as_ref().filter(|_| !has_parent) else {
+                continue;
+            };
+            match local_numbers.get(parent).copied() {
+                Some(parent_id) if parent_id != animal_id && !is_ancestor(&parents, animal_id, parent_id) => {
+                    sqlx::query(&format!("UPDATE animals SET {}_id = ? WHERE id = ?", column))
+                        .bind(parent_id)
+                        .bind(animal_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    let entry = parents.entry(animal_id).or_default();
+                    if column == "sire" { entry.0 = Some(parent_id) } else { entry.1 = Some(parent_id) }
+                }
+                Some(_) => continue,
+                None => {
+                    sqlx::query(&format!("UPDATE animals SET {}_external = ? WHERE id = ?", column))
+                        .bind(parent)
+                        .bind(animal_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+            parents_linked += 1;
+            linked_animals.insert(animal_id);
+        }
+    }
+
+    let mut updated = Vec::new();
+    for animal_id in linked_animals {
+        let row = sqlx::query(&format!("SELECT {} FROM animals WHERE id = ?", ANIMAL_COLUMNS))
+            .bind(animal_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        updated.push(animal_from_row(&row));
+    }
+
+    tx.commit().await?;
+    Ok(StudbookImport { summary: StudbookImportSummary { records: rows.len(), parents_linked }, updated })
+}
+
+#[get("/export")]
+pub async fn export_studbook(pool: web::Data<SqlitePool>, query: web::Query<StudbookExportQuery>) -> impl Responder {
+    let entries = match build_studbook(&pool, query.species.as_deref()).await {
+        Ok(entries) => entries,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match query.format.as_deref().unwrap_or("csv") {
+        "csv" => match studbook_to_csv(&entries) {
+            Ok(csv) => HttpResponse::Ok().content_type("text/csv").body(csv),
+            Err(e) => HttpResponse::InternalServerError().body(format!("CSV error: {}", e)),
+        },
+        "xml" => HttpResponse::Ok().content_type("application/xml").body(studbook_to_xml(&entries)),
+        _ => HttpResponse::BadRequest().body("Invalid format (use csv or xml)"),
+    }
+}
+
+#[post("/import")]
+pub async fn import_studbook_file(
+    pool: web::Data<SqlitePool>,
+    query: web::Query<StudbookImportQuery>,
+    req: HttpRequest,
+    body: String,
+) -> impl Responder {
+    let content_type = req
+        .headers()
+        .get("Content-Type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    let rows = match studbook_format(query.format.as_deref(), content_type).and_then(|format| parse_studbook(&body, format)) {
+        Ok(rows) => rows,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+
+    match import_studbook(&pool, &rows).await {
+        Ok(import) => {
+            for animal in &import.updated {
+                emit_event(&pool, "animal.updated", animal).await;
+            }
+            HttpResponse::Ok().json(import.summary)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
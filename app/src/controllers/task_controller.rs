@@ -0,0 +1,412 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqlitePool, Row};
+use chrono::Local;
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::models::event::DeletedEntity;
+use crate::models::task::{
+    CreateTaskCommentRequest, CreateTaskRequest, OverdueTask, Task, TaskComment, TaskDetails, TaskListQuery,
+    TaskStatusRequest, UpdateTaskRequest,
+};
+
+const TASK_COLUMNS: &str = "id, title, task_type, description, enclosure_id, animal_id, assignee_id, due_date, priority, status, created_at, completed_at";
+const TASK_TYPES: [&str; 4] = ["cleaning", "enrichment", "repair", "other"];
+const PRIORITIES: [&str; 4] = ["low", "medium", "high", "urgent"];
+
+// הדחוף ביותר קודם
+const PRIORITY_ORDER: &str = "CASE priority WHEN 'urgent' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END";
+
+// אין עדיין התחברות - הכותרת X-Staff-Id רק אומרת מי הלקוח טוען שהוא, וכל לקוח יכול לשלוח כל מזהה.
+// היא בוחרת תצוגה (המשימות שלי) או נרשמת כמידע (מי אישר התראה), ולא מגינה על שום דבר
+pub const STAFF_HEADER: &str = "X-Staff-Id";
+
+fn task_from_row(row: &SqliteRow) -> Task {
+    Task {
+        id: row.get("id"),
+        title: row.get("title"),
+        task_type: row.get("task_type"),
+        description: row.get("description"),
+        enclosure_id: row.get("enclosure_id"),
+        animal_id: row.get("animal_id"),
+        assignee_id: row.get("assignee_id"),
+        due_date: row.get("due_date"),
+        priority: row.get("priority"),
+        status: row.get("status"),
+        created_at: row.get("created_at"),
+        completed_at: row.get("completed_at"),
+    }
+}
+
+fn comment_from_row(row: &SqliteRow) -> TaskComment {
+    TaskComment {
+        id: row.get("id"),
+        task_id: row.get("task_id"),
+        staff_id: row.get("staff_id"),
+        body: row.get("body"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn invalid_task(title: &str, task_type: &str, priority: &str) -> Option<&'static str> {
+    if title.trim().is_empty() {
+        return Some("Title is required");
+    }
+    if !TASK_TYPES.contains(&task_type) {
+        return Some("Invalid task type (use cleaning, enrichment, repair or other)");
+    }
+    if !PRIORITIES.contains(&priority) {
+        return Some("Invalid priority (use low, medium, high or urgent)");
+    }
+    None
+}
+
+// מתחם, חיה ועובד שהמשימה מצביעה עליהם חייבים להתקיים
+async fn missing_reference(
+    pool: &SqlitePool,
+    enclosure_id: Option<i64>,
+    animal_id: Option<i64>,
+    assignee_id: Option<i64>,
+) -> Result<Option<&'static str>, sqlx::Error> {
+    let row = sqlx::query("SELECT
+            (? IS NULL OR EXISTS (SELECT 1 FROM enclosures WHERE id = ?)) AS enclosure_found,
+            (? IS NULL OR EXISTS (SELECT 1 FROM animals WHERE id = ?)) AS animal_found,
+            (? IS NULL OR EXISTS (SELECT 1 FROM staff WHERE id = ?)) AS assignee_found")
+        .bind(enclosure_id)
+        .bind(enclosure_id)
+        .bind(animal_id)
+        .bind(animal_id)
+        .bind(assignee_id)
+        .bind(assignee_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(if !row.get::<bool, _>("enclosure_found") {
+        Some("Enclosure not found")
+    } else if !row.get::<bool, _>("animal_found") {
+        Some("Animal not found")
+    } else if !row.get::<bool, _>("assignee_found") {
+        Some("Assignee not found")
+    } else {
+        None
+    })
+}
+
+// המעבר היחיד המותר: open -> in_progress -> done
+fn next_status(status: &str) -> Option<&'static str> {
+    match status {
+        "open" => Some("in_progress"),
+        "in_progress" => Some("done"),
+        _ => None,
+    }
+}
+
+#[post("/")]
+pub async fn create_task(pool: web::Data<SqlitePool>, task: web::Json<CreateTaskRequest>) -> impl Responder {
+    let priority = task.priority.clone().unwrap_or_else(|| "medium".to_string());
+    if let Some(message) = invalid_task(&task.title, &task.task_type, &priority) {
+        return HttpResponse::BadRequest().body(message);
+    }
+    match missing_reference(&pool, task.enclosure_id, task.animal_id, task.assignee_id).await {
+        Ok(None) => {}
+        Ok(Some(message)) => return HttpResponse::NotFound().body(message),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+    let created_at = Local::now().naive_local();
+
+    match sqlx::query("INSERT INTO tasks (title, task_type, description, enclosure_id, animal_id, assignee_id, due_date, priority, status, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'open', ?)")
+        .bind(&task.title)
+        .bind(&task.task_type)
+        .bind(&task.description)
+        .bind(task.enclosure_id)
+        .bind(task.animal_id)
+        .bind(task.assignee_id)
+        .bind(task.due_date)
+        .bind(&priority)
+        .bind(created_at)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_task = Task {
+                id: result.last_insert_rowid(),
+                title: task.title.clone(),
+                task_type: task.task_type.clone(),
+                description: task.description.clone(),
+                enclosure_id: task.enclosure_id,
+                animal_id: task.animal_id,
+                assignee_id: task.assignee_id,
+                due_date: task.due_date,
+                priority,
+                status: "open".to_string(),
+                created_at,
+                completed_at: None,
+            };
+            emit_event(&pool, "task.created", &new_task).await;
+            HttpResponse::Created().json(new_task)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/")]
+pub async fn get_tasks(pool: web::Data<SqlitePool>, query: web::Query<TaskListQuery>, req: HttpRequest) -> impl Responder {
+    let sql = format!(
+        "SELECT {} FROM tasks
+        WHERE (? IS NULL OR status = ?) AND (? IS NULL OR assignee_id = ?) AND (? IS NULL OR enclosure_id = ?)
+        ORDER BY {}, due_date IS NULL, due_date, id",
+        TASK_COLUMNS, PRIORITY_ORDER
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![
+            query.status.clone(),
+            query.status.clone(),
+            query.assignee_id,
+            query.assignee_id,
+            query.enclosure_id,
+            query.enclosure_id,
+        ],
+        task_from_row,
+        "tasks",
+    )
+    .await
+}
+
+/// Open tasks assigned to the staff member named by the `X-Staff-Id` header. The header is taken on trust and is
+/// not authentication: any client can read any staff member's tasks, exactly as through `GET /tasks/?assignee_id=`.
+#[get("/my")]
+pub async fn get_my_tasks(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let Some(staff_id) = req
+        .headers()
+        .get(STAFF_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok())
+    else {
+        return HttpResponse::BadRequest().body(format!("{} header is required", STAFF_HEADER));
+    };
+
+    // משימות פתוחות של העובד; משימות שהושלמו לא מופיעות
+    let sql = format!(
+        "SELECT {} FROM tasks WHERE assignee_id = ? AND status != 'done' ORDER BY {}, due_date IS NULL, due_date, id",
+        TASK_COLUMNS, PRIORITY_ORDER
+    );
+    query_response(&pool, &req, sql, sql_args![staff_id], task_from_row, "my_tasks").await
+}
+
+fn overdue_task_from_row(row: &SqliteRow) -> OverdueTask {
+    OverdueTask { task: task_from_row(row), days_overdue: row.get("days_overdue") }
+}
+
+#[get("/overdue")]
+pub async fn get_overdue_tasks(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let today = Local::now().date_naive();
+    let sql = format!(
+        "SELECT {}, CAST(julianday(?) - julianday(due_date) AS INTEGER) AS days_overdue FROM tasks
+        WHERE status != 'done' AND due_date < ? ORDER BY due_date, {}, id",
+        TASK_COLUMNS, PRIORITY_ORDER
+    );
+    query_response(&pool, &req, sql, sql_args![today, today], overdue_task_from_row, "overdue_tasks").await
+}
+
+#[get("/{id}")]
+pub async fn get_task_by_id(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+
+    let task = match sqlx::query(&format!("SELECT {} FROM tasks WHERE id = ?", TASK_COLUMNS))
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => task_from_row(&row),
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match sqlx::query("SELECT id, task_id, staff_id, body, created_at FROM task_comments WHERE task_id = ? ORDER BY created_at, id")
+        .bind(id)
+        .fetch_all(&**pool)
+        .await
+    {
+        Ok(rows) => HttpResponse::Ok().json(TaskDetails {
+            task,
+            comments: rows.iter().map(comment_from_row).collect(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[put("/{id}")]
+pub async fn update_task(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    updated: web::Json<UpdateTaskRequest>,
+) -> impl Responder {
+    let id = path.into_inner();
+    if let Some(message) = invalid_task(&updated.title, &updated.task_type, &updated.priority) {
+        return HttpResponse::BadRequest().body(message);
+    }
+    match missing_reference(&pool, updated.enclosure_id, updated.animal_id, updated.assignee_id).await {
+        Ok(None) => {}
+        Ok(Some(message)) => return HttpResponse::NotFound().body(message),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    // הסטטוס משתנה רק דרך /{id}/status
+    match sqlx::query(&format!(
+        "UPDATE tasks SET title = ?, task_type = ?, description = ?, enclosure_id = ?, animal_id = ?, assignee_id = ?, due_date = ?, priority = ?
+        WHERE id = ? RETURNING {}",
+        TASK_COLUMNS
+    ))
+    .bind(&updated.title)
+    .bind(&updated.task_type)
+    .bind(&updated.description)
+    .bind(updated.enclosure_id)
+    .bind(updated.animal_id)
+    .bind(updated.assignee_id)
+    .bind(updated.due_date)
+    .bind(&updated.priority)
+    .bind(id)
+    .fetch_optional(&**pool)
+    .await
+    {
+        Ok(Some(row)) => {
+            let task = task_from_row(&row);
+            emit_event(&pool, "task.updated", &task).await;
+            HttpResponse::Ok().json(task)
+        }
+        Ok(None) => HttpResponse::NotFound().body("Task not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[delete("/{id}")]
+pub async fn delete_task(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    if let Err(e) = sqlx::query("DELETE FROM task_comments WHERE task_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+    {
+        return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+    }
+
+    match sqlx::query("DELETE FROM tasks WHERE id = ? RETURNING animal_id, enclosure_id")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some(row)) => match tx.commit().await {
+            Ok(_) => {
+                let deleted = DeletedEntity { id, animal_id: row.get("animal_id"), enclosure_id: row.get("enclosure_id") };
+                emit_event(&pool, "task.deleted", &deleted).await;
+                HttpResponse::Ok().body("Deleted")
+            }
+            Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        },
+        Ok(None) => HttpResponse::NotFound().body("Task not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{id}/status")]
+pub async fn update_task_status(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    change: web::Json<TaskStatusRequest>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    let current: String = match sqlx::query("SELECT status FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => row.get("status"),
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    if next_status(&current) != Some(change.status.as_str()) {
+        return HttpResponse::Conflict().body(format!("Cannot move a task from {} to {}", current, change.status));
+    }
+
+    let completed_at = (change.status == "done").then(|| Local::now().naive_local());
+    match sqlx::query(&format!("UPDATE tasks SET status = ?, completed_at = ? WHERE id = ? AND status = ? RETURNING {}", TASK_COLUMNS))
+        .bind(&change.status)
+        .bind(completed_at)
+        .bind(id)
+        .bind(&current)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => {
+            let task = task_from_row(&row);
+            emit_event(&pool, "task.updated", &task).await;
+            HttpResponse::Ok().json(task)
+        }
+        // הסטטוס השתנה בינתיים בבקשה אחרת
+        Ok(None) => HttpResponse::Conflict().body("Task status changed, try again"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/{id}/comments")]
+pub async fn create_task_comment(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    comment: web::Json<CreateTaskCommentRequest>,
+) -> impl Responder {
+    let task_id = path.into_inner();
+    if comment.body.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Comment body is required");
+    }
+
+    match sqlx::query("SELECT EXISTS (SELECT 1 FROM tasks WHERE id = ?) AS task_found, EXISTS (SELECT 1 FROM staff WHERE id = ?) AS staff_found")
+        .bind(task_id)
+        .bind(comment.staff_id)
+        .fetch_one(&**pool)
+        .await
+    {
+        Ok(row) if !row.get::<bool, _>("task_found") => return HttpResponse::NotFound().body("Task not found"),
+        Ok(row) if !row.get::<bool, _>("staff_found") => return HttpResponse::NotFound().body("Staff not found"),
+        Ok(_) => {}
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    let created_at = Local::now().naive_local();
+    match sqlx::query("INSERT INTO task_comments (task_id, staff_id, body, created_at) VALUES (?, ?, ?, ?)")
+        .bind(task_id)
+        .bind(comment.staff_id)
+        .bind(&comment.body)
+        .bind(created_at)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_comment = TaskComment {
+                id: result.last_insert_rowid(),
+                task_id,
+                staff_id: comment.staff_id,
+                body: comment.body.clone(),
+                created_at,
+            };
+            HttpResponse::Created().json(new_comment)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/{id}/comments")]
+pub async fn get_task_comments(pool: web::Data<SqlitePool>, path: web::Path<i64>, req: HttpRequest) -> impl Responder {
+    let task_id = path.into_inner();
+    let sql = "SELECT id, task_id, staff_id, body, created_at FROM task_comments WHERE task_id = ? ORDER BY created_at, id".to_string();
+    query_response(&pool, &req, sql, sql_args![task_id], comment_from_row, "task_comments").await
+}
@@ -0,0 +1,288 @@
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqlitePool, Row};
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use crate::controllers::event_controller::emit_event;
+use crate::controllers::export_controller::{list_response, query_response, sql_args};
+use crate::controllers::medical_treatment_controller::insert_treatment;
+use crate::controllers::shift_controller::warning_header;
+use crate::models::medical_treatment::CreateMedicalTreatmentRequest;
+use crate::models::vaccination::{
+    CreateVaccinationRequest, CreateVaccineIntervalRequest, CreateVaccineRequest, DueQuery, DueVaccination,
+    Vaccination, Vaccine, VaccineInterval,
+};
+
+const DEFAULT_DUE_WITHIN_DAYS: i64 = 30;
+// מאה שנה - מעבר לזה זו טעות הקלדה ולא טווח אמיתי
+pub const MAX_WITHIN_DAYS: i64 = 36500;
+
+// מפענח טווח זמן כמו "30d", "4w" או "30" למספר ימים
+pub fn parse_within(within: &str) -> Option<i64> {
+    let within = within.trim();
+    let (number, multiplier) = match within.chars().last()? {
+        'd' => (&within[..within.len() - 1], 1),
+        'w' => (&within[..within.len() - 1], 7),
+        _ => (within, 1),
+    };
+    number
+        .parse::<i64>()
+        .ok()
+        .filter(|n| *n >= 0)
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|days| *days <= MAX_WITHIN_DAYS)
+}
+
+#[post("/vaccines")]
+pub async fn create_vaccine(pool: web::Data<SqlitePool>, vaccine: web::Json<CreateVaccineRequest>) -> impl Responder {
+    match sqlx::query("INSERT INTO vaccines (name, description) VALUES (?, ?)")
+        .bind(&vaccine.name)
+        .bind(&vaccine.description)
+        .execute(&**pool)
+        .await
+    {
+        Ok(result) => {
+            let new_vaccine = Vaccine {
+                id: result.last_insert_rowid(),
+                name: vaccine.name.clone(),
+                description: vaccine.description.clone(),
+            };
+            emit_event(&pool, "vaccine.created", &new_vaccine).await;
+            HttpResponse::Created().json(new_vaccine)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn vaccine_from_row(row: &SqliteRow) -> Vaccine {
+    Vaccine {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+    }
+}
+
+#[get("/vaccines")]
+pub async fn get_vaccines(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, name, description FROM vaccines ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], vaccine_from_row, "vaccines").await
+}
+
+#[post("/vaccines/{id}/intervals")]
+pub async fn set_vaccine_interval(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    interval: web::Json<CreateVaccineIntervalRequest>,
+) -> impl Responder {
+    let vaccine_id = path.into_inner();
+    if !(1..=MAX_WITHIN_DAYS).contains(&interval.interval_days) {
+        return HttpResponse::BadRequest().body(format!("Interval must be between 1 and {} days", MAX_WITHIN_DAYS));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let existed = match sqlx::query("SELECT id FROM vaccine_intervals WHERE vaccine_id = ? AND species = ?")
+        .bind(vaccine_id)
+        .bind(&interval.species)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(row) => row.is_some(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // מין שכבר מוגדר לו מרווח - המרווח מתעדכן
+    let id: i64 = match sqlx::query("INSERT INTO vaccine_intervals (vaccine_id, species, interval_days) VALUES (?, ?, ?)
+        ON CONFLICT (vaccine_id, species) DO UPDATE SET interval_days = excluded.interval_days
+        RETURNING id")
+        .bind(vaccine_id)
+        .bind(&interval.species)
+        .bind(interval.interval_days)
+        .fetch_one(&mut *tx)
+        .await
+    {
+        Ok(row) => row.get("id"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match tx.commit().await {
+        Ok(_) => {
+            let new_interval = VaccineInterval {
+                id,
+                vaccine_id,
+                species: interval.species.clone(),
+                interval_days: interval.interval_days,
+            };
+            let event = if existed { "vaccine_interval.updated" } else { "vaccine_interval.created" };
+            emit_event(&pool, event, &new_interval).await;
+            HttpResponse::Ok().json(new_interval)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn vaccine_interval_from_row(row: &SqliteRow) -> VaccineInterval {
+    VaccineInterval {
+        id: row.get("id"),
+        vaccine_id: row.get("vaccine_id"),
+        species: row.get("species"),
+        interval_days: row.get("interval_days"),
+    }
+}
+
+#[get("/vaccines/intervals")]
+pub async fn get_vaccine_intervals(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, vaccine_id, species, interval_days FROM vaccine_intervals ORDER BY vaccine_id, species".to_string();
+    query_response(&pool, &req, sql, sql_args![], vaccine_interval_from_row, "vaccine_intervals").await
+}
+
+#[post("/vaccinations")]
+pub async fn create_vaccination(
+    pool: web::Data<SqlitePool>,
+    vaccination: web::Json<CreateVaccinationRequest>,
+) -> impl Responder {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let vaccine_name: String = match sqlx::query("SELECT name FROM vaccines WHERE id = ?")
+        .bind(vaccination.vaccine_id)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some(row)) => row.get("name"),
+        Ok(None) => return HttpResponse::NotFound().body("Vaccine not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    // כל חיסון נרשם גם כטיפול רפואי בהיסטוריה של החיה, עם אותן בדיקות של חיה, עובד, משמרת והסמכות
+    let treatment = CreateMedicalTreatmentRequest {
+        animal_id: vaccination.animal_id,
+        staff_id: vaccination.staff_id,
+        date: vaccination.date,
+        description: format!("Vaccination: {}", vaccine_name),
+        procedure_type: Some("vaccination".to_string()),
+        follow_up_date: None,
+        outcome: None,
+        diagnoses: Vec::new(),
+        medications: Vec::new(),
+    };
+    let (new_treatment, shift_warning) = match insert_treatment(&mut tx, &treatment).await {
+        Ok(created) => created,
+        Err(e) => return e.response(),
+    };
+    let treatment_id = new_treatment.treatment.id;
+
+    let id = match sqlx::query("INSERT INTO vaccinations (animal_id, vaccine_id, staff_id, treatment_id, date, batch_number) VALUES (?, ?, ?, ?, ?, ?)")
+        .bind(vaccination.animal_id)
+        .bind(vaccination.vaccine_id)
+        .bind(vaccination.staff_id)
+        .bind(treatment_id)
+        .bind(vaccination.date)
+        .bind(&vaccination.batch_number)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(result) => result.last_insert_rowid(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match tx.commit().await {
+        Ok(_) => {
+            let new_vaccination = Vaccination {
+                id,
+                animal_id: vaccination.animal_id,
+                vaccine_id: vaccination.vaccine_id,
+                staff_id: vaccination.staff_id,
+                treatment_id,
+                date: vaccination.date,
+                batch_number: vaccination.batch_number.clone(),
+            };
+            let mut response = HttpResponse::Created();
+            if let Some(message) = shift_warning {
+                response.insert_header(warning_header(&message));
+            }
+            emit_event(&pool, "medical_treatment.created", &new_treatment).await;
+            emit_event(&pool, "vaccination.created", &new_vaccination).await;
+            response.json(new_vaccination)
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+fn vaccination_from_row(row: &SqliteRow) -> Vaccination {
+    Vaccination {
+        id: row.get("id"),
+        animal_id: row.get("animal_id"),
+        vaccine_id: row.get("vaccine_id"),
+        staff_id: row.get("staff_id"),
+        treatment_id: row.get("treatment_id"),
+        date: row.get("date"),
+        batch_number: row.get("batch_number"),
+    }
+}
+
+#[get("/vaccinations")]
+pub async fn get_vaccinations(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, animal_id, vaccine_id, staff_id, treatment_id, date, batch_number FROM vaccinations ORDER BY date, id".to_string();
+    query_response(&pool, &req, sql, sql_args![], vaccination_from_row, "vaccinations").await
+}
+
+#[get("/vaccinations/due")]
+pub async fn get_due_vaccinations(pool: web::Data<SqlitePool>, query: web::Query<DueQuery>, req: HttpRequest) -> impl Responder {
+    let within_days = match &query.within {
+        Some(within) => match parse_within(within) {
+            Some(days) => days,
+            None => return HttpResponse::BadRequest().body(format!("Invalid within (use e.g. 30d or 4w, up to {} days)", MAX_WITHIN_DAYS)),
+        },
+        None => DEFAULT_DUE_WITHIN_DAYS,
+    };
+    let now = Local::now().naive_local();
+    let Some(horizon) = TimeDelta::try_days(within_days).and_then(|delta| now.checked_add_signed(delta)) else {
+        return HttpResponse::BadRequest().body("Within is out of range");
+    };
+
+    // כל צירוף של חיה וחיסון שמוגדר למין שלה, עם החיסון האחרון שקיבלה
+    match sqlx::query("SELECT a.id AS animal_id, a.name AS animal_name, a.species, v.id AS vaccine_id, v.name AS vaccine_name, i.interval_days,
+            (SELECT MAX(x.date) FROM vaccinations x WHERE x.animal_id = a.id AND x.vaccine_id = v.id) AS last_vaccination
+        FROM animals a
+        JOIN vaccine_intervals i ON i.species = a.species
+        JOIN vaccines v ON v.id = i.vaccine_id
+        WHERE a.status = 'resident'
+        ORDER BY a.id, v.id")
+        .fetch_all(&**pool)
+        .await
+    {
+        Ok(rows) => {
+            let mut due: Vec<DueVaccination> = rows.iter().filter_map(|row| {
+                let last_vaccination: Option<NaiveDateTime> = row.get("last_vaccination");
+                // מרווח ששמור במסד יכול להיות ישן מלפני ההגבלה - תאריך שלא ניתן לחשב אינו בטווח
+                let due_date = match last_vaccination {
+                    Some(last) => Some(TimeDelta::try_days(row.get("interval_days")).and_then(|delta| last.checked_add_signed(delta))?),
+                    None => None,
+                };
+                let status = match due_date {
+                    None => "overdue",
+                    Some(date) if date < now => "overdue",
+                    Some(date) if date <= horizon => "due_soon",
+                    Some(_) => return None,
+                };
+                Some(DueVaccination {
+                    animal_id: row.get("animal_id"),
+                    animal_name: row.get("animal_name"),
+                    species: row.get("species"),
+                    vaccine_id: row.get("vaccine_id"),
+                    vaccine_name: row.get("vaccine_name"),
+                    last_vaccination,
+                    due_date,
+                    status: status.to_string(),
+                })
+            }).collect();
+            due.sort_by_key(|d| d.due_date);
+            list_response(&due, &req, "due_vaccinations")
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
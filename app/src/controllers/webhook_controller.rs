@@ -0,0 +1,399 @@
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use sqlx::{sqlite::SqliteRow, SqlitePool, Row};
+use chrono::{Duration, Local, NaiveDateTime};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use crate::controllers::event_controller::{ACTIONS, ENTITIES};
+use crate::controllers::export_controller::{query_response, sql_args};
+use crate::models::webhook::{
+    CreateWebhookRequest, DeliveryListQuery, UpdateWebhookRequest, WebhookDelivery, WebhookEvent, WebhookSubscription,
+};
+use crate::notify::{is_webhook_url, post_json, WEBHOOK_URL_RULE};
+
+const DELIVERY_COLUMNS: &str = "id, subscription_id, event_type, payload, status, attempts, last_status_code, last_error, next_attempt_at, created_at, delivered_at, redelivery_of";
+const DELIVERY_STATUSES: [&str; 3] = ["pending", "delivered", "failed"];
+
+// ניסיון ראשון מיד, ואחריו המתנה שמוכפלת בכל כישלון: 30 שניות, דקה, 2, 4, 8
+const MAX_ATTEMPTS: i64 = 6;
+const BASE_RETRY_SECONDS: i64 = 30;
+// משלוח שנלקח לטיפול לא נלקח שוב עד שהזמן הזה עובר
+const CLAIM_SECONDS: i64 = 60;
+const DISPATCH_EVERY_SECONDS: u64 = 15;
+
+fn is_valid_event_type(event_type: &str) -> bool {
+    event_type == "*"
+        || event_type
+            .split_once('.')
+            .is_some_and(|(entity, action)| ENTITIES.contains(&entity) && ACTIONS.contains(&action))
+}
+
+fn subscription_from_row(row: &SqliteRow) -> WebhookSubscription {
+    let event_types: String = row.get("event_types");
+    WebhookSubscription {
+        id: row.get("id"),
+        url: row.get("url"),
+        event_types: event_types.split(',').map(str::to_string).collect(),
+        enabled: row.get("enabled"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn delivery_from_row(row: &SqliteRow) -> WebhookDelivery {
+    WebhookDelivery {
+        id: row.get("id"),
+        subscription_id: row.get("subscription_id"),
+        event_type: row.get("event_type"),
+        payload: row.get("payload"),
+        status: row.get("status"),
+        attempts: row.get("attempts"),
+        last_status_code: row.get("last_status_code"),
+        last_error: row.get("last_error"),
+        next_attempt_at: row.get("next_attempt_at"),
+        created_at: row.get("created_at"),
+        delivered_at: row.get("delivered_at"),
+        redelivery_of: row.get("redelivery_of"),
+    }
+}
+
+// מחזיר את סוגי האירועים מנורמלים, או הודעת שגיאה
+fn validate_subscription(url: &str, event_types: &[String]) -> Result<String, String> {
+    if !is_webhook_url(url) {
+        return Err(format!("Webhook url {}", WEBHOOK_URL_RULE));
+    }
+    let event_types: Vec<String> = event_types.iter().map(|t| t.trim().to_lowercase()).collect();
+    if event_types.is_empty() {
+        return Err("At least one event type is required".to_string());
+    }
+    if let Some(invalid) = event_types.iter().find(|t| !is_valid_event_type(t)) {
+        return Err(format!(
+            "Invalid event type '{}' (use <{}>.<{}> or *)",
+            invalid,
+            ENTITIES.join("|"),
+            ACTIONS.join("|")
+        ));
+    }
+    Ok(event_types.join(","))
+}
+
+/// Hex HMAC-SHA256 of the payload, sent as `X-Zoo-Signature: sha256=<hex>`.
+pub fn sign_payload(secret: &str, payload: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Queues an event for every matching subscription and starts sending it in the background.
+pub async fn queue_event<T: Serialize>(pool: &SqlitePool, event_type: &str, occurred_at: NaiveDateTime, data: &T) {
+    let payload = match serde_json::to_string(&WebhookEvent { event: event_type, occurred_at, data }) {
+        Ok(payload) => payload,
+        Err(e) => return eprintln!("⚠️ Cannot serialize {} event: {}", event_type, e),
+    };
+    match queue_deliveries(pool, event_type, &payload, occurred_at).await {
+        Ok(0) => {}
+        Ok(_) => {
+            let pool = pool.clone();
+            tokio::spawn(async move { deliver_due(&pool).await });
+        }
+        Err(e) => eprintln!("⚠️ Cannot queue {} event: {}", event_type, e),
+    }
+}
+
+async fn queue_deliveries(pool: &SqlitePool, event_type: &str, payload: &str, now: NaiveDateTime) -> Result<usize, sqlx::Error> {
+    let subscriptions: Vec<WebhookSubscription> = sqlx::query("SELECT id, url, event_types, enabled, created_at FROM webhook_subscriptions WHERE enabled = 1")
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(subscription_from_row)
+        .collect();
+
+    let mut queued = 0;
+    for subscription in subscriptions
+        .iter()
+        .filter(|s| s.event_types.iter().any(|t| t == "*" || t == event_type))
+    {
+        sqlx::query("INSERT INTO webhook_deliveries (subscription_id, event_type, payload, status, next_attempt_at, created_at)
+            VALUES (?, ?, ?, 'pending', ?, ?)")
+            .bind(subscription.id)
+            .bind(event_type)
+            .bind(payload)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await?;
+        queued += 1;
+    }
+    Ok(queued)
+}
+
+// לוקח משלוח פנוי לטיפול; None אם מישהו אחר כבר לקח אותו
+async fn claim_delivery(pool: &SqlitePool, id: i64, now: NaiveDateTime) -> Result<Option<(WebhookDelivery, String, String)>, sqlx::Error> {
+    // מנוי מושבת לא מקבל משלוחים; הם מחכים עד שיופעל מחדש
+    let claimed = sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = ?
+        WHERE id = ? AND status = 'pending' AND next_attempt_at <= ?
+            AND subscription_id IN (SELECT id FROM webhook_subscriptions WHERE enabled = 1)
+        RETURNING id")
+        .bind(now + Duration::seconds(CLAIM_SECONDS))
+        .bind(id)
+        .bind(now)
+        .fetch_optional(pool)
+        .await?;
+    if claimed.is_none() {
+        return Ok(None);
+    }
+    let row = sqlx::query(&format!(
+        "SELECT {},
+            (SELECT url FROM webhook_subscriptions s WHERE s.id = subscription_id) AS url,
+            (SELECT secret FROM webhook_subscriptions s WHERE s.id = subscription_id) AS secret
+        FROM webhook_deliveries WHERE id = ?",
+        DELIVERY_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|row| (delivery_from_row(&row), row.get("url"), row.get("secret"))))
+}
+
+async fn attempt_delivery(pool: &SqlitePool, delivery: &WebhookDelivery, url: &str, secret: &str) -> Result<(), sqlx::Error> {
+    let headers = [
+        ("X-Zoo-Event", delivery.event_type.clone()),
+        ("X-Zoo-Delivery", delivery.id.to_string()),
+        ("X-Zoo-Signature", format!("sha256={}", sign_payload(secret, &delivery.payload))),
+    ];
+    let (status_code, error) = match post_json(url, &delivery.payload, &headers).await {
+        Ok(status) if (200..300).contains(&status) => (Some(status), None),
+        Ok(status) => (Some(status), Some(format!("HTTP {}", status))),
+        Err(e) => (None, Some(e)),
+    };
+
+    let now = Local::now().naive_local();
+    let attempts = delivery.attempts + 1;
+    let (status, next_attempt_at, delivered_at) = match &error {
+        None => ("delivered", None, Some(now)),
+        Some(_) if attempts >= MAX_ATTEMPTS => ("failed", None, None),
+        Some(_) => ("pending", Some(now + Duration::seconds(BASE_RETRY_SECONDS << (attempts - 1))), None),
+    };
+    sqlx::query("UPDATE webhook_deliveries SET status = ?, attempts = ?, last_status_code = ?, last_error = ?, next_attempt_at = ?, delivered_at = ?
+        WHERE id = ?")
+        .bind(status)
+        .bind(attempts)
+        .bind(status_code.map(i64::from))
+        .bind(&error)
+        .bind(next_attempt_at)
+        .bind(delivered_at)
+        .bind(delivery.id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Sends every pending delivery whose next attempt is due.
+pub async fn deliver_due(pool: &SqlitePool) {
+    let now = Local::now().naive_local();
+    let due: Vec<i64> = match sqlx::query("SELECT id FROM webhook_deliveries WHERE status = 'pending' AND next_attempt_at <= ? ORDER BY id")
+        .bind(now)
+        .fetch_all(pool)
+        .await
+    {
+        Ok(rows) => rows.iter().map(|row| row.get("id")).collect(),
+        Err(e) => return eprintln!("❌ Webhook dispatcher failed: {}", e),
+    };
+
+    for id in due {
+        let result = match claim_delivery(pool, id, now).await {
+            Ok(Some((delivery, url, secret))) => attempt_delivery(pool, &delivery, &url, &secret).await,
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("❌ Webhook delivery #{} failed: {}", id, e);
+        }
+    }
+}
+
+/// Background task started with the server: retries due webhook deliveries.
+pub async fn run_webhook_dispatcher(pool: SqlitePool) {
+    loop {
+        deliver_due(&pool).await;
+        tokio::time::sleep(std::time::Duration::from_secs(DISPATCH_EVERY_SECONDS)).await;
+    }
+}
+
+#[post("/")]
+pub async fn create_webhook(pool: web::Data<SqlitePool>, webhook: web::Json<CreateWebhookRequest>) -> impl Responder {
+    let url = webhook.url.trim();
+    let event_types = match validate_subscription(url, &webhook.event_types) {
+        Ok(event_types) => event_types,
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
+    if webhook.secret.is_empty() {
+        return HttpResponse::BadRequest().body("Secret is required to sign payloads");
+    }
+
+    match sqlx::query("INSERT INTO webhook_subscriptions (url, event_types, secret, enabled, created_at) VALUES (?, ?, ?, 1, ?)
+        RETURNING id, url, event_types, enabled, created_at")
+        .bind(url)
+        .bind(&event_types)
+        .bind(&webhook.secret)
+        .bind(Local::now().naive_local())
+        .fetch_one(&**pool)
+        .await
+    {
+        Ok(row) => HttpResponse::Created().json(subscription_from_row(&row)),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/")]
+pub async fn get_webhooks(pool: web::Data<SqlitePool>, req: HttpRequest) -> impl Responder {
+    let sql = "SELECT id, url, event_types, enabled, created_at FROM webhook_subscriptions ORDER BY id".to_string();
+    query_response(&pool, &req, sql, sql_args![], subscription_from_row, "webhooks").await
+}
+
+#[put("/{id}")]
+pub async fn update_webhook(
+    pool: web::Data<SqlitePool>,
+    path: web::Path<i64>,
+    updated: web::Json<UpdateWebhookRequest>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let url = updated.url.trim();
+    let event_types = match validate_subscription(url, &updated.event_types) {
+        Ok(event_types) => event_types,
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
+    if updated.secret.as_deref() == Some("") {
+        return HttpResponse::BadRequest().body("Secret cannot be empty");
+    }
+
+    // בלי secret - נשאר הקיים
+    match sqlx::query("UPDATE webhook_subscriptions SET url = ?, event_types = ?, secret = COALESCE(?, secret), enabled = ? WHERE id = ?
+        RETURNING id, url, event_types, enabled, created_at")
+        .bind(url)
+        .bind(&event_types)
+        .bind(&updated.secret)
+        .bind(updated.enabled)
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => HttpResponse::Ok().json(subscription_from_row(&row)),
+        Ok(None) => HttpResponse::NotFound().body("Webhook not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[delete("/{id}")]
+pub async fn delete_webhook(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    if let Err(e) = sqlx::query("DELETE FROM webhook_deliveries WHERE subscription_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+    {
+        return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
+    }
+
+    match sqlx::query("DELETE FROM webhook_subscriptions WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(res) if res.rows_affected() > 0 => match tx.commit().await {
+            Ok(_) => HttpResponse::Ok().body("Deleted"),
+            Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        },
+        Ok(_) => HttpResponse::NotFound().body("Webhook not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[get("/deliveries")]
+pub async fn get_deliveries(pool: web::Data<SqlitePool>, query: web::Query<DeliveryListQuery>, req: HttpRequest) -> impl Responder {
+    if query.status.as_deref().is_some_and(|status| !DELIVERY_STATUSES.contains(&status)) {
+        return HttpResponse::BadRequest().body("Invalid status (use pending, delivered or failed)");
+    }
+
+    let sql = format!(
+        "SELECT {} FROM webhook_deliveries
+        WHERE (? IS NULL OR subscription_id = ?) AND (? IS NULL OR status = ?) AND (? IS NULL OR event_type = ?)
+        ORDER BY id DESC",
+        DELIVERY_COLUMNS
+    );
+    query_response(
+        &pool,
+        &req,
+        sql,
+        sql_args![
+            query.subscription_id,
+            query.subscription_id,
+            query.status.clone(),
+            query.status.clone(),
+            query.event_type.clone(),
+            query.event_type.clone(),
+        ],
+        delivery_from_row,
+        "webhook_deliveries",
+    )
+    .await
+}
+
+#[get("/deliveries/{id}")]
+pub async fn get_delivery_by_id(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    match sqlx::query(&format!("SELECT {} FROM webhook_deliveries WHERE id = ?", DELIVERY_COLUMNS))
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) => HttpResponse::Ok().json(delivery_from_row(&row)),
+        Ok(None) => HttpResponse::NotFound().body("Delivery not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
+
+#[post("/deliveries/{id}/redeliver")]
+pub async fn redeliver(pool: web::Data<SqlitePool>, path: web::Path<i64>) -> impl Responder {
+    let id = path.into_inner();
+    let now = Local::now().naive_local();
+
+    match sqlx::query("SELECT s.enabled FROM webhook_deliveries d JOIN webhook_subscriptions s ON s.id = d.subscription_id WHERE d.id = ?")
+        .bind(id)
+        .fetch_optional(&**pool)
+        .await
+    {
+        Ok(Some(row)) if row.get::<bool, _>("enabled") => {}
+        Ok(Some(_)) => return HttpResponse::Conflict().body("Webhook is disabled"),
+        Ok(None) => return HttpResponse::NotFound().body("Delivery not found"),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+
+    // משלוח חדש עם אותו תוכן - המקורי נשאר ביומן כמו שהיה
+    match sqlx::query(&format!(
+        "INSERT INTO webhook_deliveries (subscription_id, event_type, payload, status, next_attempt_at, created_at, redelivery_of)
+        SELECT subscription_id, event_type, payload, 'pending', ?, ?, id FROM webhook_deliveries WHERE id = ?
+        RETURNING {}",
+        DELIVERY_COLUMNS
+    ))
+    .bind(now)
+    .bind(now)
+    .bind(id)
+    .fetch_optional(&**pool)
+    .await
+    {
+        Ok(Some(row)) => {
+            let delivery = delivery_from_row(&row);
+            let pool = pool.get_ref().clone();
+            tokio::spawn(async move { deliver_due(&pool).await });
+            HttpResponse::Accepted().json(delivery)
+        }
+        Ok(None) => HttpResponse::NotFound().body("Delivery not found"),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    }
+}
@@ -1,14 +1,607 @@
-use sqlx::sqlite::SqlitePool;
+use std::fs::{File, OpenOptions, TryLockError};
+use sqlx::{sqlite::SqlitePool, Row, SqliteConnection};
 
-pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
-    let database_url = "sqlite:src/zoo.db";
-    let pool = SqlitePool::connect(database_url).await?;
+pub const DB_PATH: &str = "src/zoo.db";
+
+// שינוי אחד בתוך שלב. כל השינויים בטוחים להרצה חוזרת (IF NOT EXISTS, עמודה רק כשהיא חסרה), כי מסד
+// שלא קיבל גרסה או שנבנה לפני שהטבלאות עברו לשלבים כבר מכיל חלק מהם
+enum Change {
+    Sql(&'static str),
+    // טבלה, עמודה והגדרת העמודה
+    AddColumn(&'static str, &'static str, &'static str),
+}
+
+// שלבי מבנה אחרי מבנה הבסיס (גרסה 1), לפי הסדר. כל שלב רץ פעם אחת ומעלה את user_version;
+// שינוי מבנה חדש נכנס כשלב חדש בסוף הרשימה ולא בתוך create_base_schema
+const MIGRATIONS: &[&[Change]] = &[
+    // 2: מלאי מזון - ספקים, פריטי מזון, מנות מלאי ותוכניות האכלה; ההאכלה רושמת כמה מזון נצרך
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS suppliers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            phone TEXT NOT NULL
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS food_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            unit TEXT NOT NULL,
+            reorder_threshold REAL NOT NULL
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS stock_lots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            food_item_id INTEGER NOT NULL,
+            supplier_id INTEGER,
+            quantity REAL NOT NULL,
+            received_date TEXT NOT NULL,
+            expiry_date TEXT,
+            FOREIGN KEY (food_item_id) REFERENCES food_items(id),
+            FOREIGN KEY (supplier_id) REFERENCES suppliers(id)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS feeding_schedules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            animal_id INTEGER NOT NULL,
+            food_item_id INTEGER NOT NULL,
+            quantity REAL NOT NULL,
+            feedings_per_day INTEGER NOT NULL,
+            FOREIGN KEY (animal_id) REFERENCES animals(id),
+            FOREIGN KEY (food_item_id) REFERENCES food_items(id)
+        )"),
+        Change::AddColumn("feedings", "food_item_id", "INTEGER REFERENCES food_items(id)"),
+        Change::AddColumn("feedings", "quantity", "REAL"),
+    ],
+    // 3: מרווחי האכלה - לפי מין או לפי חיה ספציפית
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS feeding_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            species TEXT,
+            animal_id INTEGER,
+            min_interval_minutes INTEGER NOT NULL,
+            max_interval_hours INTEGER NOT NULL,
+            FOREIGN KEY (animal_id) REFERENCES animals(id)
+        )"),
+    ],
+    // 4: סוג הליך, מעקב ותוצאה לטיפול רפואי, ואבחנות ותרופות לכל טיפול
+    &[
+        Change::AddColumn("medical_treatments", "procedure_type", "TEXT"),
+        Change::AddColumn("medical_treatments", "follow_up_date", "TEXT"),
+        Change::AddColumn("medical_treatments", "outcome", "TEXT"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS treatment_diagnoses (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            treatment_id INTEGER NOT NULL,
+            code TEXT NOT NULL,
+            description TEXT NOT NULL,
+            FOREIGN KEY (treatment_id) REFERENCES medical_treatments(id) ON DELETE CASCADE
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS treatment_medications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            treatment_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            dose REAL NOT NULL,
+            unit TEXT NOT NULL,
+            route TEXT NOT NULL,
+            frequency TEXT NOT NULL,
+            duration_days INTEGER NOT NULL,
+            FOREIGN KEY (treatment_id) REFERENCES medical_treatments(id) ON DELETE CASCADE
+        )"),
+    ],
+    // 5: קטלוג חיסונים, מרווחים בין מנות וחיסונים שניתנו
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS vaccines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            description TEXT NOT NULL
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS vaccine_intervals (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            vaccine_id INTEGER NOT NULL,
+            species TEXT NOT NULL,
+            interval_days INTEGER NOT NULL,
+            UNIQUE (vaccine_id, species),
+            FOREIGN KEY (vaccine_id) REFERENCES vaccines(id)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS vaccinations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            animal_id INTEGER NOT NULL,
+            vaccine_id INTEGER NOT NULL,
+            staff_id INTEGER NOT NULL,
+            treatment_id INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            batch_number TEXT,
+            FOREIGN KEY (animal_id) REFERENCES animals(id),
+            FOREIGN KEY (vaccine_id) REFERENCES vaccines(id),
+            FOREIGN KEY (staff_id) REFERENCES staff(id),
+            FOREIGN KEY (treatment_id) REFERENCES medical_treatments(id)
+        )"),
+    ],
+    // 6: מדידות - משקל, ציון מצב גופני ואורך - וספי ירידה במשקל
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS measurements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            animal_id INTEGER NOT NULL,
+            weight_kg REAL,
+            body_condition_score INTEGER,
+            length_cm REAL,
+            taken_by INTEGER NOT NULL,
+            taken_at TEXT NOT NULL,
+            FOREIGN KEY (animal_id) REFERENCES animals(id),
+            FOREIGN KEY (taken_by) REFERENCES staff(id)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS weight_loss_thresholds (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            species TEXT NOT NULL UNIQUE,
+            max_loss_pct REAL NOT NULL,
+            window_days INTEGER NOT NULL
+        )"),
+    ],
+    // 7: הסגר - מתחמי הסגר והחיות שנמצאות בהם
+    &[
+        Change::AddColumn("enclosures", "is_quarantine", "INTEGER NOT NULL DEFAULT 0"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS quarantines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            animal_id INTEGER NOT NULL,
+            treatment_id INTEGER,
+            reason TEXT NOT NULL,
+            start_date TEXT NOT NULL,
+            required_days INTEGER NOT NULL,
+            end_date TEXT,
+            cleared_by INTEGER,
+            FOREIGN KEY (animal_id) REFERENCES animals(id),
+            FOREIGN KEY (treatment_id) REFERENCES medical_treatments(id),
+            FOREIGN KEY (cleared_by) REFERENCES staff(id)
+        )"),
+    ],
+    // 8: אירועי מחזור חיים - לידה, הגעה, השאלה, מכירה, שחרור, מוות; הסטטוס נגזר מהאירוע האחרון
+    &[
+        Change::AddColumn("animals", "status", "TEXT NOT NULL DEFAULT 'resident'"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS animal_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            animal_id INTEGER NOT NULL,
+            event_type TEXT NOT NULL,
+            event_date TEXT NOT NULL,
+            institution TEXT,
+            cause TEXT,
+            necropsy_treatment_id INTEGER,
+            notes TEXT,
+            FOREIGN KEY (animal_id) REFERENCES animals(id),
+            FOREIGN KEY (necropsy_treatment_id) REFERENCES medical_treatments(id)
+        )"),
+    ],
+    // 9: הורים - חיה בגן (sire_id / dam_id) או הורה חיצוני בטקסט חופשי; NULL = לא ידוע - וזוגות רבייה
+    &[
+        Change::AddColumn("animals", "sire_id", "INTEGER REFERENCES animals(id)"),
+        Change::AddColumn("animals", "dam_id", "INTEGER REFERENCES animals(id)"),
+        Change::AddColumn("animals", "sire_external", "TEXT"),
+        Change::AddColumn("animals", "dam_external", "TEXT"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS breeding_pairs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sire_id INTEGER NOT NULL,
+            dam_id INTEGER NOT NULL,
+            start_date TEXT NOT NULL,
+            end_date TEXT,
+            notes TEXT,
+            FOREIGN KEY (sire_id) REFERENCES animals(id),
+            FOREIGN KEY (dam_id) REFERENCES animals(id)
+        )"),
+    ],
+    // 10: מספר בספר הגידול, ורשומות ספר גידול מגנים שותפים
+    &[
+        Change::AddColumn("animals", "studbook_number", "TEXT"),
+        Change::Sql("CREATE UNIQUE INDEX IF NOT EXISTS idx_animals_studbook_number ON animals(studbook_number)"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS studbook_records (
+            studbook_number TEXT PRIMARY KEY,
+            name TEXT,
+            species TEXT NOT NULL,
+            sex TEXT NOT NULL,
+            birth_date TEXT,
+            sire TEXT,
+            dam TEXT,
+            location_history TEXT,
+            death_date TEXT
+        )"),
+    ],
+    // 11: זיהוי - מין (M / F / U), מספר קליטה, שבב / משדר, סימנים מזהים ומזהים נוספים (טבעת, תג, קעקוע)
+    &[
+        Change::AddColumn("animals", "sex", "TEXT NOT NULL DEFAULT 'U'"),
+        Change::AddColumn("animals", "accession_number", "TEXT"),
+        Change::AddColumn("animals", "microchip_id", "TEXT"),
+        Change::AddColumn("animals", "transponder_id", "TEXT"),
+        Change::AddColumn("animals", "distinguishing_marks", "TEXT"),
+        Change::Sql("CREATE UNIQUE INDEX IF NOT EXISTS idx_animals_accession_number ON animals(accession_number)"),
+        Change::Sql("CREATE UNIQUE INDEX IF NOT EXISTS idx_animals_microchip_id ON animals(microchip_id)"),
+        Change::Sql("CREATE UNIQUE INDEX IF NOT EXISTS idx_animals_transponder_id ON animals(transponder_id)"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS animal_identifiers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            animal_id INTEGER NOT NULL,
+            id_type TEXT NOT NULL,
+            value TEXT NOT NULL,
+            UNIQUE (id_type, value),
+            FOREIGN KEY (animal_id) REFERENCES animals(id)
+        )"),
+    ],
+    // 12: משמרות צוות, והגדרות מערכת - מפתח שלא נשמר מקבל ברירת מחדל בקוד
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS staff_shifts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            staff_id INTEGER NOT NULL,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            enclosure_id INTEGER,
+            area TEXT,
+            required_role TEXT,
+            FOREIGN KEY (staff_id) REFERENCES staff(id),
+            FOREIGN KEY (enclosure_id) REFERENCES enclosures(id)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )"),
+    ],
+    // 13: שיוך שומרים למתחמים - שומר ראשי או משני, עם תאריכי התחלה וסיום
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS enclosure_assignments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            staff_id INTEGER NOT NULL,
+            enclosure_id INTEGER NOT NULL,
+            keeper_role TEXT NOT NULL CHECK (keeper_role IN ('primary', 'secondary')),
+            since TEXT NOT NULL,
+            until TEXT,
+            FOREIGN KEY (staff_id) REFERENCES staff(id),
+            FOREIGN KEY (enclosure_id) REFERENCES enclosures(id)
+        )"),
+    ],
+    // 14: הסמכות צוות (תאריך תפוגה NULL = ללא תפוגה) והסמכה נדרשת לעבודה עם מין / מתחם (activity NULL = כל סוגי העבודה)
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS certifications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            staff_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            issued_date TEXT NOT NULL,
+            expiry_date TEXT,
+            FOREIGN KEY (staff_id) REFERENCES staff(id)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS certification_requirements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            certification TEXT NOT NULL,
+            species TEXT,
+            enclosure_id INTEGER,
+            activity TEXT CHECK (activity IN ('feeding', 'medical_treatment')),
+            FOREIGN KEY (enclosure_id) REFERENCES enclosures(id)
+        )"),
+    ],
+    // 15: משימות לשומרים - ניקיון, העשרה, תיקונים - והערות עליהן
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            task_type TEXT NOT NULL CHECK (task_type IN ('cleaning', 'enrichment', 'repair', 'other')),
+            description TEXT,
+            enclosure_id INTEGER,
+            animal_id INTEGER,
+            assignee_id INTEGER,
+            due_date TEXT,
+            priority TEXT NOT NULL DEFAULT 'medium' CHECK (priority IN ('low', 'medium', 'high', 'urgent')),
+            status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'in_progress', 'done')),
+            created_at TEXT NOT NULL,
+            completed_at TEXT,
+            FOREIGN KEY (enclosure_id) REFERENCES enclosures(id),
+            FOREIGN KEY (animal_id) REFERENCES animals(id),
+            FOREIGN KEY (assignee_id) REFERENCES staff(id)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS task_comments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            staff_id INTEGER NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (task_id) REFERENCES tasks(id),
+            FOREIGN KEY (staff_id) REFERENCES staff(id)
+        )"),
+    ],
+    // 16: בדיקות תקינות למתחמים ופריטי רשימת הבדיקה, ותקלות - תקלה חמורה שלא תוקנה מוציאה את המתחם משימוש
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS enclosure_inspections (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            enclosure_id INTEGER NOT NULL,
+            inspection_date TEXT NOT NULL,
+            inspector_id INTEGER,
+            notes TEXT,
+            FOREIGN KEY (enclosure_id) REFERENCES enclosures(id),
+            FOREIGN KEY (inspector_id) REFERENCES staff(id)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS inspection_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            inspection_id INTEGER NOT NULL,
+            item TEXT NOT NULL,
+            passed INTEGER NOT NULL,
+            notes TEXT,
+            FOREIGN KEY (inspection_id) REFERENCES enclosure_inspections(id)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS enclosure_defects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            enclosure_id INTEGER NOT NULL,
+            inspection_id INTEGER,
+            description TEXT NOT NULL,
+            severity TEXT NOT NULL CHECK (severity IN ('low', 'medium', 'high', 'critical')),
+            status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'in_repair', 'resolved')),
+            reported_date TEXT NOT NULL,
+            resolved_date TEXT,
+            FOREIGN KEY (enclosure_id) REFERENCES enclosures(id),
+            FOREIGN KEY (inspection_id) REFERENCES enclosure_inspections(id)
+        )"),
+    ],
+    // 17: קריאות חיישנים (סדרות זמן בלי rowid, ממוינות לפי מתחם, מדד וזמן), טווח תקין לכל מין וקריאות מחוץ לטווח
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS sensor_readings (
+            enclosure_id INTEGER NOT NULL,
+            metric TEXT NOT NULL,
+            recorded_at TEXT NOT NULL,
+            sensor_id TEXT NOT NULL,
+            value REAL NOT NULL,
+            PRIMARY KEY (enclosure_id, metric, recorded_at, sensor_id),
+            FOREIGN KEY (enclosure_id) REFERENCES enclosures(id)
+        ) WITHOUT ROWID"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS species_ranges (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            species TEXT NOT NULL,
+            metric TEXT NOT NULL,
+            min_value REAL NOT NULL,
+            max_value REAL NOT NULL,
+            UNIQUE (species, metric)
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS sensor_alerts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            enclosure_id INTEGER NOT NULL,
+            sensor_id TEXT NOT NULL,
+            metric TEXT NOT NULL,
+            value REAL NOT NULL,
+            recorded_at TEXT NOT NULL,
+            species TEXT NOT NULL,
+            min_value REAL NOT NULL,
+            max_value REAL NOT NULL,
+            FOREIGN KEY (enclosure_id) REFERENCES enclosures(id)
+        )"),
+    ],
+    // 18: התראות - התראה פתוחה אחת לכל מפתח, נסגרת אוטומטית כשהתנאי חולף - וערוצי משלוח (log / webhook / smtp)
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS alerts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            rule TEXT NOT NULL,
+            alert_key TEXT NOT NULL,
+            severity TEXT NOT NULL CHECK (severity IN ('info', 'warning', 'critical')),
+            message TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'acknowledged', 'resolved')),
+            created_at TEXT NOT NULL,
+            last_seen_at TEXT NOT NULL,
+            acknowledged_at TEXT,
+            acknowledged_by INTEGER,
+            resolved_at TEXT,
+            FOREIGN KEY (acknowledged_by) REFERENCES staff(id)
+        )"),
+        Change::Sql("CREATE UNIQUE INDEX IF NOT EXISTS idx_alerts_active_key ON alerts(alert_key) WHERE status != 'resolved'"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS alert_channels (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL CHECK (kind IN ('log', 'webhook', 'smtp')),
+            target TEXT,
+            min_severity TEXT NOT NULL DEFAULT 'info' CHECK (min_severity IN ('info', 'warning', 'critical')),
+            enabled BOOLEAN NOT NULL DEFAULT 1
+        )"),
+    ],
+    // 19: מנויי webhook (סוגי האירועים מופרדים בפסיקים) ויומן המשלוחים - כל ניסיון חוזר נקבע לפי next_attempt_at
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            url TEXT NOT NULL,
+            event_types TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            enabled BOOLEAN NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL
+        )"),
+        Change::Sql("CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            subscription_id INTEGER NOT NULL,
+            event_type TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+            attempts INTEGER NOT NULL DEFAULT 0,
+            last_status_code INTEGER,
+            last_error TEXT,
+            next_attempt_at TEXT,
+            created_at TEXT NOT NULL,
+            delivered_at TEXT,
+            redelivery_of INTEGER,
+            FOREIGN KEY (subscription_id) REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+            FOREIGN KEY (redelivery_of) REFERENCES webhook_deliveries(id)
+        )"),
+        Change::Sql("CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_pending ON webhook_deliveries(status, next_attempt_at)"),
+    ],
+    // 20: מאילו מנות מלאי כל האכלה לקחה, כדי להחזיר בדיוק אותן בעדכון או מחיקה של ההאכלה
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS feeding_stock (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            feeding_id INTEGER NOT NULL,
+            stock_lot_id INTEGER NOT NULL,
+            quantity REAL NOT NULL,
+            FOREIGN KEY (feeding_id) REFERENCES feedings(id),
+            FOREIGN KEY (stock_lot_id) REFERENCES stock_lots(id)
+        )"),
+        Change::Sql("CREATE INDEX IF NOT EXISTS idx_feeding_stock_feeding ON feeding_stock(feeding_id)"),
+    ],
+    // 21: שומרים שמשויכים לחיה מסוימת, בנוסף לשומרי המתחם שלה
+    &[
+        Change::Sql("CREATE TABLE IF NOT EXISTS animal_assignments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            staff_id INTEGER NOT NULL,
+            animal_id INTEGER NOT NULL,
+            keeper_role TEXT NOT NULL CHECK (keeper_role IN ('primary', 'secondary')),
+            since TEXT NOT NULL,
+            until TEXT,
+            FOREIGN KEY (staff_id) REFERENCES staff(id),
+            FOREIGN KEY (animal_id) REFERENCES animals(id)
+        )"),
+        Change::Sql("CREATE INDEX IF NOT EXISTS idx_animal_assignments_animal ON animal_assignments(animal_id)"),
+    ],
+    // 22: האכלות מלפני שלב 20 נרשמות על המנה ש-consume_stock לוקח ממנה ראשונה - מנה שכבר התקבלה ועוד בתוקף
+    // בזמן ההאכלה, שתוקפה פג ראשונה - כדי שהחזרה למלאי תגיע אליה ולא למנה האחרונה שהתקבלה
+    &[
+        Change::Sql("INSERT INTO feeding_stock (feeding_id, stock_lot_id, quantity)
+        SELECT feeding_id, stock_lot_id, quantity FROM (
+            SELECT f.id AS feeding_id, f.quantity AS quantity,
+                (SELECT l.id FROM stock_lots l
+                WHERE l.food_item_id = f.food_item_id AND l.received_date <= f.feeding_time
+                    AND (l.expiry_date IS NULL OR l.expiry_date > f.feeding_time)
+                ORDER BY l.expiry_date IS NULL, l.expiry_date, l.received_date, l.id LIMIT 1) AS stock_lot_id
+            FROM feedings f
+            WHERE f.food_item_id IS NOT NULL AND f.quantity IS NOT NULL
+                AND NOT EXISTS (SELECT 1 FROM feeding_stock s WHERE s.feeding_id = f.id)
+        )
+        WHERE stock_lot_id IS NOT NULL"),
+    ],
+    // 23: השומר האחראי על התראה, וערוצים שמיועדים לעובד מסוים ומקבלים רק את ההתראות שלו
+    &[
+        Change::AddColumn("alerts", "assignee_id", "INTEGER REFERENCES staff(id)"),
+        Change::AddColumn("alert_channels", "staff_id", "INTEGER REFERENCES staff(id)"),
+    ],
+];
+
+/// The version a fully migrated database has: the base schema plus every step in `MIGRATIONS`.
+pub const SCHEMA_VERSION: i64 = 1 + MIGRATIONS.len() as i64;
+
+// מסדים ממוספרים מלפני ששלבים 2-19 יצאו ממבנה הבסיס: גרסה 1 שלהם כבר כללה את כל הטבלאות עד webhook_deliveries,
+// והשלבים 2-5 שלהם הם שלבים 20-23 כאן. מזהים אותם לפי טבלה ששלב 19 יוצר, שבמספור הנוכחי לא קיימת לפני גרסה 19
+const LEGACY_LAST_VERSION: i64 = 5;
+const LEGACY_VERSION_OFFSET: i64 = 18;
+const LEGACY_MARKER_TABLE: &str = "webhook_subscriptions";
+
+/// Takes the lock the server holds while it runs; the file stays locked until the returned handle is dropped.
+pub fn lock_database() -> Result<File, String> {
+    let path = format!("{}.lock", DB_PATH);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Cannot open {}: {}", path, e))?;
+    // נעילה של מערכת ההפעלה, כך שקובץ שנשאר אחרי קריסה לא חוסם
+    match file.try_lock() {
+        Ok(()) => Ok(file),
+        Err(TryLockError::WouldBlock) => Err(format!("{} is in use by a running server", DB_PATH)),
+        Err(TryLockError::Error(e)) => Err(format!("Cannot lock {}: {}", path, e)),
+    }
+}
+
+/// Opens the database without creating or migrating tables.
+pub async fn connect() -> Result<SqlitePool, sqlx::Error> {
+    // המסד לא נשמר במאגר; בהרצה הראשונה הוא נוצר ריק ו-migrate בונה אותו
+    let database_url = format!("sqlite:{}?mode=rwc", DB_PATH);
+    let pool = SqlitePool::connect(&database_url).await?;
 
     // הפעלת תמיכה במפתחות זרים
     sqlx::query("PRAGMA foreign_keys = ON;")
         .execute(&pool)
         .await?;
+    Ok(pool)
+}
+
+/// The schema version of the last migration step that ran (0 for a database that was never migrated).
+pub async fn schema_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    Ok(sqlx::query("PRAGMA user_version").fetch_one(pool).await?.get(0))
+}
+
+pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
+    let pool = connect().await?;
+    migrate(&pool).await?;
+    Ok(pool)
+}
+
+/// Brings any pool up to `SCHEMA_VERSION`, running only the steps its `user_version` has not seen yet.
+pub async fn migrate(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let mut version = schema_version(pool).await?;
+    if (1..=LEGACY_LAST_VERSION).contains(&version) && has_table(pool, LEGACY_MARKER_TABLE).await? {
+        version += LEGACY_VERSION_OFFSET;
+        set_schema_version(pool, version).await?;
+    }
+    if version > SCHEMA_VERSION {
+        return Err(sqlx::Error::Configuration(
+            format!("Database schema version {} is newer than this app ({})", version, SCHEMA_VERSION).into(),
+        ));
+    }
+    if version < 1 {
+        create_base_schema(pool).await?;
+        set_schema_version(pool, 1).await?;
+    }
+
+    for (index, changes) in MIGRATIONS.iter().enumerate() {
+        let step_version = index as i64 + 2;
+        if version >= step_version {
+            continue;
+        }
+        // השלב והגרסה החדשה נשמרים יחד, כך ששלב שנכשל באמצע ירוץ שוב בפעם הבאה
+        let mut tx = pool.begin().await?;
+        for change in *changes {
+            match change {
+                Change::Sql(statement) => {
+                    sqlx::query(statement).execute(&mut *tx).await?;
+                }
+                Change::AddColumn(table, column, definition) => {
+                    add_column_if_missing(&mut tx, table, column, definition).await?;
+                }
+            }
+        }
+        sqlx::query(&format!("PRAGMA user_version = {}", step_version))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        println!("✅ Schema migrated to version {}", step_version);
+    }
+    Ok(())
+}
+
+async fn has_table(pool: &SqlitePool, table: &str) -> Result<bool, sqlx::Error> {
+    Ok(sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?")
+        .bind(table)
+        .fetch_optional(pool)
+        .await?
+        .is_some())
+}
 
+async fn set_schema_version(pool: &SqlitePool, version: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!("PRAGMA user_version = {}", version))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Rows of other tables that still point at row `id` of `table`, as labels like "3 feedings"; empty when none do.
+pub async fn references_to(conn: &mut SqliteConnection, table: &str, id: i64) -> Result<Vec<String>, sqlx::Error> {
+    // המפתחות הזרים נקראים מהמבנה עצמו, כך שטבלה חדשה נבדקת בלי לעדכן רשימה
+    let foreign_keys = sqlx::query(
+        "SELECT m.name AS child_table, f.\"from\" AS child_column
+        FROM sqlite_master m JOIN pragma_foreign_key_list(m.name) f
+        WHERE m.type = 'table' AND f.\"table\" = ?
+        ORDER BY m.name, f.\"from\"",
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut references = Vec::new();
+    for foreign_key in foreign_keys {
+        let child_table: String = foreign_key.get("child_table");
+        let child_column: String = foreign_key.get("child_column");
+        let count: i64 = sqlx::query(&format!("SELECT COUNT(*) AS count FROM {} WHERE {} = ?", child_table, child_column))
+            .bind(id)
+            .fetch_one(&mut *conn)
+            .await?
+            .get("count");
+        if count > 0 {
+            references.push(format!("{} {}", count, child_table.replace('_', " ")));
+        }
+    }
+    Ok(references)
+}
+
+// מבנה הבסיס (גרסה 1) - חמש הטבלאות שהיו לפני שהמבנה קיבל גרסאות; כל השאר נבנה בשלבים שב-MIGRATIONS
+async fn create_base_schema(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     // 🦁 טבלת מתחמים (Enclosures)
     println!("📋 Creating enclosures table if not exists...");
     sqlx::query(
@@ -21,7 +614,7 @@ pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
         )
         "#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
     println!("✅ Enclosures table ready");
 
@@ -38,7 +631,7 @@ pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
         )
         "#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
     println!("✅ Staff table ready");
 
@@ -56,7 +649,7 @@ pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
         )
         "#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
     println!("✅ Animals table ready");
 
@@ -74,7 +667,7 @@ pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
         )
         "#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
     println!("✅ Feedings table ready");
 
@@ -93,10 +686,31 @@ pub async fn init_db() -> Result<SqlitePool, sqlx::Error> {
         )
         "#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
     println!("✅ Medical_Treatments table ready");
 
-    println!("🎉 All zoo tables initialized successfully!");
-    Ok(pool)
-}
\ No newline at end of file
+    println!("🎉 Base zoo tables initialized successfully!");
+    Ok(())
+}
+
+// מוסיף עמודה לטבלה קיימת אם היא עדיין לא קיימת (ל-ALTER TABLE ADD COLUMN אין IF NOT EXISTS)
+async fn add_column_if_missing(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let columns = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(&mut *conn)
+        .await?;
+
+    if columns.iter().any(|row| row.get::<String, _>("name") == column) {
+        return Ok(());
+    }
+
+    sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
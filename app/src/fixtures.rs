@@ -0,0 +1,437 @@
+// נתוני דמו: אותו seed, אותו preset ואותו תאריך סיום נותנים תמיד את אותו מסד
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use serde::Serialize;
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use crate::controllers::animal_controller::{insert_animal, CreateAnimalError};
+use crate::controllers::enclosure_controller::insert_enclosure;
+use crate::controllers::medical_treatment_controller::{insert_treatment, CreateTreatmentError};
+use crate::controllers::staff_controller::insert_staff;
+use crate::models::animal::CreateAnimalRequest;
+use crate::models::enclosure::CreateEnclosureRequest;
+use crate::models::medical_treatment::CreateMedicalTreatmentRequest;
+use crate::models::staff::CreateStaffRequest;
+use crate::rng::Rng;
+
+pub const PRESETS: [&str; 3] = ["small", "medium", "large"];
+const VET_ROLE: &str = "Veterinarian";
+const KEEPER_ROLE: &str = "Zookeeper";
+const OTHER_ROLES: [&str; 3] = ["Animal Trainer", "Cleaner", "Manager"];
+// בממוצע טיפול אחד לחיה בחודשיים
+const TREATMENT_CHANCE_PER_DAY: f64 = 1.0 / 60.0;
+// שלוש שנים של האכלות יומיות; large עם 2000 חיות כבר מגיע למיליוני שורות
+pub const MAX_DAYS: i64 = 3 * 366;
+const DEFAULT_UNTIL: NaiveDate = NaiveDate::from_ymd_opt(2025, 12, 31).expect("valid date");
+
+struct Species {
+    name: &'static str,
+    max_age: i64,
+    temp: (f64, f64),
+    humidity: (f64, f64),
+}
+
+struct Habitat {
+    name: &'static str,
+    enclosure_type: &'static str,
+    species: &'static [Species],
+}
+
+const HABITATS: [Habitat; 6] = [
+    Habitat {
+        name: "Savannah",
+        enclosure_type: "Outdoor",
+        species: &[
+            Species { name: "Lion", max_age: 20, temp: (15.0, 35.0), humidity: (20.0, 60.0) },
+            Species { name: "Zebra", max_age: 25, temp: (15.0, 35.0), humidity: (20.0, 60.0) },
+            Species { name: "Giraffe", max_age: 25, temp: (15.0, 35.0), humidity: (20.0, 60.0) },
+        ],
+    },
+    Habitat {
+        name: "Big Cat Ridge",
+        enclosure_type: "Outdoor",
+        species: &[
+            Species { name: "Tiger", max_age: 20, temp: (5.0, 30.0), humidity: (40.0, 80.0) },
+            Species { name: "Leopard", max_age: 20, temp: (10.0, 32.0), humidity: (40.0, 80.0) },
+        ],
+    },
+    Habitat {
+        name: "Primate Forest",
+        enclosure_type: "Outdoor",
+        species: &[
+            Species { name: "Monkey", max_age: 30, temp: (20.0, 32.0), humidity: (60.0, 90.0) },
+            Species { name: "Lemur", max_age: 25, temp: (20.0, 30.0), humidity: (60.0, 90.0) },
+        ],
+    },
+    Habitat {
+        name: "Reptile House",
+        enclosure_type: "Indoor",
+        species: &[
+            Species { name: "Iguana", max_age: 20, temp: (26.0, 35.0), humidity: (60.0, 80.0) },
+            Species { name: "Tortoise", max_age: 80, temp: (24.0, 32.0), humidity: (50.0, 70.0) },
+            Species { name: "Snake", max_age: 25, temp: (25.0, 32.0), humidity: (50.0, 70.0) },
+        ],
+    },
+    Habitat {
+        name: "Aviary",
+        enclosure_type: "Outdoor",
+        species: &[
+            Species { name: "Parrot", max_age: 50, temp: (18.0, 30.0), humidity: (50.0, 80.0) },
+            Species { name: "Flamingo", max_age: 40, temp: (15.0, 32.0), humidity: (40.0, 70.0) },
+        ],
+    },
+    Habitat {
+        name: "Aquarium",
+        enclosure_type: "Indoor",
+        species: &[Species { name: "Clownfish", max_age: 10, temp: (24.0, 28.0), humidity: (70.0, 100.0) }],
+    },
+];
+
+const ANIMAL_NAMES: [&str; 24] = [
+    "Leo", "Nala", "Simba", "Kiara", "Zuri", "Tamu", "Raja", "Shira", "Bamba", "Luna", "Kofi", "Ayla",
+    "Mango", "Pita", "Tiki", "Goldie", "Shelly", "Rocky", "Coco", "Nemo", "Sasha", "Bongo", "Dana", "Yoyo",
+];
+const FIRST_NAMES: [&str; 16] = [
+    "Sarah", "Daniel", "Maya", "Tom", "Noa", "Amit", "Yael", "Omer", "Tamar", "Itai", "Shira", "Eyal", "Lior", "Roni", "Gal", "Adi",
+];
+const LAST_NAMES: [&str; 12] = ["Green", "Cohen", "Levi", "Adler", "Mizrahi", "Peretz", "Biton", "Friedman", "Azulay", "Katz", "Shapiro", "Golan"];
+
+// תיאור, סוג פעולה, תוצאה, ימים עד מעקב
+const PROCEDURES: [(&str, &str, Option<&str>, Option<i64>); 6] = [
+    ("Annual check-up", "examination", Some("healthy"), None),
+    ("Vaccination", "vaccination", Some("healthy"), None),
+    ("Dental check", "examination", Some("healthy"), None),
+    ("Wound cleaning and dressing", "treatment", Some("recovering"), Some(7)),
+    ("Skin infection treatment", "treatment", Some("recovering"), Some(14)),
+    ("Limping, X-ray taken", "diagnostic", None, Some(3)),
+];
+
+/// What to generate; `preset` gives the sizes and the fields can be overridden before generating.
+#[derive(Debug, Clone)]
+pub struct FixtureOptions {
+    pub seed: u64,
+    pub enclosures: usize,
+    pub staff: usize,
+    pub animals: usize,
+    pub days: i64,
+    // היום האחרון עם האכלות; קבוע כדי שהתוצאה לא תלויה בשעה שבה מריצים
+    pub until: NaiveDate,
+}
+
+impl FixtureOptions {
+    /// `small` for development, `medium` for demos, `large` for load testing.
+    pub fn preset(name: &str) -> Option<Self> {
+        let (enclosures, staff, animals, days) = match name {
+            "small" => (4, 8, 24, 14),
+            "medium" => (12, 30, 200, 60),
+            "large" => (40, 150, 2000, 90),
+            _ => return None,
+        };
+        Some(FixtureOptions {
+            seed: 42,
+            enclosures,
+            staff,
+            animals,
+            days,
+            until: DEFAULT_UNTIL,
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enclosures == 0 || self.staff < 2 || self.days < 1 {
+            return Err("Fixtures need at least 1 enclosure, 2 staff members and 1 day".to_string());
+        }
+        if self.days > MAX_DAYS {
+            return Err(format!("Fixtures can cover at most {} days", MAX_DAYS));
+        }
+        // גם מועד המעקב של הטיפול האחרון צריך להיות תאריך חוקי
+        let last_follow_up = PROCEDURES.iter().filter_map(|procedure| procedure.3).max().unwrap_or(0);
+        if self.first_day().is_none() || days_after(self.until, last_follow_up).is_none() {
+            return Err(format!("{} is out of range for {} days of fixtures", self.until, self.days));
+        }
+        Ok(())
+    }
+
+    fn first_day(&self) -> Option<NaiveDate> {
+        self.until.checked_sub_signed(TimeDelta::try_days(self.days - 1)?)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct FixtureSummary {
+    pub enclosures: usize,
+    pub species_ranges: usize,
+    pub staff: usize,
+    pub keeper_assignments: usize,
+    pub shifts: usize,
+    pub animals: usize,
+    pub feedings: usize,
+    pub medical_treatments: usize,
+}
+
+fn days_after(day: NaiveDate, days: i64) -> Option<NaiveDate> {
+    day.checked_add_signed(TimeDelta::try_days(days)?)
+}
+
+fn at(day: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+    day.and_time(NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or_default())
+}
+
+fn db_error(e: sqlx::Error) -> String {
+    format!("Database error: {}", e)
+}
+
+struct FixtureStaff {
+    id: i64,
+    role: &'static str,
+}
+
+async fn insert_staff_members(tx: &mut Transaction<'_, Sqlite>, rng: &mut Rng, count: usize) -> Result<Vec<FixtureStaff>, String> {
+    let vets = (count / 6).max(1);
+    let keepers = (count / 2).max(1);
+    let mut staff = Vec::new();
+    for index in 0..count {
+        let role = if index < vets {
+            VET_ROLE
+        } else if index < vets + keepers {
+            KEEPER_ROLE
+        } else {
+            OTHER_ROLES[index % OTHER_ROLES.len()]
+        };
+        let request = CreateStaffRequest {
+            first_name: rng.pick(&FIRST_NAMES).to_string(),
+            last_name: rng.pick(&LAST_NAMES).to_string(),
+            role: role.to_string(),
+            phone: format!("05{}-{:07}", rng.between(0, 8), rng.between(0, 9_999_999)),
+        };
+        let created = insert_staff(tx, &request).await.map_err(db_error)?;
+        staff.push(FixtureStaff { id: created.id, role });
+    }
+    Ok(staff)
+}
+
+/// Fills an empty, migrated database with realistic data. Staff, enclosures, animals and treatments go
+/// through the same insert helpers as the API and its checks. Feedings, shifts, keeper assignments and
+/// species ranges are written directly, for speed, but laid out to satisfy those rules: animals only in
+/// enclosures with room, keepers assigned and on shift at every feeding. Works on any pool, e.g. an
+/// in-memory database in a test.
+pub async fn generate_fixtures(pool: &SqlitePool, options: &FixtureOptions) -> Result<FixtureSummary, String> {
+    options.validate()?;
+    let existing: i64 = sqlx::query_scalar("SELECT (SELECT COUNT(*) FROM enclosures) + (SELECT COUNT(*) FROM staff) + (SELECT COUNT(*) FROM animals)")
+        .fetch_one(pool)
+        .await
+        .map_err(db_error)?;
+    if existing > 0 {
+        return Err("Fixtures can only be generated into an empty database".to_string());
+    }
+
+    let mut rng = Rng::new(options.seed);
+    let mut summary = FixtureSummary::default();
+    let first_day = options.first_day().ok_or("until is out of range")?;
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let staff = insert_staff_members(&mut tx, &mut rng, options.staff).await?;
+    summary.staff = staff.len();
+    let vets: Vec<i64> = staff.iter().filter(|s| s.role == VET_ROLE).map(|s| s.id).collect();
+    let keepers: Vec<i64> = staff.iter().filter(|s| s.role == KEEPER_ROLE).map(|s| s.id).collect();
+
+    // מתחם -> (מזהה, שומר ראשי, בית גידול); החיות מתחלקות בין המתחמים כמעט שווה בשווה
+    let mut enclosures = Vec::new();
+    let mut used_species = Vec::new();
+    for index in 0..options.enclosures {
+        let habitat = &HABITATS[index % HABITATS.len()];
+        let residents = options.animals / options.enclosures + usize::from(index < options.animals % options.enclosures);
+        let round = index / HABITATS.len() + 1;
+        let request = CreateEnclosureRequest {
+            name: if round == 1 { habitat.name.to_string() } else { format!("{} {}", habitat.name, round) },
+            enclosure_type: habitat.enclosure_type.to_string(),
+            capacity: (residents as i64 + rng.between(2, 6)) as i32,
+            is_quarantine: false,
+        };
+        let enclosure = insert_enclosure(&mut tx, &request).await.map_err(db_error)?;
+        summary.enclosures += 1;
+
+        let primary = keepers[index % keepers.len()];
+        let secondary = keepers[(index + 1) % keepers.len()];
+        for (keeper, keeper_role) in [(primary, "primary"), (secondary, "secondary")] {
+            if keeper_role == "secondary" && secondary == primary {
+                continue;
+            }
+            sqlx::query("INSERT INTO enclosure_assignments (staff_id, enclosure_id, keeper_role, since, until) VALUES (?, ?, ?, ?, NULL)")
+                .bind(keeper)
+                .bind(enclosure.id)
+                .bind(keeper_role)
+                .bind(first_day)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+            summary.keeper_assignments += 1;
+        }
+        enclosures.push((enclosure.id, primary, habitat, residents));
+    }
+
+    // (מזהה חיה, השומר שמאכיל אותה)
+    let mut animals = Vec::new();
+    for (enclosure_id, keeper, habitat, residents) in &enclosures {
+        for _ in 0..*residents {
+            let species = rng.pick(habitat.species);
+            if !used_species.contains(&species.name) {
+                used_species.push(species.name);
+            }
+            let sequence = animals.len() + 1;
+            let request = CreateAnimalRequest {
+                name: rng.pick(&ANIMAL_NAMES).to_string(),
+                age: rng.between(1, species.max_age / 2) as i32,
+                enclosure_id: *enclosure_id,
+                species: species.name.to_string(),
+                sire_id: None,
+                dam_id: None,
+                sire_external: None,
+                dam_external: None,
+                studbook_number: None,
+                sex: Some(rng.pick(&["M", "F"]).to_string()),
+                accession_number: Some(format!("ZOO-{}-{:05}", first_day.format("%Y"), sequence)),
+                microchip_id: Some(format!("985{:012}", sequence)),
+                transponder_id: None,
+                distinguishing_marks: None,
+            };
+            let animal = match insert_animal(&mut tx, &request).await {
+                Ok(animal) => animal,
+                Err(CreateAnimalError::Invalid(message)) | Err(CreateAnimalError::Conflict(message)) => return Err(message.to_string()),
+                Err(CreateAnimalError::Database(e)) => return Err(db_error(e)),
+            };
+            animals.push((animal.id, *keeper));
+        }
+    }
+    summary.animals = animals.len();
+
+    for habitat in &HABITATS {
+        for species in habitat.species.iter().filter(|s| used_species.contains(&s.name)) {
+            for (metric, (min_value, max_value)) in [("temp", species.temp), ("humidity", species.humidity)] {
+                sqlx::query("INSERT INTO species_ranges (species, metric, min_value, max_value) VALUES (?, ?, ?, ?)")
+                    .bind(species.name)
+                    .bind(metric)
+                    .bind(min_value)
+                    .bind(max_value)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_error)?;
+                summary.species_ranges += 1;
+            }
+        }
+    }
+
+    for offset in 0..options.days {
+        let day = days_after(first_day, offset).ok_or("until is out of range")?;
+
+        // כולם במשמרת כל יום, כך שכל האכלה וכל טיפול נופלים בתוך משמרת
+        for member in &staff {
+            let (start, end) = match member.role {
+                KEEPER_ROLE => (6, 14),
+                VET_ROLE => (8, 16),
+                _ => (9, 17),
+            };
+            sqlx::query("INSERT INTO staff_shifts (staff_id, start_time, end_time, enclosure_id, area, required_role) VALUES (?, ?, ?, NULL, NULL, ?)")
+                .bind(member.id)
+                .bind(at(day, start, 0))
+                .bind(at(day, end, 0))
+                .bind(member.role)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+            summary.shifts += 1;
+        }
+
+        for (animal_id, keeper) in &animals {
+            // שתי האכלות ביום, רחוקות זו מזו הרבה יותר מהמרווח המינימלי
+            for hour in [7, 12] {
+                sqlx::query("INSERT INTO feedings (animal_id, staff_id, feeding_time) VALUES (?, ?, ?)")
+                    .bind(animal_id)
+                    .bind(keeper)
+                    .bind(at(day, hour, rng.between(0, 59) as u32))
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_error)?;
+                summary.feedings += 1;
+            }
+
+            if !rng.chance(TREATMENT_CHANCE_PER_DAY) {
+                continue;
+            }
+            let (description, procedure_type, outcome, follow_up_days) = *rng.pick(&PROCEDURES);
+            let request = CreateMedicalTreatmentRequest {
+                animal_id: *animal_id,
+                staff_id: *rng.pick(&vets),
+                date: at(day, rng.between(9, 14) as u32, rng.between(0, 59) as u32),
+                description: description.to_string(),
+                procedure_type: Some(procedure_type.to_string()),
+                follow_up_date: follow_up_days.and_then(|days| days_after(day, days)).map(|follow_up| at(follow_up, 10, 0)),
+                outcome: outcome.map(str::to_string),
+                diagnoses: Vec::new(),
+                medications: Vec::new(),
+            };
+            match insert_treatment(&mut tx, &request).await {
+                Ok(_) => {}
+                Err(CreateTreatmentError::Invalid(message)) | Err(CreateTreatmentError::NotFound(message)) => return Err(message.to_string()),
+                Err(CreateTreatmentError::Forbidden(message)) | Err(CreateTreatmentError::Conflict(message)) => return Err(message),
+                Err(CreateTreatmentError::Database(e)) => return Err(db_error(e)),
+            }
+            summary.medical_treatments += 1;
+        }
+    }
+
+    tx.commit().await.map_err(db_error)?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+    use super::*;
+    use crate::db::migrate;
+
+    const TABLES: [&str; 8] = [
+        "staff", "enclosures", "enclosure_assignments", "animals", "species_ranges", "staff_shifts", "feedings", "medical_treatments",
+    ];
+
+    async fn generate(options: &FixtureOptions) -> (FixtureSummary, Vec<String>) {
+        // מסד בזיכרון חי רק בתוך החיבור שלו, לכן חיבור אחד
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        migrate(&pool).await.unwrap();
+        let summary = generate_fixtures(&pool, options).await.unwrap();
+
+        let mut rows = Vec::new();
+        for table in TABLES {
+            let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?) ORDER BY cid")
+                .bind(table)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            let sql = format!("SELECT json_array({}) FROM {} ORDER BY rowid", columns.join(", "), table);
+            let table_rows: Vec<String> = sqlx::query_scalar(&sql).fetch_all(&pool).await.unwrap();
+            rows.extend(table_rows.into_iter().map(|row| format!("{} {}", table, row)));
+        }
+        (summary, rows)
+    }
+
+    #[tokio::test]
+    async fn same_seed_generates_the_same_database() {
+        let options = FixtureOptions::preset("small").unwrap();
+        let (first_summary, first_rows) = generate(&options).await;
+        let (second_summary, second_rows) = generate(&options).await;
+
+        assert!(first_summary.feedings > 0);
+        assert_eq!(first_summary, second_summary);
+        assert_eq!(first_rows, second_rows);
+    }
+
+    #[test]
+    fn days_and_until_are_bounded() {
+        let mut options = FixtureOptions::preset("small").unwrap();
+        options.days = MAX_DAYS + 1;
+        assert!(options.validate().is_err());
+        options.days = 30;
+        options.until = NaiveDate::MIN;
+        assert!(options.validate().is_err());
+        options.until = NaiveDate::MAX;
+        assert!(options.validate().is_err());
+    }
+}
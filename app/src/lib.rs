@@ -0,0 +1,9 @@
+// הקוד המשותף לשרת (main.rs) ולכלי הניהול (bin/zooctl.rs)
+pub mod cli;
+pub mod db;
+pub mod models;
+pub mod controllers;
+pub mod routes;
+pub mod notify;
+pub mod fixtures;
+pub mod rng;
@@ -1,19 +1,44 @@
 use actix_web::{web, App, HttpServer};
-use crate::{db::init_db, routes::{animals::animal_routes, enclosures::enclosure_routes, feedings::feeding_routes, medical_treatments::medical_treatment_routes, staff::staff_routes}};
-
-mod db;
-mod models;
-mod controllers;
-mod routes;
+use app::{cli, controllers::{alert_controller::run_alert_engine, backup_controller::run_backup_scheduler, webhook_controller::run_webhook_dispatcher}, db::{init_db, lock_database}, routes::{alerts::alert_routes, animals::animal_routes, backups::backup_routes, breeding::breeding_routes, enclosures::enclosure_routes, events::event_routes, exports::export_routes, feedings::feeding_routes, imports::import_routes, inventory::inventory_routes, medical::medical_routes, measurements::measurement_routes, medical_treatments::medical_treatment_routes, quarantine::quarantine_routes, readings::reading_routes, reports::report_routes, settings::settings_routes, staff::staff_routes, studbook::studbook_routes, tasks::task_routes, webhooks::webhook_routes}};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // השרת מחזיק את הנעילה כל זמן שהוא רץ, כדי ששחזור לא יחליף את הקובץ מתחתיו
+    let _lock = if args.is_empty() {
+        match lock_database() {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
     // Initialize database
     let pool = init_db().await.expect("Failed to initialize database");
-    
+
+    // עם ארגומנטים מריצים פקודה אחת במקום את השרת
+    if !args.is_empty() {
+        if let Err(e) = cli::run(&pool, &args).await {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     println!("🚀 Server running at http://127.0.0.1:8080");
     println!("📊 SQLite database initialized at src/zoo.db");
 
+    // מנוע ההתראות רץ ברקע לצד השרת
+    tokio::spawn(run_alert_engine(pool.clone()));
+    // משלוחי webhook שנכשלו נשלחים שוב ברקע
+    tokio::spawn(run_webhook_dispatcher(pool.clone()));
+    // גיבויים מתוזמנים לפי ההגדרות
+    tokio::spawn(run_backup_scheduler(pool.clone()));
+
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(pool.clone()))
@@ -22,6 +47,22 @@ async fn main() -> std::io::Result<()> {
             .service(feeding_routes())
             .service(medical_treatment_routes())
             .service(staff_routes())
+            .service(inventory_routes())
+            .service(medical_routes())
+            .service(measurement_routes())
+            .service(quarantine_routes())
+            .service(breeding_routes())
+            .service(studbook_routes())
+            .service(settings_routes())
+            .service(report_routes())
+            .service(task_routes())
+            .service(reading_routes())
+            .service(alert_routes())
+            .service(webhook_routes())
+            .service(event_routes())
+            .service(import_routes())
+            .service(export_routes())
+            .service(backup_routes())
     })
     .bind(("127.0.0.1", 8080))?
     .run()
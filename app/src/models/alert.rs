@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Alert {
+    pub id: i64,
+    pub rule: String,
+    pub alert_key: String,
+    pub severity: String,
+    pub message: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+    pub acknowledged_at: Option<NaiveDateTime>,
+    pub acknowledged_by: Option<i64>,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub assignee_id: Option<i64>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlertListQuery {
+    pub status: Option<String>,
+    pub severity: Option<String>,
+    pub rule: Option<String>,
+    pub assignee_id: Option<i64>
+}
+
+#[derive(Debug, Serialize)]
+pub struct AlertRunSummary {
+    pub raised: usize,
+    pub still_active: usize,
+    pub auto_resolved: u64
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AlertChannel {
+    pub id: i64,
+    pub kind: String,
+    pub target: Option<String>,
+    pub min_severity: String,
+    pub enabled: bool,
+    pub staff_id: Option<i64>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertChannelRequest {
+    pub kind: String,
+    pub target: Option<String>,
+    pub min_severity: Option<String>,
+    pub staff_id: Option<i64>
+}
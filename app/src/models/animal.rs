@@ -1,12 +1,23 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Animal {
     pub id: i64,
     pub name: String,
     pub age: i32,
     pub enclosure_id: i64,
     pub species: String,
+    pub status: String,
+    pub sire_id: Option<i64>,
+    pub dam_id: Option<i64>,
+    pub sire_external: Option<String>,
+    pub dam_external: Option<String>,
+    pub studbook_number: Option<String>,
+    pub sex: String,
+    pub accession_number: Option<String>,
+    pub microchip_id: Option<String>,
+    pub transponder_id: Option<String>,
+    pub distinguishing_marks: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -15,6 +26,16 @@ pub struct CreateAnimalRequest {
     pub age: i32,
     pub enclosure_id: i64,
     pub species: String,
+    pub sire_id: Option<i64>,
+    pub dam_id: Option<i64>,
+    pub sire_external: Option<String>,
+    pub dam_external: Option<String>,
+    pub studbook_number: Option<String>,
+    pub sex: Option<String>,
+    pub accession_number: Option<String>,
+    pub microchip_id: Option<String>,
+    pub transponder_id: Option<String>,
+    pub distinguishing_marks: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,4 +44,40 @@ pub struct UpdateAnimalRequest {
     pub age: i32,
     pub enclosure_id: i64,
     pub species: String,
+    pub sire_id: Option<i64>,
+    pub dam_id: Option<i64>,
+    pub sire_external: Option<String>,
+    pub dam_external: Option<String>,
+    pub studbook_number: Option<String>,
+    pub sex: Option<String>,
+    pub accession_number: Option<String>,
+    pub microchip_id: Option<String>,
+    pub transponder_id: Option<String>,
+    pub distinguishing_marks: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnimalListQuery {
+    pub status: Option<String>,
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnimalLookupQuery {
+    pub chip: String,
+}
+
+// טבעת / תג / קעקוע - לחיה יכולים להיות כמה
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AnimalIdentifier {
+    pub id: i64,
+    pub animal_id: i64,
+    pub id_type: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAnimalIdentifierRequest {
+    pub id_type: String,
+    pub value: String,
 }
\ No newline at end of file
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AnimalEvent {
+    pub id: i64,
+    pub animal_id: i64,
+    pub event_type: String,
+    pub event_date: NaiveDateTime,
+    pub institution: Option<String>,
+    pub cause: Option<String>,
+    pub necropsy_treatment_id: Option<i64>,
+    pub notes: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAnimalEventRequest {
+    pub event_type: String,
+    pub event_date: NaiveDateTime,
+    pub institution: Option<String>,
+    pub cause: Option<String>,
+    pub necropsy_treatment_id: Option<i64>,
+    pub notes: Option<String>
+}
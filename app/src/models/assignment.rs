@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EnclosureAssignment {
+    pub id: i64,
+    pub staff_id: i64,
+    pub enclosure_id: i64,
+    pub keeper_role: String,
+    pub since: NaiveDate,
+    pub until: Option<NaiveDate>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AnimalAssignment {
+    pub id: i64,
+    pub staff_id: i64,
+    pub animal_id: i64,
+    pub keeper_role: String,
+    pub since: NaiveDate,
+    pub until: Option<NaiveDate>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateKeeperAssignmentRequest {
+    pub staff_id: i64,
+    pub keeper_role: String,
+    pub since: NaiveDate,
+    pub until: Option<NaiveDate>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EndAssignmentRequest {
+    pub until: Option<NaiveDate>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignmentListQuery {
+    pub all: Option<bool>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeeperQuery {
+    pub keeper_id: Option<i64>
+}
@@ -0,0 +1,18 @@
+use serde::Serialize;
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Default)]
+pub struct BackupInfo {
+    pub file: String,
+    pub size_bytes: u64,
+    pub created_at: NaiveDateTime
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupCheck {
+    pub file: String,
+    pub ok: bool,
+    pub integrity: Vec<String>,
+    pub schema_version: i64,
+    pub expected_schema_version: i64
+}
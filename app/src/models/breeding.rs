@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BreedingPair {
+    pub id: i64,
+    pub sire_id: i64,
+    pub dam_id: i64,
+    pub start_date: NaiveDateTime,
+    pub end_date: Option<NaiveDateTime>,
+    pub notes: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBreedingPairRequest {
+    pub sire_id: i64,
+    pub dam_id: i64,
+    pub start_date: NaiveDateTime,
+    pub end_date: Option<NaiveDateTime>,
+    pub notes: Option<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct PedigreeNode {
+    pub id: Option<i64>,
+    pub name: String,
+    pub species: Option<String>,
+    pub sire: Option<Box<PedigreeNode>>,
+    pub dam: Option<Box<PedigreeNode>>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PedigreeQuery {
+    pub generations: Option<u32>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PairingQuery {
+    pub sire_id: i64,
+    pub dam_id: i64
+}
+
+#[derive(Debug, Serialize)]
+pub struct PairingEvaluation {
+    pub sire_id: i64,
+    pub dam_id: i64,
+    pub sire_inbreeding: f64,
+    pub dam_inbreeding: f64,
+    pub offspring_inbreeding: f64
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Certification {
+    pub id: i64,
+    pub staff_id: i64,
+    pub name: String,
+    pub issued_date: NaiveDate,
+    pub expiry_date: Option<NaiveDate>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCertificationRequest {
+    pub name: String,
+    pub issued_date: NaiveDate,
+    pub expiry_date: Option<NaiveDate>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CertificationRequirement {
+    pub id: i64,
+    pub certification: String,
+    pub species: Option<String>,
+    pub enclosure_id: Option<i64>,
+    pub activity: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCertificationRequirementRequest {
+    pub certification: String,
+    pub species: Option<String>,
+    pub enclosure_id: Option<i64>,
+    pub activity: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpiringQuery {
+    pub within: Option<String>
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ExpiringCertification {
+    #[serde(flatten)]
+    pub certification: Certification,
+    pub staff_name: String,
+    pub expired: bool
+}
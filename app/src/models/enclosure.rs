@@ -1,11 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Enclosure {
     pub id: i64,
     pub name: String,
     pub enclosure_type: String,
     pub capacity: i32,
+    pub is_quarantine: bool,
+    pub out_of_service: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +15,8 @@ pub struct CreateEnclosureRequest {
     pub name: String,
     pub enclosure_type: String,
     pub capacity: i32,
+    #[serde(default)]
+    pub is_quarantine: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,4 +24,5 @@ pub struct UpdateEnclosureRequest {
    pub name: String,
     pub enclosure_type: String,
     pub capacity: i32,
+    pub is_quarantine: Option<bool>,
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LiveEvent {
+    pub id: u64,
+    pub event: String,
+    pub entity: String,
+    pub enclosure_id: Option<i64>,
+    pub previous_enclosure_id: Option<i64>,
+    pub occurred_at: NaiveDateTime,
+    pub data: serde_json::Value
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventStreamQuery {
+    pub entity: Option<String>,
+    pub enclosure_id: Option<i64>
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeletedEntity {
+    pub id: i64,
+    pub animal_id: Option<i64>,
+    pub enclosure_id: Option<i64>
+}
@@ -1,24 +1,85 @@
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDateTime;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Feeding {
     pub id: i64,
     pub animal_id: i64,
     pub staff_id: i64,
-    pub feeding_time: NaiveDateTime
+    pub feeding_time: NaiveDateTime,
+    pub food_item_id: Option<i64>,
+    pub quantity: Option<f64>
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateFeedingRequest {
     pub animal_id: i64,
-    pub staff_id: i64,
-    pub feeding_time: NaiveDateTime
+    pub staff_id: Option<i64>,
+    pub feeding_time: NaiveDateTime,
+    pub food_item_id: Option<i64>,
+    pub quantity: Option<f64>
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateFeedingRequest {
    pub animal_id: i64,
     pub staff_id: i64,
-    pub feeding_time: NaiveDateTime
-}
\ No newline at end of file
+    pub feeding_time: NaiveDateTime,
+    pub food_item_id: Option<i64>,
+    pub quantity: Option<f64>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFeedingQuery {
+    pub force: Option<bool>
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedingConflict {
+    pub message: String,
+    pub conflicting_feeding: Feeding
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FeedingRule {
+    pub id: i64,
+    pub species: Option<String>,
+    pub animal_id: Option<i64>,
+    pub min_interval_minutes: i64,
+    pub max_interval_hours: i64
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFeedingRuleRequest {
+    pub species: Option<String>,
+    pub animal_id: Option<i64>,
+    pub min_interval_minutes: i64,
+    pub max_interval_hours: i64
+}
+
+#[derive(Debug, Serialize)]
+pub struct DoubleFeeding {
+    pub animal_id: i64,
+    pub first_feeding_id: i64,
+    pub second_feeding_id: i64,
+    pub first_feeding_time: NaiveDateTime,
+    pub second_feeding_time: NaiveDateTime,
+    pub min_interval_minutes: i64,
+    pub keeper_ids: Vec<i64>
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnfedAnimal {
+    pub animal_id: i64,
+    pub name: String,
+    pub species: String,
+    pub last_feeding_time: Option<NaiveDateTime>,
+    pub max_interval_hours: i64,
+    pub keeper_ids: Vec<i64>
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedingAnomalies {
+    pub double_feedings: Vec<DoubleFeeding>,
+    pub unfed_animals: Vec<UnfedAnimal>
+}
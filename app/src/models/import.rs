@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    pub format: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub error: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    pub entity: String,
+    pub dry_run: bool,
+    pub rows: usize,
+    pub imported: usize,
+    pub errors: Vec<ImportRowError>
+}
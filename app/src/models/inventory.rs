@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Supplier {
+    pub id: i64,
+    pub name: String,
+    pub phone: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSupplierRequest {
+    pub name: String,
+    pub phone: String
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FoodItem {
+    pub id: i64,
+    pub name: String,
+    pub unit: String,
+    pub reorder_threshold: f64
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFoodItemRequest {
+    pub name: String,
+    pub unit: String,
+    pub reorder_threshold: f64
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StockLot {
+    pub id: i64,
+    pub food_item_id: i64,
+    pub supplier_id: Option<i64>,
+    pub quantity: f64,
+    pub received_date: NaiveDateTime,
+    pub expiry_date: Option<NaiveDateTime>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateStockLotRequest {
+    pub food_item_id: i64,
+    pub supplier_id: Option<i64>,
+    pub quantity: f64,
+    pub received_date: NaiveDateTime,
+    pub expiry_date: Option<NaiveDateTime>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FeedingSchedule {
+    pub id: i64,
+    pub animal_id: i64,
+    pub food_item_id: i64,
+    pub quantity: f64,
+    pub feedings_per_day: i32
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFeedingScheduleRequest {
+    pub animal_id: i64,
+    pub food_item_id: i64,
+    pub quantity: f64,
+    pub feedings_per_day: i32
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct StockLevel {
+    pub food_item_id: i64,
+    pub name: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub reorder_threshold: f64
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct StockForecast {
+    pub food_item_id: i64,
+    pub name: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub daily_consumption: f64,
+    pub days_remaining: Option<f64>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpiringQuery {
+    pub days: Option<i64>
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Inspection {
+    pub id: i64,
+    pub enclosure_id: i64,
+    pub inspection_date: NaiveDate,
+    pub inspector_id: Option<i64>,
+    pub notes: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InspectionItem {
+    pub id: i64,
+    pub inspection_id: i64,
+    pub item: String,
+    pub passed: bool,
+    pub notes: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInspectionItemRequest {
+    pub item: String,
+    pub passed: bool,
+    pub notes: Option<String>,
+    pub severity: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInspectionRequest {
+    pub inspection_date: NaiveDate,
+    pub inspector_id: Option<i64>,
+    pub notes: Option<String>,
+    pub items: Vec<CreateInspectionItemRequest>
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct InspectionDetails {
+    #[serde(flatten)]
+    pub inspection: Inspection,
+    pub items: Vec<InspectionItem>,
+    pub defects: Vec<Defect>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Defect {
+    pub id: i64,
+    pub enclosure_id: i64,
+    pub inspection_id: Option<i64>,
+    pub description: String,
+    pub severity: String,
+    pub status: String,
+    pub reported_date: NaiveDate,
+    pub resolved_date: Option<NaiveDate>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDefectRequest {
+    pub description: String,
+    pub severity: String,
+    pub reported_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DefectStatusRequest {
+    pub status: String,
+    pub resolved_date: Option<NaiveDate>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DefectListQuery {
+    pub status: Option<String>
+}
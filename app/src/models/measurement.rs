@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Measurement {
+    pub id: i64,
+    pub animal_id: i64,
+    pub weight_kg: Option<f64>,
+    pub body_condition_score: Option<i32>,
+    pub length_cm: Option<f64>,
+    pub taken_by: i64,
+    pub taken_at: NaiveDateTime
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateMeasurementRequest {
+    pub weight_kg: Option<f64>,
+    pub body_condition_score: Option<i32>,
+    pub length_cm: Option<f64>,
+    pub taken_by: i64,
+    pub taken_at: NaiveDateTime
+}
+
+#[derive(Debug, Serialize)]
+pub struct WeightTrend {
+    pub animal_id: i64,
+    pub latest: Option<Measurement>,
+    pub change_30d_pct: Option<f64>,
+    pub change_90d_pct: Option<f64>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WeightLossThreshold {
+    pub id: i64,
+    pub species: String,
+    pub max_loss_pct: f64,
+    pub window_days: i64
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWeightLossThresholdRequest {
+    pub species: String,
+    pub max_loss_pct: f64,
+    pub window_days: i64
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct WeightLossAlert {
+    pub animal_id: i64,
+    pub name: String,
+    pub species: String,
+    pub latest_weight_kg: f64,
+    pub change_pct: f64,
+    pub max_loss_pct: f64,
+    pub window_days: i64,
+    pub keeper_ids: Vec<i64>
+}
@@ -1,13 +1,17 @@
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDateTime;
+use crate::models::animal::Animal;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct MedicalTreatment {
     pub id: i64,
     pub animal_id: i64,
     pub staff_id: i64,
     pub date: NaiveDateTime,
-    pub description: String
+    pub description: String,
+    pub procedure_type: Option<String>,
+    pub follow_up_date: Option<NaiveDateTime>,
+    pub outcome: Option<String>
 }
 
 #[derive(Debug, Deserialize)]
@@ -15,7 +19,14 @@ pub struct CreateMedicalTreatmentRequest {
     pub animal_id: i64,
     pub staff_id: i64,
     pub date: NaiveDateTime,
-    pub description: String
+    pub description: String,
+    pub procedure_type: Option<String>,
+    pub follow_up_date: Option<NaiveDateTime>,
+    pub outcome: Option<String>,
+    #[serde(default)]
+    pub diagnoses: Vec<CreateDiagnosisRequest>,
+    #[serde(default)]
+    pub medications: Vec<CreateMedicationRequest>
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,5 +34,74 @@ pub struct UpdateMedicalTreatmentRequest {
     pub animal_id: i64,
     pub staff_id: i64,
     pub date: NaiveDateTime,
+    pub description: String,
+    pub procedure_type: Option<String>,
+    pub follow_up_date: Option<NaiveDateTime>,
+    pub outcome: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Diagnosis {
+    pub id: i64,
+    pub treatment_id: i64,
+    pub code: String,
+    pub description: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDiagnosisRequest {
+    pub code: String,
+    pub description: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateDiagnosisRequest {
+    pub code: String,
     pub description: String
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Medication {
+    pub id: i64,
+    pub treatment_id: i64,
+    pub name: String,
+    pub dose: f64,
+    pub unit: String,
+    pub route: String,
+    pub frequency: String,
+    pub duration_days: i32
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateMedicationRequest {
+    pub name: String,
+    pub dose: f64,
+    pub unit: String,
+    pub route: String,
+    pub frequency: String,
+    pub duration_days: i32
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMedicationRequest {
+    pub name: String,
+    pub dose: f64,
+    pub unit: String,
+    pub route: String,
+    pub frequency: String,
+    pub duration_days: i32
+}
+
+#[derive(Debug, Serialize)]
+pub struct MedicalTreatmentDetails {
+    #[serde(flatten)]
+    pub treatment: MedicalTreatment,
+    pub diagnoses: Vec<Diagnosis>,
+    pub medications: Vec<Medication>
+}
+
+#[derive(Debug, Serialize)]
+pub struct MedicalRecord {
+    pub animal: Animal,
+    pub treatments: Vec<MedicalTreatmentDetails>
+}
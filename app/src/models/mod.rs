@@ -2,4 +2,25 @@ pub mod animal;
 pub mod enclosure;
 pub mod feeding;
 pub mod medical_treatment;
-pub mod staff;
\ No newline at end of file
+pub mod staff;
+pub mod inventory;
+pub mod vaccination;
+pub mod measurement;
+pub mod quarantine;
+pub mod animal_event;
+pub mod breeding;
+pub mod studbook;
+pub mod shift;
+pub mod setting;
+pub mod assignment;
+pub mod certification;
+pub mod report;
+pub mod task;
+pub mod maintenance;
+pub mod sensor;
+pub mod alert;
+pub mod webhook;
+pub mod event;
+pub mod import;
+pub mod export;
+pub mod backup;
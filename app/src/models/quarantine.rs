@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Quarantine {
+    pub id: i64,
+    pub animal_id: i64,
+    pub treatment_id: Option<i64>,
+    pub reason: String,
+    pub start_date: NaiveDateTime,
+    pub required_days: i64,
+    pub end_date: Option<NaiveDateTime>,
+    pub cleared_by: Option<i64>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartQuarantineRequest {
+    pub animal_id: i64,
+    pub treatment_id: Option<i64>,
+    pub reason: String,
+    pub start_date: NaiveDateTime,
+    pub required_days: i64,
+    pub enclosure_id: Option<i64>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearQuarantineRequest {
+    pub staff_id: i64,
+    pub end_date: Option<NaiveDateTime>
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ActiveQuarantine {
+    #[serde(flatten)]
+    pub quarantine: Quarantine,
+    pub animal_name: String,
+    pub enclosure_id: i64,
+    pub earliest_release: Option<NaiveDateTime>
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+
+#[derive(Debug, Deserialize)]
+pub struct WorkloadQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub period: Option<String>,
+    pub format: Option<String>
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct StaffWorkload {
+    pub staff_id: i64,
+    pub staff_name: String,
+    pub role: String,
+    pub period_start: NaiveDate,
+    pub feedings: i64,
+    pub treatments: i64,
+    pub total: i64
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct RoleWorkload {
+    pub role: String,
+    pub staff_count: i64,
+    pub feedings: i64,
+    pub treatments: i64,
+    pub total: i64
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct HourlyWorkload {
+    pub hour: i64,
+    pub feedings: i64,
+    pub treatments: i64,
+    pub total: i64
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct AnimalsHandled {
+    pub staff_id: i64,
+    pub staff_name: String,
+    pub role: String,
+    pub animals_handled: i64,
+    pub feedings: i64,
+    pub treatments: i64
+}
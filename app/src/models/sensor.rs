@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SensorReading {
+    pub enclosure_id: i64,
+    pub sensor_id: String,
+    pub metric: String,
+    pub value: f64,
+    pub recorded_at: NaiveDateTime
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IngestReadingsRequest {
+    pub readings: Vec<SensorReading>
+}
+
+#[derive(Debug, Serialize)]
+pub struct IngestSummary {
+    pub received: usize,
+    pub stored: usize,
+    pub duplicates: usize,
+    pub alerts: Vec<SensorAlert>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SensorAlert {
+    pub id: i64,
+    pub enclosure_id: i64,
+    pub sensor_id: String,
+    pub metric: String,
+    pub value: f64,
+    pub recorded_at: NaiveDateTime,
+    pub species: String,
+    pub min_value: f64,
+    pub max_value: f64
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SensorAlertQuery {
+    pub enclosure_id: Option<i64>,
+    pub metric: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SpeciesRange {
+    pub id: i64,
+    pub species: String,
+    pub metric: String,
+    pub min_value: f64,
+    pub max_value: f64
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSpeciesRangeRequest {
+    pub species: String,
+    pub metric: String,
+    pub min_value: f64,
+    pub max_value: f64
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadingsQuery {
+    pub metric: String,
+    pub bucket: Option<String>,
+    pub sensor_id: Option<String>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ReadingBucket {
+    pub bucket_start: NaiveDateTime,
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+    pub readings: i64
+}
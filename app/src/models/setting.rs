@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Setting {
+    pub key: String,
+    pub value: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSettingRequest {
+    pub value: String
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use chrono::{NaiveDate, NaiveDateTime};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Shift {
+    pub id: i64,
+    pub staff_id: i64,
+    pub start_time: NaiveDateTime,
+    pub end_time: NaiveDateTime,
+    pub enclosure_id: Option<i64>,
+    pub area: Option<String>,
+    pub required_role: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShiftRequest {
+    pub staff_id: i64,
+    pub start_time: NaiveDateTime,
+    pub end_time: NaiveDateTime,
+    pub enclosure_id: Option<i64>,
+    pub area: Option<String>,
+    pub required_role: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShiftQuery {
+    pub force: Option<bool>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShiftListQuery {
+    pub staff_id: Option<i64>
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ShiftConflict {
+    pub kind: String,
+    pub staff_id: i64,
+    pub shift_id: i64,
+    pub other_shift_id: i64,
+    pub rest_hours: Option<f64>
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShiftConflictResponse {
+    pub message: String,
+    pub conflicts: Vec<ShiftConflict>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RotaQuery {
+    pub week: Option<NaiveDate>
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotaShift {
+    #[serde(flatten)]
+    pub shift: Shift,
+    pub staff_name: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotaDay {
+    pub date: NaiveDate,
+    pub shifts: Vec<RotaShift>
+}
+
+#[derive(Debug, Serialize)]
+pub struct Rota {
+    pub week_start: NaiveDate,
+    pub days: Vec<RotaDay>
+}
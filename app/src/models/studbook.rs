@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StudbookEntry {
+    pub studbook_number: Option<String>,
+    pub name: String,
+    pub species: String,
+    pub sex: String,
+    pub birth_date: Option<NaiveDate>,
+    pub sire: Option<String>,
+    pub dam: Option<String>,
+    pub locations: Vec<LocationEntry>,
+    pub death_date: Option<NaiveDate>
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LocationEntry {
+    pub date: NaiveDate,
+    pub event: String,
+    pub institution: Option<String>
+}
+
+// שורה בקובץ CSV - היסטוריית המיקומים כטקסט "תאריך:אירוע:מוסד;..."
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StudbookCsvRow {
+    pub studbook_number: Option<String>,
+    pub name: Option<String>,
+    pub species: String,
+    pub sex: String,
+    pub birth_date: Option<NaiveDate>,
+    pub sire: Option<String>,
+    pub dam: Option<String>,
+    pub location_history: Option<String>,
+    pub death_date: Option<NaiveDate>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StudbookExportQuery {
+    pub species: Option<String>,
+    pub format: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StudbookImportQuery {
+    pub format: Option<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct StudbookImportSummary {
+    pub records: usize,
+    pub parents_linked: usize
+}
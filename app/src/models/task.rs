@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use chrono::{NaiveDate, NaiveDateTime};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Task {
+    pub id: i64,
+    pub title: String,
+    pub task_type: String,
+    pub description: Option<String>,
+    pub enclosure_id: Option<i64>,
+    pub animal_id: Option<i64>,
+    pub assignee_id: Option<i64>,
+    pub due_date: Option<NaiveDate>,
+    pub priority: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTaskRequest {
+    pub title: String,
+    pub task_type: String,
+    pub description: Option<String>,
+    pub enclosure_id: Option<i64>,
+    pub animal_id: Option<i64>,
+    pub assignee_id: Option<i64>,
+    pub due_date: Option<NaiveDate>,
+    pub priority: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTaskRequest {
+    pub title: String,
+    pub task_type: String,
+    pub description: Option<String>,
+    pub enclosure_id: Option<i64>,
+    pub animal_id: Option<i64>,
+    pub assignee_id: Option<i64>,
+    pub due_date: Option<NaiveDate>,
+    pub priority: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskStatusRequest {
+    pub status: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskListQuery {
+    pub status: Option<String>,
+    pub assignee_id: Option<i64>,
+    pub enclosure_id: Option<i64>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TaskComment {
+    pub id: i64,
+    pub task_id: i64,
+    pub staff_id: i64,
+    pub body: String,
+    pub created_at: NaiveDateTime
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTaskCommentRequest {
+    pub staff_id: i64,
+    pub body: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskDetails {
+    #[serde(flatten)]
+    pub task: Task,
+    pub comments: Vec<TaskComment>
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct OverdueTask {
+    #[serde(flatten)]
+    pub task: Task,
+    pub days_overdue: i64
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Vaccine {
+    pub id: i64,
+    pub name: String,
+    pub description: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateVaccineRequest {
+    pub name: String,
+    pub description: String
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VaccineInterval {
+    pub id: i64,
+    pub vaccine_id: i64,
+    pub species: String,
+    pub interval_days: i64
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateVaccineIntervalRequest {
+    pub species: String,
+    pub interval_days: i64
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Vaccination {
+    pub id: i64,
+    pub animal_id: i64,
+    pub vaccine_id: i64,
+    pub staff_id: i64,
+    pub treatment_id: i64,
+    pub date: NaiveDateTime,
+    pub batch_number: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateVaccinationRequest {
+    pub animal_id: i64,
+    pub vaccine_id: i64,
+    pub staff_id: i64,
+    pub date: NaiveDateTime,
+    pub batch_number: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DueQuery {
+    pub within: Option<String>
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct DueVaccination {
+    pub animal_id: i64,
+    pub animal_name: String,
+    pub species: String,
+    pub vaccine_id: i64,
+    pub vaccine_name: String,
+    pub last_vaccination: Option<NaiveDateTime>,
+    pub due_date: Option<NaiveDateTime>,
+    pub status: String
+}
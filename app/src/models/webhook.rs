@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub enabled: bool,
+    pub created_at: NaiveDateTime
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub secret: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub secret: Option<String>,
+    pub enabled: bool
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub event_type: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i64,
+    pub last_status_code: Option<i64>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub redelivery_of: Option<i64>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryListQuery {
+    pub subscription_id: Option<i64>,
+    pub status: Option<String>,
+    pub event_type: Option<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookEvent<'a, T: Serialize> {
+    pub event: &'a str,
+    pub occurred_at: NaiveDateTime,
+    pub data: &'a T
+}
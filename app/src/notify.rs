@@ -0,0 +1,184 @@
+// משלוח התראות החוצה - כל ערוץ מממש Notifier; HTTP(S) דרך ureq, SMTP מינימלי בלי תלויות נוספות
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use crate::models::alert::{Alert, AlertChannel};
+
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// A delivery channel for alerts.
+pub trait Notifier: Send + Sync {
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendFuture<'a>;
+}
+
+pub struct LogNotifier;
+
+pub struct WebhookNotifier {
+    pub url: String,
+}
+
+pub struct SmtpNotifier {
+    pub server: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl Notifier for LogNotifier {
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendFuture<'a> {
+        Box::pin(async move {
+            println!("🔔 [{}] {}: {}", alert.severity, alert.rule, alert.message);
+            Ok(())
+        })
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::to_string(alert).map_err(|e| e.to_string())?;
+            match post_json(&self.url, &body, &[]).await? {
+                status if (200..300).contains(&status) => Ok(()),
+                status => Err(format!("Webhook answered HTTP {}", status)),
+            }
+        })
+    }
+}
+
+impl Notifier for SmtpNotifier {
+    fn send<'a>(&'a self, alert: &'a Alert) -> SendFuture<'a> {
+        Box::pin(async move {
+            let subject = format!("[{}] {}", alert.severity, alert.rule);
+            let body = format!("{}\r\n\r\nAlert #{} raised at {}", alert.message, alert.id, alert.created_at);
+            send_mail(&self.server, &self.from, &self.to, &subject, &body).await
+        })
+    }
+}
+
+pub fn notifier_for(channel: &AlertChannel, smtp_server: &str, smtp_from: &str) -> Box<dyn Notifier> {
+    let target = channel.target.clone().unwrap_or_default();
+    match channel.kind.as_str() {
+        "webhook" => Box::new(WebhookNotifier { url: target }),
+        "smtp" => Box::new(SmtpNotifier { server: smtp_server.to_string(), from: smtp_from.to_string(), to: target }),
+        _ => Box::new(LogNotifier),
+    }
+}
+
+/// Error text for targets that fail `is_webhook_url`, shared by alert channels and webhook subscriptions.
+pub const WEBHOOK_URL_RULE: &str = "must be an https:// URL (http:// is allowed only for localhost)";
+
+/// Whether `value` contains control characters such as CR or LF, which would let it inject lines into a protocol.
+pub fn has_control_chars(value: &str) -> bool {
+    value.chars().any(char::is_control)
+}
+
+/// Whether `url` can be used as a webhook target: https://, or plain http:// to a loopback host for local stand-ins.
+pub fn is_webhook_url(url: &str) -> bool {
+    if url.chars().any(|c| c.is_control() || c.is_whitespace()) {
+        return false;
+    }
+    if url.starts_with("https://") {
+        return true;
+    }
+    let Some(rest) = url.strip_prefix("http://") else {
+        return false;
+    };
+    // בלי user@host - אחרת http://localhost@example.com היה עובר
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if authority.contains('@') {
+        return false;
+    }
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    ["localhost", "127.0.0.1", "::1"].contains(&host.to_lowercase().as_str())
+}
+
+/// POSTs a JSON body to a URL accepted by `is_webhook_url` and returns the response status code.
+pub async fn post_json(url: &str, body: &str, headers: &[(&str, String)]) -> Result<u16, String> {
+    if !is_webhook_url(url) {
+        return Err(format!("Webhook url {}", WEBHOOK_URL_RULE));
+    }
+    let mut request = ureq::AgentBuilder::new()
+        .timeout(IO_TIMEOUT)
+        .redirects(0)
+        .build()
+        .post(url)
+        .set("Content-Type", "application/json");
+    for (name, value) in headers {
+        request = request.set(name, value);
+    }
+
+    // ureq חוסם, אז רץ בחוט נפרד; גוף התשובה לא נקרא - מספיקה שורת הסטטוס
+    let body = body.to_string();
+    let sent = tokio::task::spawn_blocking(move || match request.send_string(&body) {
+        Ok(response) => Ok(response.status()),
+        Err(ureq::Error::Status(status, _)) => Ok(status),
+        Err(e) => Err(e.to_string()),
+    });
+    sent.await.map_err(|e| format!("Webhook request aborted: {}", e))?
+}
+
+async fn smtp_reply<R: AsyncBufReadExt + Unpin>(reader: &mut R, expected: u16) -> Result<(), String> {
+    // תשובה מרובת שורות: 250-... עד שורה שבה אחרי הקוד יש רווח
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.map_err(|e| e.to_string())? == 0 {
+            return Err("SMTP server closed the connection".to_string());
+        }
+        if line.len() < 4 || line.as_bytes()[3] != b'-' {
+            let code: u16 = line.get(..3).and_then(|code| code.parse().ok()).unwrap_or_default();
+            if code != expected {
+                return Err(format!("SMTP server answered: {}", line.trim_end()));
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Sends a plain-text mail through an SMTP server without authentication or TLS.
+pub async fn send_mail(server: &str, from: &str, to: &str, subject: &str, body: &str) -> Result<(), String> {
+    // כתובת עם CR/LF הייתה מוסיפה פקודות SMTP או כותרות משלה
+    if has_control_chars(from) || has_control_chars(to) || has_control_chars(subject) {
+        return Err("Mail addresses and subject must not contain control characters".to_string());
+    }
+    let dialog = async {
+        let stream = TcpStream::connect(server).await.map_err(|e| format!("Cannot reach {}: {}", server, e))?;
+        let (read_half, mut write_half) = stream.into_split();
+        let mut reader = BufReader::new(read_half);
+
+        smtp_reply(&mut reader, 220).await?;
+        for (command, expected) in [
+            ("EHLO zoo.local".to_string(), 250),
+            (format!("MAIL FROM:<{}>", from), 250),
+            (format!("RCPT TO:<{}>", to), 250),
+            ("DATA".to_string(), 354),
+        ] {
+            write_half.write_all(format!("{}\r\n", command).as_bytes()).await.map_err(|e| e.to_string())?;
+            smtp_reply(&mut reader, expected).await?;
+        }
+
+        // שורה שמתחילה בנקודה מוכפלת כדי לא לסיים את ההודעה מוקדם
+        let text: Vec<String> = body
+            .lines()
+            .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+            .collect();
+        let message = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\n\r\n{}\r\n.\r\n",
+            from,
+            to,
+            subject,
+            text.join("\r\n")
+        );
+        write_half.write_all(message.as_bytes()).await.map_err(|e| e.to_string())?;
+        smtp_reply(&mut reader, 250).await?;
+        write_half.write_all(b"QUIT\r\n").await.map_err(|e| e.to_string())?;
+        Ok(())
+    };
+    timeout(IO_TIMEOUT, dialog).await.unwrap_or_else(|_| Err(format!("Timed out talking to {}", server)))
+}
@@ -0,0 +1,15 @@
+use actix_web::{web, Scope};
+use crate::controllers::alert_controller;
+
+pub fn alert_routes() -> Scope {
+    web::scope("/alerts")
+        .service(alert_controller::get_alerts)
+        .service(alert_controller::run_alerts_now)
+        .service(alert_controller::create_alert_channel)
+        .service(alert_controller::get_alert_channels)
+        .service(alert_controller::delete_alert_channel)
+        .service(alert_controller::test_alert_channel)
+        .service(alert_controller::get_alert_by_id)
+        .service(alert_controller::acknowledge_alert)
+        .service(alert_controller::resolve_alert)
+}
@@ -1,12 +1,26 @@
 use actix_web::{web, Scope};
-use crate::controllers::animal_controller;
+use crate::controllers::{animal_controller, assignment_controller, breeding_controller, measurement_controller, medical_treatment_controller};
 
 pub fn animal_routes() -> Scope {
     web::scope("/animals")
         .service(animal_controller::create_animal)
         .service(animal_controller::get_animals)
+        .service(animal_controller::lookup_animal)
         .service(animal_controller::get_animal_by_id)
         .service(animal_controller::update_animal)
         .service(animal_controller::delete_animal)
         .service(animal_controller::get_animals_older_than)
+        .service(animal_controller::create_animal_event)
+        .service(animal_controller::get_animal_events)
+        .service(animal_controller::create_animal_identifier)
+        .service(animal_controller::get_animal_identifiers)
+        .service(assignment_controller::create_animal_assignment)
+        .service(assignment_controller::get_animal_keepers)
+        .service(assignment_controller::end_animal_assignment)
+        .service(breeding_controller::get_pedigree)
+        .service(breeding_controller::get_offspring)
+        .service(medical_treatment_controller::get_animal_medical_record)
+        .service(measurement_controller::create_measurement)
+        .service(measurement_controller::get_measurements)
+        .service(measurement_controller::get_measurement_trends)
 }
\ No newline at end of file
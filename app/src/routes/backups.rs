@@ -0,0 +1,9 @@
+use actix_web::{web, Scope};
+use crate::controllers::backup_controller;
+
+pub fn backup_routes() -> Scope {
+    web::scope("/admin/backups")
+        .service(backup_controller::create_backup_now)
+        .service(backup_controller::get_backups)
+        .service(backup_controller::verify_backup_file)
+}
@@ -0,0 +1,9 @@
+use actix_web::{web, Scope};
+use crate::controllers::breeding_controller;
+
+pub fn breeding_routes() -> Scope {
+    web::scope("/breeding")
+        .service(breeding_controller::create_breeding_pair)
+        .service(breeding_controller::get_breeding_pairs)
+        .service(breeding_controller::evaluate_pairing)
+}
@@ -1,5 +1,5 @@
 use actix_web::{web, Scope};
-use crate::controllers::enclosure_controller;
+use crate::controllers::{assignment_controller, enclosure_controller, maintenance_controller, sensor_controller};
 
 pub fn enclosure_routes() -> Scope {
     web::scope("/enclosures")
@@ -9,4 +9,13 @@ pub fn enclosure_routes() -> Scope {
         .service(enclosure_controller::get_enclosure_by_id)
         .service(enclosure_controller::update_enclosure)
         .service(enclosure_controller::delete_enclosure)
+        .service(assignment_controller::create_enclosure_assignment)
+        .service(assignment_controller::get_enclosure_keepers)
+        .service(assignment_controller::end_enclosure_assignment)
+        .service(maintenance_controller::create_inspection)
+        .service(maintenance_controller::get_inspections)
+        .service(maintenance_controller::create_defect)
+        .service(maintenance_controller::get_defects)
+        .service(maintenance_controller::update_defect_status)
+        .service(sensor_controller::get_enclosure_readings)
 }
\ No newline at end of file
@@ -0,0 +1,7 @@
+use actix_web::{web, Scope};
+use crate::controllers::event_controller;
+
+pub fn event_routes() -> Scope {
+    web::scope("/events")
+        .service(event_controller::stream_events)
+}
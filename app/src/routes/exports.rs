@@ -0,0 +1,7 @@
+use actix_web::{web, Scope};
+use crate::controllers::export_controller;
+
+pub fn export_routes() -> Scope {
+    web::scope("/export")
+        .service(export_controller::export_bundle)
+}
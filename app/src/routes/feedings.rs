@@ -5,6 +5,10 @@ pub fn feeding_routes() -> Scope {
     web::scope("/feedings")
         .service(feeding_controller::create_feeding)
         .service(feeding_controller::get_feedings)
+        .service(feeding_controller::create_feeding_rule)
+        .service(feeding_controller::get_feeding_rules)
+        .service(feeding_controller::delete_feeding_rule)
+        .service(feeding_controller::get_feeding_anomalies)
         .service(feeding_controller::get_feeding_by_id)
         .service(feeding_controller::update_feeding)
         .service(feeding_controller::delete_feeding)
@@ -0,0 +1,11 @@
+use actix_web::{web, Scope};
+use crate::controllers::import_controller;
+
+// קובץ ייבוא יכול להיות גדול בהרבה מגוף בקשה רגיל
+const MAX_IMPORT_BYTES: usize = 20 * 1024 * 1024;
+
+pub fn import_routes() -> Scope {
+    web::scope("/import")
+        .app_data(web::PayloadConfig::new(MAX_IMPORT_BYTES))
+        .service(import_controller::import_data)
+}
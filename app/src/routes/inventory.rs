@@ -0,0 +1,17 @@
+use actix_web::{web, Scope};
+use crate::controllers::inventory_controller;
+
+pub fn inventory_routes() -> Scope {
+    web::scope("/inventory")
+        .service(inventory_controller::create_supplier)
+        .service(inventory_controller::get_suppliers)
+        .service(inventory_controller::create_food_item)
+        .service(inventory_controller::get_food_items)
+        .service(inventory_controller::create_stock_lot)
+        .service(inventory_controller::get_stock_lots)
+        .service(inventory_controller::create_feeding_schedule)
+        .service(inventory_controller::get_feeding_schedules)
+        .service(inventory_controller::get_low_stock)
+        .service(inventory_controller::get_expiring_stock)
+        .service(inventory_controller::get_stock_forecast)
+}
@@ -0,0 +1,9 @@
+use actix_web::{web, Scope};
+use crate::controllers::measurement_controller;
+
+pub fn measurement_routes() -> Scope {
+    web::scope("/measurements")
+        .service(measurement_controller::set_weight_loss_threshold)
+        .service(measurement_controller::get_weight_loss_thresholds)
+        .service(measurement_controller::get_weight_loss_alerts)
+}
@@ -0,0 +1,13 @@
+use actix_web::{web, Scope};
+use crate::controllers::vaccination_controller;
+
+pub fn medical_routes() -> Scope {
+    web::scope("/medical")
+        .service(vaccination_controller::create_vaccine)
+        .service(vaccination_controller::get_vaccines)
+        .service(vaccination_controller::get_vaccine_intervals)
+        .service(vaccination_controller::set_vaccine_interval)
+        .service(vaccination_controller::create_vaccination)
+        .service(vaccination_controller::get_vaccinations)
+        .service(vaccination_controller::get_due_vaccinations)
+}
@@ -8,5 +8,11 @@ pub fn medical_treatment_routes() -> Scope {
         .service(medical_treatment_controller::get_medical_treatment_by_id)
         .service(medical_treatment_controller::update_medical_treatment)
         .service(medical_treatment_controller::delete_medical_treatment)
+        .service(medical_treatment_controller::add_diagnosis)
+        .service(medical_treatment_controller::add_medication)
+        .service(medical_treatment_controller::update_diagnosis)
+        .service(medical_treatment_controller::delete_diagnosis)
+        .service(medical_treatment_controller::update_medication)
+        .service(medical_treatment_controller::delete_medication)
         .service(medical_treatment_controller::get_medical_treatment_by_staff)
 }
\ No newline at end of file
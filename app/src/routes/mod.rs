@@ -2,4 +2,20 @@ pub mod animals;
 pub mod enclosures;
 pub mod staff;
 pub mod feedings;
-pub mod medical_treatments;
\ No newline at end of file
+pub mod medical_treatments;
+pub mod inventory;
+pub mod medical;
+pub mod measurements;
+pub mod quarantine;
+pub mod breeding;
+pub mod studbook;
+pub mod settings;
+pub mod reports;
+pub mod tasks;
+pub mod readings;
+pub mod alerts;
+pub mod webhooks;
+pub mod events;
+pub mod imports;
+pub mod exports;
+pub mod backups;
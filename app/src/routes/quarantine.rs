@@ -0,0 +1,10 @@
+use actix_web::{web, Scope};
+use crate::controllers::quarantine_controller;
+
+pub fn quarantine_routes() -> Scope {
+    web::scope("/quarantine")
+        .service(quarantine_controller::start_quarantine)
+        .service(quarantine_controller::get_quarantines)
+        .service(quarantine_controller::get_active_quarantines)
+        .service(quarantine_controller::clear_quarantine)
+}
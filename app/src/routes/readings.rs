@@ -0,0 +1,10 @@
+use actix_web::{web, Scope};
+use crate::controllers::sensor_controller;
+
+pub fn reading_routes() -> Scope {
+    web::scope("/readings")
+        .service(sensor_controller::ingest_readings)
+        .service(sensor_controller::upsert_species_range)
+        .service(sensor_controller::get_species_ranges)
+        .service(sensor_controller::get_sensor_alerts)
+}
@@ -0,0 +1,10 @@
+use actix_web::{web, Scope};
+use crate::controllers::report_controller;
+
+pub fn report_routes() -> Scope {
+    web::scope("/reports")
+        .service(report_controller::get_staff_workload)
+        .service(report_controller::get_role_workload)
+        .service(report_controller::get_hourly_workload)
+        .service(report_controller::get_animals_handled)
+}
@@ -0,0 +1,8 @@
+use actix_web::{web, Scope};
+use crate::controllers::settings_controller;
+
+pub fn settings_routes() -> Scope {
+    web::scope("/settings")
+        .service(settings_controller::get_settings)
+        .service(settings_controller::update_setting)
+}
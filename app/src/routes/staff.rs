@@ -1,12 +1,26 @@
 use actix_web::{web, Scope};
-use crate::controllers::staff_controller;
+use crate::controllers::{assignment_controller, certification_controller, shift_controller, staff_controller};
 
 pub fn staff_routes() -> Scope {
     web::scope("/staff")
         .service(staff_controller::create_staff)
         .service(staff_controller::get_staff)
+        .service(shift_controller::create_shift)
+        .service(shift_controller::get_shifts)
+        .service(shift_controller::get_shift_conflicts)
+        .service(shift_controller::delete_shift)
+        .service(shift_controller::get_rota)
+        .service(certification_controller::get_expiring_certifications)
+        .service(certification_controller::create_certification_requirement)
+        .service(certification_controller::get_certification_requirements)
+        .service(certification_controller::delete_certification_requirement)
+        .service(certification_controller::delete_certification)
         .service(staff_controller::get_staff_by_id)
         .service(staff_controller::update_staff)
         .service(staff_controller::delete_staff)
         .service(staff_controller::get_staff_by_role)
+        .service(assignment_controller::get_staff_enclosures)
+        .service(assignment_controller::get_staff_animals)
+        .service(certification_controller::create_certification)
+        .service(certification_controller::get_staff_certifications)
 }
\ No newline at end of file
@@ -0,0 +1,8 @@
+use actix_web::{web, Scope};
+use crate::controllers::studbook_controller;
+
+pub fn studbook_routes() -> Scope {
+    web::scope("/studbook")
+        .service(studbook_controller::export_studbook)
+        .service(studbook_controller::import_studbook_file)
+}
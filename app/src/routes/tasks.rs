@@ -0,0 +1,16 @@
+use actix_web::{web, Scope};
+use crate::controllers::task_controller;
+
+pub fn task_routes() -> Scope {
+    web::scope("/tasks")
+        .service(task_controller::create_task)
+        .service(task_controller::get_tasks)
+        .service(task_controller::get_my_tasks)
+        .service(task_controller::get_overdue_tasks)
+        .service(task_controller::get_task_by_id)
+        .service(task_controller::update_task)
+        .service(task_controller::delete_task)
+        .service(task_controller::update_task_status)
+        .service(task_controller::create_task_comment)
+        .service(task_controller::get_task_comments)
+}
@@ -0,0 +1,13 @@
+use actix_web::{web, Scope};
+use crate::controllers::webhook_controller;
+
+pub fn webhook_routes() -> Scope {
+    web::scope("/webhooks")
+        .service(webhook_controller::create_webhook)
+        .service(webhook_controller::get_webhooks)
+        .service(webhook_controller::get_deliveries)
+        .service(webhook_controller::get_delivery_by_id)
+        .service(webhook_controller::redeliver)
+        .service(webhook_controller::update_webhook)
+        .service(webhook_controller::delete_webhook)
+}
@@ -0,0 +1,72 @@
+// בדיקות של ה-API מול מסד בזיכרון שמתמלא מנתוני הדמו
+use actix_web::{test, web, App};
+use app::db::migrate;
+use app::fixtures::{generate_fixtures, FixtureOptions, FixtureSummary};
+use app::routes::{animals::animal_routes, feedings::feeding_routes, medical_treatments::medical_treatment_routes};
+use serde_json::{json, Value};
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+
+async fn seeded_pool() -> (SqlitePool, FixtureSummary) {
+    // מסד בזיכרון חי רק בתוך החיבור שלו, לכן חיבור אחד
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    migrate(&pool).await.unwrap();
+    let summary = generate_fixtures(&pool, &FixtureOptions::preset("small").unwrap()).await.unwrap();
+    (pool, summary)
+}
+
+#[actix_web::test]
+async fn animals_are_listed_as_json_and_csv() {
+    let (pool, summary) = seeded_pool().await;
+    let app = test::init_service(App::new().app_data(web::Data::new(pool)).service(animal_routes())).await;
+
+    let animals: Vec<Value> = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/animals/").to_request()).await;
+    assert_eq!(animals.len(), summary.animals);
+
+    let response = test::call_service(&app, test::TestRequest::get().uri("/animals/?format=csv").to_request()).await;
+    assert!(response.status().is_success());
+    assert!(response.headers().get("content-type").unwrap().to_str().unwrap().starts_with("text/csv"));
+    let body = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    assert_eq!(body.lines().count(), summary.animals + 1);
+}
+
+#[actix_web::test]
+async fn second_feeding_within_the_minimum_interval_is_rejected() {
+    let (pool, _) = seeded_pool().await;
+    let app = test::init_service(App::new().app_data(web::Data::new(pool)).service(feeding_routes())).await;
+
+    let feedings: Vec<Value> = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/feedings/").to_request()).await;
+    let fed = &feedings[0];
+    let feeding_time = chrono::NaiveDateTime::parse_from_str(fed["feeding_time"].as_str().unwrap(), "%Y-%m-%dT%H:%M:%S").unwrap();
+    let again = json!({
+        "animal_id": fed["animal_id"],
+        "staff_id": fed["staff_id"],
+        "feeding_time": feeding_time + chrono::TimeDelta::minutes(5),
+    });
+
+    let request = test::TestRequest::post().uri("/feedings/").set_json(&again).to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), 409);
+    let conflict: Value = test::read_body_json(response).await;
+    assert_eq!(conflict["conflicting_feeding"]["id"], fed["id"]);
+
+    let request = test::TestRequest::post().uri("/feedings/?force=true").set_json(&again).to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), 201);
+}
+
+#[actix_web::test]
+async fn treatment_for_a_missing_animal_is_not_found() {
+    let (pool, summary) = seeded_pool().await;
+    let app = test::init_service(App::new().app_data(web::Data::new(pool)).service(medical_treatment_routes())).await;
+
+    let treatments: Vec<Value> = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/medical_treatments/").to_request()).await;
+    assert_eq!(treatments.len(), summary.medical_treatments);
+
+    let treatment = json!({
+        "animal_id": summary.animals as i64 + 1,
+        "staff_id": treatments[0]["staff_id"],
+        "date": treatments[0]["date"],
+        "description": "Check-up",
+    });
+    let request = test::TestRequest::post().uri("/medical_treatments/").set_json(&treatment).to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), 404);
+}